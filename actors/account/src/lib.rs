@@ -2,16 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use fvm_ipld_blockstore::Blockstore;
-use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::{Address, Protocol};
-use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
+use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
 
 use fil_actors_runtime::builtin::singletons::SYSTEM_ACTOR_ADDR;
-use fil_actors_runtime::cbor;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
-use fil_actors_runtime::{actor_error, ActorError};
+use fil_actors_runtime::{actor_dispatch, actor_error, method_hash, ActorError};
 
 pub use self::state::State;
 
@@ -24,11 +21,13 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 // * Updated to specs-actors commit: 845089a6d2580e46055c24415a6c32ee688e5186 (v3.0.0)
 
 /// Account actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     PubkeyAddress = 2,
+    // Methods exported to user actors, under their FRC-0042 numbers.
+    PubkeyAddressExported = method_hash!("PubkeyAddress"),
 }
 
 /// Account Actor
@@ -65,25 +64,11 @@ impl Actor {
 }
 
 impl ActorCode for Actor {
-    fn invoke_method<BS, RT>(
-        rt: &mut RT,
-        method: MethodNum,
-        params: &RawBytes,
-    ) -> Result<RawBytes, ActorError>
-    where
-        BS: Blockstore,
-        RT: Runtime<BS>,
-    {
-        match FromPrimitive::from_u64(method) {
-            Some(Method::Constructor) => {
-                Self::constructor(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::PubkeyAddress) => {
-                let addr = Self::pubkey_address(rt)?;
-                Ok(RawBytes::serialize(addr)?)
-            }
-            None => Err(actor_error!(unhandled_message; "Invalid method")),
+    actor_dispatch! {
+        restricted Method {
+            Constructor => constructor(Address),
+            PubkeyAddress => pubkey_address() -> Address,
+            PubkeyAddressExported => pubkey_address() -> Address,
         }
     }
 }
@@ -1,12 +1,13 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use fil_actor_account::{testing::check_state_invariants, Actor as AccountActor, State};
+use fil_actor_account::{testing::check_state_invariants, Actor as AccountActor, Method, State};
 use fil_actors_runtime::builtin::SYSTEM_ACTOR_ADDR;
 use fil_actors_runtime::test_utils::*;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
 
 fn check_state(rt: &MockRuntime) {
     let test_address = Address::new_id(1000);
@@ -23,10 +24,9 @@ macro_rules! account_tests {
 
                 let mut rt = MockRuntime {
                     receiver: fvm_shared::address::Address::new_id(100),
-                    caller: SYSTEM_ACTOR_ADDR.clone(),
-                    caller_type: SYSTEM_ACTOR_CODE_ID.clone(),
                     ..Default::default()
                 };
+                rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
                 rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
 
                 if exit_code.is_success() {
@@ -74,3 +74,28 @@ account_tests! {
         ExitCode::USR_ILLEGAL_ARGUMENT
     ),
 }
+
+#[test]
+fn exported_methods() {
+    let mut rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    let addr = Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap();
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    rt.call::<AccountActor>(Method::Constructor as MethodNum, &RawBytes::serialize(addr).unwrap())
+        .unwrap();
+
+    // actors that aren't builtin can only call the FRC-0042 method numbers
+    rt.set_caller(make_builtin(b"user actor"), Address::new_id(1000));
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<AccountActor>(Method::PubkeyAddress as MethodNum, &RawBytes::default()),
+    );
+    rt.expect_validate_caller_any();
+    let pk: Address = rt
+        .call::<AccountActor>(Method::PubkeyAddressExported as MethodNum, &RawBytes::default())
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    assert_eq!(pk, addr);
+    rt.verify();
+}
@@ -0,0 +1,91 @@
+use anyhow::anyhow;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::Cbor;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser;
+use fvm_shared::econ::TokenAmount;
+use num_traits::Zero;
+
+/// Identifier of an asset that can be moved across subnets.
+///
+/// Wrapped assets need to be registered with the same ID in every
+/// subnet of the hierarchy they are bridged to.
+pub type AssetID = u64;
+
+/// ID of the native token of the hierarchy.
+pub const NATIVE_ASSET: AssetID = 0;
+
+/// Wrapped asset registered in the SCA.
+///
+/// The token actor of the asset needs to accept mint and burn
+/// requests from the SCA (see `ext::token`).
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct Asset {
+    pub id: AssetID,
+    pub symbol: String,
+    pub token: Address,
+}
+impl Cbor for Asset {}
+
+/// Amount of a wrapped asset.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AssetAmount {
+    pub asset: AssetID,
+    #[serde(with = "bigint_ser")]
+    pub value: TokenAmount,
+}
+impl Cbor for AssetAmount {}
+
+/// return the amount of an asset in a list of amounts.
+pub fn amount_of(amounts: &[AssetAmount], asset: AssetID) -> TokenAmount {
+    amounts.iter().find(|a| a.asset == asset).map(|a| a.value.clone()).unwrap_or_default()
+}
+
+/// add some value of an asset to a list of amounts.
+pub(crate) fn add_amount(amounts: &mut Vec<AssetAmount>, asset: AssetID, value: &TokenAmount) {
+    match amounts.iter_mut().find(|a| a.asset == asset) {
+        Some(a) => a.value += value,
+        None => amounts.push(AssetAmount { asset, value: value.clone() }),
+    }
+}
+
+/// subtract some value of an asset from a list of amounts, removing
+/// the asset from the list if there is nothing left.
+pub(crate) fn sub_amount(
+    amounts: &mut Vec<AssetAmount>,
+    asset: AssetID,
+    value: &TokenAmount,
+) -> anyhow::Result<()> {
+    if value.is_zero() {
+        return Ok(());
+    }
+    let index = amounts
+        .iter()
+        .position(|a| a.asset == asset && &a.value >= value)
+        .ok_or_else(|| anyhow!("not enough funds of asset {} to subtract", asset))?;
+    amounts[index].value -= value;
+    if amounts[index].value.is_zero() {
+        amounts.remove(index);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asset::*;
+
+    #[test]
+    fn test_amounts() {
+        let mut amounts = Vec::new();
+        add_amount(&mut amounts, 1, &TokenAmount::from(5));
+        add_amount(&mut amounts, 2, &TokenAmount::from(3));
+        add_amount(&mut amounts, 1, &TokenAmount::from(2));
+        assert_eq!(amount_of(&amounts, 1), TokenAmount::from(7));
+        assert_eq!(amount_of(&amounts, 3), TokenAmount::zero());
+
+        sub_amount(&mut amounts, 1, &TokenAmount::from(7)).unwrap();
+        assert_eq!(amounts.len(), 1);
+        assert!(sub_amount(&mut amounts, 2, &TokenAmount::from(4)).is_err());
+        assert!(sub_amount(&mut amounts, 1, &TokenAmount::from(1)).is_err());
+    }
+}
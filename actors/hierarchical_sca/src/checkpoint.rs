@@ -4,13 +4,15 @@ use cid::multihash::MultihashDigest;
 use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::{serde_bytes, to_vec, Cbor};
-use fvm_shared::address::SubnetID;
+use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::bigint::bigint_ser;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 
+use crate::asset::{add_amount, AssetAmount, AssetID, NATIVE_ASSET};
+use crate::merkle::{self, MerkleProof};
 use crate::tcid::{TCid, TLink};
-use crate::CrossMsgs;
+use crate::{CrossMsgs, MAX_CHILD_CHECKS};
 
 #[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct Checkpoint {
@@ -84,6 +86,28 @@ impl Checkpoint {
             }
             None => self.data.cross_msgs.push(meta),
         }
+        self.data.update_roots()
+    }
+
+    /// return the inclusion proof of a crossmsg meta against `data.cross_msgs_root`.
+    pub fn prove_crossmsg_meta(&self, meta: &CrossMsgMeta) -> anyhow::Result<MerkleProof> {
+        merkle::prove(&self.data.cross_msgs, meta)
+    }
+
+    /// return the inclusion proof of a child checkpoint against `data.children_root`.
+    pub fn prove_child_check(&self, child: &ChildCheck) -> anyhow::Result<MerkleProof> {
+        merkle::prove(&self.data.children, child)
+    }
+
+    /// set the tipset key and state root of the subnet anchored by the checkpoint.
+    pub fn set_tipset(&mut self, tipset: &[Cid], state_root: Cid) {
+        self.data.tip_set = tipset.iter().flat_map(|c| c.to_bytes()).collect();
+        self.data.state_root = state_root;
+    }
+
+    /// commit the snapshot of balances in the subnet at the time of the checkpoint.
+    pub fn set_balances(&mut self, balances: &[SnapshotBalance]) -> anyhow::Result<()> {
+        self.data.balances_root = merkle::merkle_root(balances)?;
         Ok(())
     }
 
@@ -94,15 +118,24 @@ impl Checkpoint {
         match self.data.children.iter_mut().find(|m| commit.source() == &m.source) {
             // if there is already a structure for that child
             Some(ck) => {
-                // check if the cid already exists
-                if ck.checks.iter().any(|c| c == &cid) {
+                if ck.checks.len() >= MAX_CHILD_CHECKS {
                     return Err(anyhow!(
-                        "child checkpoint being committed already exists for source {}",
+                        "checkpoint already includes {} checkpoints from source {}",
+                        MAX_CHILD_CHECKS,
                         commit.source()
                     ));
                 }
-                // and if not append to list of child checkpoints.
-                ck.checks.push(cid);
+                // the list is kept sorted by cid, so the lookup for duplicates
+                // doesn't scan it, and the cid is inserted where it belongs.
+                match ck.checks.binary_search_by(|c| c.cid().cmp(&cid.cid())) {
+                    Ok(_) => {
+                        return Err(anyhow!(
+                            "child checkpoint being committed already exists for source {}",
+                            commit.source()
+                        ))
+                    }
+                    Err(pos) => ck.checks.insert(pos, cid),
+                }
             }
             None => {
                 // if none, new structure for source
@@ -111,7 +144,7 @@ impl Checkpoint {
                     .push(ChildCheck { source: commit.data.source.clone(), checks: vec![cid] });
             }
         };
-        Ok(())
+        self.data.update_roots()
     }
 }
 
@@ -124,6 +157,19 @@ pub struct CheckData {
     pub prev_check: TCid<TLink<Checkpoint>>,
     pub children: Vec<ChildCheck>,
     pub cross_msgs: Vec<CrossMsgMeta>,
+    /// Merkle root of `children`, so light clients can verify the inclusion
+    /// of a child checkpoint without the whole checkpoint.
+    #[serde(with = "serde_bytes")]
+    pub children_root: Vec<u8>,
+    /// Merkle root of `cross_msgs`.
+    #[serde(with = "serde_bytes")]
+    pub cross_msgs_root: Vec<u8>,
+    /// Merkle root of the balances in the subnet at the time of the checkpoint,
+    /// used to recover funds if the subnet stops committing checkpoints.
+    #[serde(with = "serde_bytes")]
+    pub balances_root: Vec<u8>,
+    /// State root of the subnet in the tipset of the checkpoint.
+    pub state_root: Cid,
 }
 impl CheckData {
     pub fn new(id: SubnetID, epoch: ChainEpoch) -> Self {
@@ -134,8 +180,54 @@ impl CheckData {
             prev_check: TCid::default(),
             children: Vec::new(),
             cross_msgs: Vec::new(),
+            children_root: Vec::new(),
+            cross_msgs_root: Vec::new(),
+            balances_root: Vec::new(),
+            state_root: Cid::default(),
         }
     }
+
+    /// return the cids of the blocks in the tipset key of the checkpoint.
+    pub fn tipset_cids(&self) -> anyhow::Result<Vec<Cid>> {
+        let mut cids = Vec::new();
+        let mut reader = self.tip_set.as_slice();
+        while !reader.is_empty() {
+            cids.push(Cid::read_bytes(&mut reader)?);
+        }
+        Ok(cids)
+    }
+
+    /// check that the checkpoint anchors a well-formed tipset and
+    /// state root of the subnet.
+    pub fn check_tipset(&self) -> anyhow::Result<()> {
+        if self.tip_set.is_empty() {
+            return Err(anyhow!("checkpoint doesn't include a tipset"));
+        }
+        self.tipset_cids().map_err(|e| anyhow!("malformed tipset key in checkpoint: {}", e))?;
+        if self.state_root == Cid::default() {
+            return Err(anyhow!("checkpoint doesn't include a state root"));
+        }
+        Ok(())
+    }
+
+    /// recompute the Merkle roots for the children and cross_msgs of the checkpoint.
+    pub fn update_roots(&mut self) -> anyhow::Result<()> {
+        self.children_root = merkle::merkle_root(&self.children)?;
+        self.cross_msgs_root = merkle::merkle_root(&self.cross_msgs)?;
+        Ok(())
+    }
+
+    /// check that the Merkle roots committed are consistent with the
+    /// children and cross_msgs of the checkpoint.
+    pub fn check_roots(&self) -> anyhow::Result<()> {
+        if self.children_root != merkle::merkle_root(&self.children)? {
+            return Err(anyhow!("children root not consistent with checkpoint children"));
+        }
+        if self.cross_msgs_root != merkle::merkle_root(&self.cross_msgs)? {
+            return Err(anyhow!("cross-msgs root not consistent with checkpoint cross-msgs"));
+        }
+        Ok(())
+    }
 }
 impl Cbor for CheckData {}
 
@@ -147,6 +239,8 @@ pub struct CrossMsgMeta {
     pub nonce: u64,
     #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
+    /// Value of wrapped assets included in the messages.
+    pub assets: Vec<AssetAmount>,
 }
 impl Cbor for CrossMsgMeta {}
 
@@ -158,6 +252,14 @@ impl CrossMsgMeta {
     pub fn set_nonce(&mut self, nonce: u64) {
         self.nonce = nonce;
     }
+
+    /// add the value of an asset to the msgmeta.
+    pub fn add_value(&mut self, asset: AssetID, value: &TokenAmount) {
+        match asset {
+            NATIVE_ASSET => self.value += value,
+            _ => add_amount(&mut self.assets, asset, value),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -167,6 +269,15 @@ pub struct ChildCheck {
 }
 impl Cbor for ChildCheck {}
 
+/// Balance of an address in a subnet included in the balances snapshot of a checkpoint.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct SnapshotBalance {
+    pub addr: Address,
+    #[serde(with = "bigint_ser")]
+    pub value: TokenAmount,
+}
+impl Cbor for SnapshotBalance {}
+
 /// CheckpointEpoch returns the epoch of the next checkpoint
 /// that needs to be signed
 ///
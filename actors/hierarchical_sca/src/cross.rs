@@ -13,6 +13,7 @@ use fvm_shared::MethodNum;
 use fvm_shared::METHOD_SEND;
 use std::path::Path;
 
+use crate::asset::{AssetID, NATIVE_ASSET};
 use crate::checkpoint::CrossMsgMeta;
 use crate::tcid::{TAmt, TCid, TLink};
 
@@ -32,6 +33,8 @@ pub struct StorableMsg {
     #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
     pub nonce: u64,
+    /// Asset of the value of the message.
+    pub asset: AssetID,
 }
 impl Cbor for StorableMsg {}
 
@@ -44,10 +47,21 @@ impl Default for StorableMsg {
             params: RawBytes::default(),
             value: TokenAmount::from(0),
             nonce: 0,
+            asset: NATIVE_ASSET,
         }
     }
 }
 
+/// Envelope in which cross-messages are delivered to actors that need to
+/// authenticate their sender, like multisigs with signers in other subnets.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CrossMsgEnvelope {
+    /// Hierarchical address of the sender of the cross-message.
+    pub from: Address,
+    pub params: RawBytes,
+}
+impl Cbor for CrossMsgEnvelope {}
+
 #[derive(PartialEq, Eq)]
 pub enum HCMsgType {
     Unknown = 0,
@@ -70,7 +84,7 @@ impl StorableMsg {
             sig_addr,
         )?;
         let from = Address::new_hierarchical(sub_id, &BURNT_FUNDS_ACTOR_ADDR)?;
-        Ok(Self { from, to, method: METHOD_SEND, params: RawBytes::default(), value, nonce })
+        Ok(Self { from, to, method: METHOD_SEND, value, nonce, ..Default::default() })
     }
 
     pub fn new_fund_msg(
@@ -111,6 +125,11 @@ impl StorableMsg {
     }
 }
 
+/// Checks if `sub` is `root` or a subnet in its subtree.
+pub fn is_in_subtree(root: &SubnetID, sub: &SubnetID) -> bool {
+    matches!(sub.common_parent(root), Some((_, cp)) if &cp == root)
+}
+
 pub fn is_bottomup(from: &SubnetID, to: &SubnetID) -> bool {
     let index = match from.common_parent(&to) {
         Some((ind, _)) => ind,
@@ -0,0 +1,69 @@
+//! Events emitted by the SCA, so that indexers can follow cross-messages,
+//! checkpoints and atomic executions without diffing its state.
+
+use cid::Cid;
+use fil_actors_runtime::runtime::{ActorEvent, EventBuilder};
+use fil_actors_runtime::ActorError;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+
+use crate::exec::ExecStatus;
+use crate::StorableMsg;
+
+/// Funds injected into `subnet` with the top-down message `msg`.
+pub fn fund(subnet: &SubnetID, msg: &StorableMsg) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new().typ("fund").field_indexed("subnet", subnet).field("msg", msg).build()
+}
+
+/// Funds released to the parent with the bottom-up message `msg`.
+pub fn release(msg: &StorableMsg) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new().typ("release").field("msg", msg).build()
+}
+
+/// Cross-message `msg` sent to `destination`.
+pub fn send_cross(destination: &SubnetID, msg: &StorableMsg) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new()
+        .typ("send-cross")
+        .field_indexed("destination", destination)
+        .field("msg", msg)
+        .build()
+}
+
+/// Checkpoint `checkpoint` of `subnet` committed, burning `burnt` for its
+/// bottom-up messages.
+pub fn checkpoint_commit(
+    subnet: &SubnetID,
+    epoch: ChainEpoch,
+    checkpoint: &Cid,
+    burnt: &TokenAmount,
+) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new()
+        .typ("checkpoint-commit")
+        .field_indexed("subnet", subnet)
+        .field("epoch", &epoch)
+        .field("checkpoint", checkpoint)
+        .field("burnt", &BigIntSer(burnt))
+        .build()
+}
+
+/// Atomic execution `exec` initialized.
+pub fn atomic_exec_init(exec: &Cid) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new().typ("atomic-exec-init").field_indexed("exec", exec).build()
+}
+
+/// Output of atomic execution `exec` submitted by `submitter`, leaving the
+/// execution in `status`.
+pub fn atomic_exec_submit(
+    exec: &Cid,
+    submitter: &Address,
+    status: ExecStatus,
+) -> Result<ActorEvent, ActorError> {
+    EventBuilder::new()
+        .typ("atomic-exec-submit")
+        .field_indexed("exec", exec)
+        .field("submitter", submitter)
+        .field("status", &status)
+        .build()
+}
@@ -5,11 +5,10 @@ use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
 use fvm_ipld_encoding::repr::*;
 use fvm_ipld_encoding::{tuple::*, Cbor};
 use fvm_shared::address::{Address, SubnetID};
-use std::convert::TryFrom;
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
 
 use crate::{atomic, StorableMsg};
-use actor_primitives::taddress::{Hierarchical, TAddress, TAddressKey, ID};
+use actor_primitives::taddress::{AnyRawAddr, Hierarchical, TAddress, TAddressKey, ID};
 use actor_primitives::tcid::{TAmt, TCid, THamt, TLink};
 
 /// Status of an atomic execution
@@ -33,17 +32,14 @@ pub struct AtomicExec {
     params: AtomicExecParams,
     /// Map that tracks the output submitted by every party involved in the
     /// execution.
-    submitted: HashMap<StringifiedAddr, Cid>,
+    submitted: HashMap<TAddressKey<ID>, Cid>,
     /// Status of the execution.
     status: ExecStatus,
 }
 impl Cbor for AtomicExec {}
 
-/// The serialization of Address doesn't support
-/// undefined addresses. To overcome this problem
-/// in order to be able to use addresses as keys of a hashmap
-/// we use their string format (thus this type).
-type StringifiedAddr = String;
+/// A hierarchical address as provided by users.
+pub type HierarchicalAddr = TAddressKey<Hierarchical<AnyRawAddr>>;
 
 /// A hierarchical address resolved to an ID.
 pub type HierarchicalId = TAddressKey<Hierarchical<ID>>;
@@ -52,7 +48,7 @@ impl AtomicExec {
     pub fn new(params: AtomicExecParams) -> Self {
         AtomicExec {
             params,
-            submitted: HashMap::<StringifiedAddr, Cid>::new(),
+            submitted: HashMap::<TAddressKey<ID>, Cid>::new(),
             status: ExecStatus::Initialized,
         }
     }
@@ -60,11 +56,11 @@ impl AtomicExec {
         self.status
     }
 
-    pub fn submitted(&self) -> &HashMap<StringifiedAddr, Cid> {
+    pub fn submitted(&self) -> &HashMap<TAddressKey<ID>, Cid> {
         &self.submitted
     }
 
-    pub fn submitted_mut(&mut self) -> &mut HashMap<StringifiedAddr, Cid> {
+    pub fn submitted_mut(&mut self) -> &mut HashMap<TAddressKey<ID>, Cid> {
         &mut self.submitted
     }
 
@@ -93,7 +89,7 @@ impl Cbor for SubmitExecParams {}
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct AtomicExecParamsRaw {
     pub msgs: Vec<StorableMsg>,
-    pub inputs: HashMap<StringifiedAddr, LockedStateInfo>,
+    pub inputs: HashMap<HierarchicalAddr, LockedStateInfo>,
 }
 impl Cbor for AtomicExecParamsRaw {}
 
@@ -158,17 +154,11 @@ impl AtomicExecParamsRaw {
     {
         let mut out = HashMap::new();
         for (key, val) in self.inputs.into_iter() {
-            let addr = Address::from_str(&key)?;
-            let sn = addr.subnet()?;
-            let addr = addr.raw_addr()?;
-            let id_addr = match rt.resolve_address(&addr) {
-                Some(id) => id,
-                None => return Err(anyhow!("couldn't resolve id address in exec input")),
-            };
-            // Update with id_addr and subnet
-            let sn_addr = Address::new_hierarchical(&sn, &id_addr)?;
-            let addr = TAddressKey(TAddress::try_from(sn_addr)?);
-            out.insert(addr, val);
+            let addr = key
+                .0
+                .resolve_id(rt)
+                .map_err(|e| anyhow!("couldn't resolve id address in exec input: {}", e))?;
+            out.insert(TAddressKey(addr), val);
         }
         Ok(AtomicExecParams { msgs: self.msgs, inputs: out })
     }
@@ -182,15 +172,9 @@ impl AtomicExecParamsRaw {
             msgs_array.batch_set(self.msgs.clone()).map_err(|e| e.into())
         })?;
 
-        meta.inputs_cid.update(&store, |input_map| {
-            for (k, v) in self.inputs.iter() {
-                let addr = Address::from_str(k)?;
-                input_map.set(addr.to_bytes().into(), v.clone()).map_err(|e| {
-                    e.downcast_wrap(format!("failed to set input map to compute exec cid"))
-                })?;
-            }
-            Ok(())
-        })?;
+        meta.inputs_cid
+            .batch_set(&store, self.inputs.iter().map(|(k, v)| (*k.0.addr(), v.clone())))
+            .map_err(|e| e.downcast_wrap("failed to set input map to compute exec cid"))?;
 
         let meta_cid: TCid<TLink<AtomicExecParamsMeta>> = TCid::new_link(&store, &meta)?;
 
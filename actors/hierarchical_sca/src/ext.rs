@@ -2,6 +2,32 @@ pub mod account {
     pub const PUBKEY_ADDRESS_METHOD: u64 = 2;
 }
 
+pub mod token {
+    use fvm_ipld_encoding::tuple::*;
+    use fvm_shared::address::Address;
+    use fvm_shared::bigint::bigint_ser;
+    use fvm_shared::econ::TokenAmount;
+
+    /// Methods the token actors of wrapped assets need to expose
+    /// so the SCA can mint and burn them.
+    pub const MINT_METHOD: u64 = 2;
+    pub const BURN_METHOD: u64 = 3;
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+    pub struct MintParams {
+        pub to: Address,
+        #[serde(with = "bigint_ser")]
+        pub value: TokenAmount,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+    pub struct BurnParams {
+        pub owner: Address,
+        #[serde(with = "bigint_ser")]
+        pub value: TokenAmount,
+    }
+}
+
 pub mod reward {
     use fvm_ipld_encoding::tuple::*;
     use fvm_shared::address::Address;
@@ -1,14 +1,16 @@
-use actor_primitives::taddress::TAddress;
+use actor_primitives::taddress::{TAddress, TAddressKey};
+use actor_primitives::tcid::TCid;
 use actor_primitives::{atomic, tcid};
 use cid::Cid;
 use exec::{
     is_addr_in_exec, is_common_parent, AtomicExec, AtomicExecParamsRaw, ExecStatus, LockedOutput,
     SubmitExecParams, SubmitOutput,
 };
+use fil_actors_runtime::dispatch::RawParams;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_error, cbor, ActorDowncast, ActorError, BURNT_FUNDS_ACTOR_ADDR, REWARD_ACTOR_ADDR,
-    SCA_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, cbor, method_hash, ActorDowncast, ActorError,
+    BURNT_FUNDS_ACTOR_ADDR, REWARD_ACTOR_ADDR, SCA_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
@@ -17,33 +19,41 @@ use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::bigint::Zero;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_CONSTRUCTOR;
 use fvm_shared::METHOD_SEND;
-use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
-pub use self::checkpoint::{Checkpoint, CrossMsgMeta};
-pub use self::cross::{is_bottomup, CrossMsgs, HCMsgType, StorableMsg};
+pub use self::asset::{Asset, AssetAmount, AssetID, NATIVE_ASSET};
+pub use self::checkpoint::{Checkpoint, CrossMsgMeta, SnapshotBalance};
+pub use self::cross::{
+    is_bottomup, is_in_subtree, CrossMsgEnvelope, CrossMsgs, HCMsgType, StorableMsg,
+};
 pub use self::state::*;
 pub use self::subnet::*;
 pub use self::types::*;
+pub use self::validators::{Validator, ValidatorSet};
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+pub mod asset;
 pub mod checkpoint;
 mod cross;
+pub mod events;
 pub mod exec;
 #[doc(hidden)]
 pub mod ext;
+pub mod merkle;
 mod state;
 pub mod subnet;
+pub mod testing;
 mod types;
+pub mod validators;
 
 /// SCA actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     /// Constructor for Storage Power Actor
@@ -59,6 +69,30 @@ pub enum Method {
     ApplyMessage = 10,
     InitAtomicExec = 11,
     SubmitAtomicExec = 12,
+    ClaimFunds = 13,
+    ListSubnets = 14,
+    GetSubnet = 15,
+    RegisterAsset = 16,
+    SetValidators = 17,
+    ValidatorSet = 18,
+    NetworkName = 19,
+    // Methods exported to user actors, under their FRC-0042 numbers.
+    RegisterExported = method_hash!("Register"),
+    AddStakeExported = method_hash!("AddStake"),
+    ReleaseStakeExported = method_hash!("ReleaseStake"),
+    KillExported = method_hash!("Kill"),
+    CommitChildCheckpointExported = method_hash!("CommitChildCheckpoint"),
+    FundExported = method_hash!("Fund"),
+    ReleaseExported = method_hash!("Release"),
+    SendCrossExported = method_hash!("SendCross"),
+    InitAtomicExecExported = method_hash!("InitAtomicExec"),
+    SubmitAtomicExecExported = method_hash!("SubmitAtomicExec"),
+    ClaimFundsExported = method_hash!("ClaimFunds"),
+    ListSubnetsExported = method_hash!("ListSubnets"),
+    GetSubnetExported = method_hash!("GetSubnet"),
+    SetValidatorsExported = method_hash!("SetValidators"),
+    ValidatorSetExported = method_hash!("ValidatorSet"),
+    NetworkNameExported = method_hash!("NetworkName"),
 }
 
 /// Subnet Coordinator Actor
@@ -81,16 +115,29 @@ impl Actor {
 
     /// Register is called by subnet actors to put the required collateral
     /// and register the subnet to the hierarchy.
-    fn register<BS, RT>(rt: &mut RT) -> Result<SubnetID, ActorError>
+    fn register<BS, RT>(rt: &mut RT, params: RawParams) -> Result<SubnetID, ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
         rt.validate_immediate_caller_type(std::iter::once(&Type::Subnet))?;
+        // subnet actors deployed before `RegisterParams` was introduced call it without params
+        let params: Option<RegisterParams> =
+            if params.0.is_empty() { None } else { Some(cbor::deserialize_params(&params.0)?) };
+        if let Some(params) = &params {
+            if params.name.len() > MAX_SUBNET_NAME_LENGTH {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "subnet name longer than {} bytes",
+                    MAX_SUBNET_NAME_LENGTH
+                ));
+            }
+        }
         let subnet_addr = rt.message().caller();
         let mut shid = SubnetID::default();
         rt.transaction(|st: &mut State, rt| {
             shid = SubnetID::new(&st.network_name, subnet_addr);
+            let params = params.unwrap_or_else(|| RegisterParams::legacy(&shid));
             let sub = st.get_subnet(rt.store(), &shid).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
             })?;
@@ -103,7 +150,7 @@ impl Actor {
                     ))
                 }
                 None => {
-                    st.register_subnet(rt, &shid).map_err(|e| {
+                    st.register_subnet(rt, &shid, params).map_err(|e| {
                         e.downcast_default(
                             ExitCode::USR_ILLEGAL_ARGUMENT,
                             "Failed to register subnet",
@@ -139,6 +186,7 @@ impl Actor {
             })?;
             match sub {
                 Some(mut sub) => {
+                    sub.check_status(Method::AddStake)?;
                     sub.add_stake(rt, st, &val).map_err(|e| {
                         e.downcast_default(
                             ExitCode::USR_ILLEGAL_STATE,
@@ -182,6 +230,7 @@ impl Actor {
             })?;
             match sub {
                 Some(mut sub) => {
+                    sub.check_status(Method::ReleaseStake)?;
                     if sub.stake < params.value {
                         return Err(actor_error!(
                             illegal_state,
@@ -220,6 +269,10 @@ impl Actor {
 
     /// Kill propagates the kill signal from a subnet actor to unregister it from th
     /// hierarchy.
+    ///
+    /// The first call moves the subnet to `Terminating`, giving it a grace period to
+    /// commit its final checkpoints. Once the grace period is over, a second call kills
+    /// the subnet and releases its stake.
     fn kill<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
     where
         BS: Blockstore,
@@ -235,24 +288,42 @@ impl Actor {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
             })?;
             match sub {
-                Some(sub) => {
+                Some(mut sub) => {
+                    sub.check_status(Method::Kill)?;
+                    if sub.status != Status::Terminating {
+                        // start the grace period
+                        let epoch = rt.curr_epoch();
+                        st.terminate_subnet(rt.store(), &mut sub, epoch).map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::USR_ILLEGAL_STATE,
+                                "failed to terminate subnet",
+                            )
+                        })?;
+                        return Ok(());
+                    }
+                    if rt.curr_epoch() <= sub.grace_period_end {
+                        return Err(actor_error!(
+                            illegal_state,
+                            "subnet in grace period until epoch {}",
+                            sub.grace_period_end
+                        ));
+                    }
                     if rt.current_balance() < sub.stake {
                         return Err(actor_error!(
                             illegal_state,
                             "something went really wrong! the actor doesn't have enough balance to release"
                         ));
                     }
-                    if sub.circ_supply > TokenAmount::zero() {
+                    if sub.has_supply() {
                         return Err(actor_error!(
                             illegal_state,
                             "cannot kill a subnet that still holds user funds in its circ. supply"
                         ));
                     }
-                    send_val = sub.stake;
-                    // delete subnet
-                    st.rm_subnet(rt.store(), &shid).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
-            })?;
+                    send_val = sub.stake.clone();
+                    st.kill_subnet(rt.store(), &mut sub).map_err(|e| {
+                        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to kill subnet")
+                    })?;
                 }
                 None => {
                     return Err(actor_error!(
@@ -266,7 +337,9 @@ impl Actor {
             Ok(())
         })?;
 
-        rt.send(subnet_addr, METHOD_SEND, RawBytes::default(), send_val.clone())?;
+        if send_val > TokenAmount::zero() {
+            rt.send(subnet_addr, METHOD_SEND, RawBytes::default(), send_val)?;
+        }
         Ok(())
     }
 
@@ -290,19 +363,65 @@ impl Actor {
             ));
         }
 
+        // bound the work performed for the checkpoint
+        if commit.data.cross_msgs.len() > MAX_CHECKPOINT_CROSS_MSGS {
+            return Err(actor_error!(
+                illegal_argument,
+                "checkpoint includes {} cross-msg metas, the maximum is {}",
+                commit.data.cross_msgs.len(),
+                MAX_CHECKPOINT_CROSS_MSGS
+            ));
+        }
+        if commit.data.children.len() > MAX_CHECKPOINT_CHILDREN {
+            return Err(actor_error!(
+                illegal_argument,
+                "checkpoint includes {} children, the maximum is {}",
+                commit.data.children.len(),
+                MAX_CHECKPOINT_CHILDREN
+            ));
+        }
+        if let Some(child) = commit.data.children.iter().find(|c| c.checks.len() > MAX_CHILD_CHECKS)
+        {
+            return Err(actor_error!(
+                illegal_argument,
+                "checkpoint includes {} checkpoints from child {}, the maximum is {}",
+                child.checks.len(),
+                child.source,
+                MAX_CHILD_CHECKS
+            ));
+        }
+
+        // check that the roots committed in the checkpoint match its content
+        commit.data.check_roots().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "inconsistent checkpoint roots")
+        })?;
+        // check that the checkpoint anchors the subnet chain
+        commit.data.check_tipset().map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "invalid tipset in checkpoint")
+        })?;
+
         let mut burn_value = TokenAmount::zero();
-        rt.transaction(|st: &mut State, rt| {
+        let event = rt.transaction(|st: &mut State, rt| {
             let shid = SubnetID::new(&st.network_name, subnet_addr);
             let sub = st.get_subnet(rt.store(), &shid).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
             })?;
             match sub {
                 Some(mut sub) => {
-                    // check if subnet active
-                    if sub.status != Status::Active {
+                    // check if subnet can commit checkpoints
+                    sub.check_status(Method::CommitChildCheckpoint)?;
+                    if sub.status == Status::Terminating && rt.curr_epoch() > sub.grace_period_end {
+                        return Err(actor_error!(
+                            illegal_state,
+                            "grace period for terminating subnet finished at epoch {}",
+                            sub.grace_period_end
+                        ));
+                    }
+                    // checkpoints are not accepted once users can recover their funds
+                    if sub.escape_hatch_open(st, rt.curr_epoch()) {
                         return Err(actor_error!(
                             illegal_state,
-                            "can't commit checkpoint for an inactive subnet"
+                            "subnet missed too many checkpoints, escape hatch open"
                         ));
                     }
 
@@ -325,7 +444,7 @@ impl Actor {
                             ));
                         }
                         // check that the previous cid is consistent with the previous one
-                        if commit.prev_check().cid() != prev_checkpoint.cid() {
+                        if commit.prev_check().cid() != sub.prev_checkpoint_cid.cid() {
                             return Err(actor_error!(
                                 illegal_argument,
                                 "previous checkpoint not consistente with previous one"
@@ -333,6 +452,44 @@ impl Actor {
                         }
                     }
 
+                    // the subnet can't roll back to a tipset already committed
+                    let committed = sub
+                        .committed_tipsets
+                        .get(rt.store(), &commit.data.tip_set)
+                        .map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::USR_ILLEGAL_STATE,
+                                "failed to load committed tipsets",
+                            )
+                        })?;
+                    if let Some(epoch) = committed {
+                        return Err(actor_error!(
+                            illegal_argument,
+                            "checkpoint rolls back to the tipset committed in epoch {}",
+                            epoch
+                        ));
+                    }
+
+                    // children only commit the cross-msgs sent from their own subnet
+                    if let Some(mm) = commit.cross_msgs().iter().find(|mm| mm.from != shid) {
+                        return Err(actor_error!(
+                            illegal_argument,
+                            "cross-msg meta from {} committed by subnet {}",
+                            mm.from,
+                            shid
+                        ));
+                    }
+
+                    // charge for the cross-msg metas and children processed
+                    let child_checks: usize =
+                        commit.data.children.iter().map(|c| c.checks.len()).sum();
+                    rt.charge_gas(
+                        "OnCommitChildCheckpoint",
+                        gas::GAS_PER_CROSS_MSG_META * commit.data.cross_msgs.len() as i64
+                            + gas::GAS_PER_CHILD_CHECK * commit.data.children.len() as i64
+                            + gas::GAS_PER_CHILD_CHECK_CID * child_checks as i64,
+                    );
+
                     // process and commit the checkpoint
                     // apply check messages
                     let ap_msgs: HashMap<SubnetID, Vec<&CrossMsgMeta>>;
@@ -358,32 +515,45 @@ impl Actor {
                         )
                     })?;
                     // flush checkpoint
-                    st.flush_checkpoint(rt.store(), &ch).map_err(|e| {
+                    st.flush_checkpoint(rt.store(), &mut ch).map_err(|e| {
                         e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error flushing checkpoint")
                     })?;
 
+                    let event = events::checkpoint_commit(
+                        &shid,
+                        commit.epoch(),
+                        &commit.cid(),
+                        &burn_value,
+                    )?;
+
                     // update prev_check for child
+                    sub.committed_tipsets
+                        .set(rt.store(), &commit.data.tip_set, commit.epoch())
+                        .map_err(|e| {
+                            e.downcast_default(
+                                ExitCode::USR_ILLEGAL_STATE,
+                                "error recording committed tipset",
+                            )
+                        })?;
+                    sub.prev_checkpoint_cid = TCid::from(commit.cid());
                     sub.prev_checkpoint = Some(commit);
+                    sub.last_checkpoint_epoch = rt.curr_epoch();
                     // flush subnet
                     st.flush_subnet(rt.store(), &sub).map_err(|e| {
                         e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error flushing subnet")
                     })?;
+                    Ok(event)
                 }
                 None => {
-                    return Err(actor_error!(
-                        illegal_argument,
-                        "subnet with id {} not registered",
-                        shid
-                    ))
+                    Err(actor_error!(illegal_argument, "subnet with id {} not registered", shid))
                 }
             }
-
-            Ok(())
         })?;
 
         if burn_value > TokenAmount::zero() {
             rt.send(*BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, RawBytes::default(), burn_value.clone())?;
         }
+        rt.emit_event(&event)?;
         Ok(())
     }
 
@@ -409,18 +579,19 @@ impl Actor {
 
         let sig_addr = resolve_secp_bls(rt, rt.message().caller())?;
 
-        rt.transaction(|st: &mut State, rt| {
+        let f_msg = rt.transaction(|st: &mut State, rt| {
             // Create fund message
             let mut f_msg = StorableMsg::new_fund_msg(&params, &sig_addr, value).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error creating fund cross-message")
             })?;
             // Commit top-down message.
-            st.commit_topdown_msg(rt.store(), &mut f_msg).map_err(|e| {
+            st.commit_topdown_msg(rt.store(), &mut f_msg, rt.curr_epoch()).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error committing top-down message")
             })?;
-            Ok(())
+            Ok(f_msg)
         })?;
 
+        rt.emit_event(&events::fund(&params, &f_msg)?)?;
         Ok(())
     }
 
@@ -447,7 +618,7 @@ impl Actor {
         // burn funds that are being released
         rt.send(*BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, RawBytes::default(), value.clone())?;
 
-        rt.transaction(|st: &mut State, rt| {
+        let r_msg = rt.transaction(|st: &mut State, rt| {
             // Create release message
             let r_msg = StorableMsg::new_release_msg(&st.network_name, &sig_addr, value, st.nonce)
                 .map_err(|e| {
@@ -461,9 +632,10 @@ impl Actor {
             st.commit_bottomup_msg(rt.store(), &r_msg, rt.curr_epoch()).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error committing top-down message")
             })?;
-            Ok(())
+            Ok(r_msg)
         })?;
 
+        rt.emit_event(&events::release(&r_msg)?)?;
         Ok(())
     }
 
@@ -480,7 +652,7 @@ impl Actor {
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
+        rt.validate_immediate_caller_type(CALLER_TYPES_SEND_CROSS.iter())?;
         if params.destination == SubnetID::default() {
             return Err(actor_error!(
                 illegal_argument,
@@ -490,9 +662,19 @@ impl Actor {
         let mut msg = params.msg.clone();
         let mut tp = HCMsgType::Unknown;
 
-        // FIXME: Only supporting cross-messages initiated by signable addresses for
-        // now. Consider supporting also send-cross messages initiated by actors.
-        let sig_addr = resolve_secp_bls(rt, rt.message().caller())?;
+        // FIXME: Only supporting cross-messages initiated by signable addresses and
+        // payment channels for now. Consider supporting also send-cross messages
+        // initiated by any actor.
+        let caller = rt.message().caller();
+        let caller_type =
+            rt.get_actor_code_cid(&caller).and_then(|code| rt.resolve_builtin_actor_type(&code));
+        // payment channels don't have a key, so they are identified by their ID address.
+        let sig_addr = if caller_type == Some(Type::PaymentChannel) {
+            caller
+        } else {
+            resolve_secp_bls(rt, caller)?
+        };
+        let mut asset = None;
 
         rt.transaction(|st: &mut State, rt| {
             if params.destination == st.network_name {
@@ -519,6 +701,9 @@ impl Actor {
             ));
             }
         };
+        asset = st.check_asset(rt.store(), msg.asset).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "error checking cross-msg asset")
+            })?;
         tp = st.send_cross(rt.store(), &mut msg, rt.curr_epoch()).map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error committing cross message")
             })?;
@@ -526,8 +711,27 @@ impl Actor {
         Ok(())
         })?;
 
-        if tp == HCMsgType::BottomUp && msg.value > TokenAmount::zero() {
-            rt.send(*BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, RawBytes::default(), msg.value)?;
+        rt.emit_event(&events::send_cross(&params.destination, &msg)?)?;
+        match asset {
+            // wrapped assets are burnt in the source subnet and minted again
+            // in the destination when the message is applied.
+            Some(asset) => {
+                if msg.value > TokenAmount::zero() {
+                    let params =
+                        ext::token::BurnParams { owner: sig_addr, value: msg.value.clone() };
+                    rt.send(
+                        asset.token,
+                        ext::token::BURN_METHOD,
+                        RawBytes::serialize(params)?,
+                        TokenAmount::zero(),
+                    )?;
+                }
+            }
+            None => {
+                if tp == HCMsgType::BottomUp && msg.value > TokenAmount::zero() {
+                    rt.send(*BURNT_FUNDS_ACTOR_ADDR, METHOD_SEND, RawBytes::default(), msg.value)?;
+                }
+            }
         }
         Ok(())
     }
@@ -538,9 +742,10 @@ impl Actor {
     /// be the SystemActor. Cross messages are applied similarly to how rewards are applied once
     /// a block has been validated. This function:
     /// - Determines the type of cross-message.
+    /// - Checks that bottom-up messages come from the subtree of the child that committed them.
     /// - Performs the corresponding state changes.
     /// - And updated the latest nonce applied for future checks.
-    fn apply_msg<BS, RT>(rt: &mut RT, params: StorableMsg) -> Result<(), ActorError>
+    fn apply_msg<BS, RT>(rt: &mut RT, params: ApplyMsgParams) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -551,7 +756,8 @@ impl Actor {
         // picking up the whole state. Is it more efficient in terms of performance and
         // gas usage to check how to apply the message (b-u or t-p) inside rt.transaction?
         let st: State = rt.state()?;
-        let mut msg = params.clone();
+        let curr_epoch = rt.curr_epoch();
+        let mut msg = params.msg;
         let rto = match msg.to.raw_addr() {
             Ok(to) => to,
             Err(_) => {
@@ -564,6 +770,27 @@ impl Actor {
         };
         match msg.apply_type(&st.network_name) {
             Ok(HCMsgType::BottomUp) => {
+                // the sender can't be outside of the subnet the message was committed from
+                let meta_from = match params.meta_from {
+                    Some(from) if from.parent().as_ref() == Some(&st.network_name) => from,
+                    _ => {
+                        return Err(actor_error!(
+                            illegal_argument,
+                            "bottom-up message doesn't come from a child subnet"
+                        ))
+                    }
+                };
+                let sfrom = msg.from.subnet().map_err(|_| {
+                    actor_error!(illegal_argument, "error getting subnet from msg sender")
+                })?;
+                if !is_in_subtree(&meta_from, &sfrom) {
+                    return Err(actor_error!(
+                        forbidden,
+                        "sender {} of bottom-up message is not in subnet {}",
+                        msg.from,
+                        meta_from
+                    ));
+                }
                 // perform state transition
                 rt.transaction(|st: &mut State, rt| {
                     st.bottomup_state_transition(&msg).map_err(|e| {
@@ -573,7 +800,7 @@ impl Actor {
                         )
                     })?;
                     if sto != st.network_name {
-                        st.commit_topdown_msg(rt.store(), &mut msg).map_err(|e| {
+                        st.commit_topdown_msg(rt.store(), &mut msg, curr_epoch).map_err(|e| {
                             e.downcast_default(
                                 ExitCode::USR_ILLEGAL_STATE,
                                 "error committing topdown messages",
@@ -584,20 +811,24 @@ impl Actor {
                 })?;
                 // if directed to current network, execute message.
                 if sto == st.network_name {
-                    // FIXME: Should we handle return in some way?
-                    let _ = rt.send(rto, msg.method, msg.params, msg.value)?;
+                    Self::deliver_msg(rt, &st, rto, msg)?;
                 }
             }
             Ok(HCMsgType::TopDown) => {
                 // Mint funds for SCA so it can direct them accordingly as part of the message.
-                let params =
-                    ext::reward::FundingParams { addr: *SCA_ACTOR_ADDR, value: msg.value.clone() };
-                rt.send(
-                    *REWARD_ACTOR_ADDR,
-                    ext::reward::EXTERNAL_FUNDING_METHOD,
-                    RawBytes::serialize(params)?,
-                    TokenAmount::zero(),
-                )?;
+                // Wrapped assets are minted by their token actor when delivered.
+                if msg.asset == NATIVE_ASSET {
+                    let params = ext::reward::FundingParams {
+                        addr: *SCA_ACTOR_ADDR,
+                        value: msg.value.clone(),
+                    };
+                    rt.send(
+                        *REWARD_ACTOR_ADDR,
+                        ext::reward::EXTERNAL_FUNDING_METHOD,
+                        RawBytes::serialize(params)?,
+                        TokenAmount::zero(),
+                    )?;
+                }
 
                 rt.transaction(|st: &mut State, rt| {
                     // perform nonce state transition
@@ -610,7 +841,7 @@ impl Actor {
                     st.applied_topdown_nonce += 1;
                     // if not directed to subnet go down.
                     if sto != st.network_name {
-                        st.commit_topdown_msg(rt.store(), &mut msg).map_err(|e| {
+                        st.commit_topdown_msg(rt.store(), &mut msg, curr_epoch).map_err(|e| {
                             e.downcast_default(
                                 ExitCode::USR_ILLEGAL_STATE,
                                 "error committing top-down message while applying it",
@@ -622,8 +853,7 @@ impl Actor {
 
                 // if directed to the current network propagate the message
                 if sto == st.network_name {
-                    // FIXME: Should we handle return in some way?
-                    let _ = rt.send(rto, msg.method, msg.params, msg.value)?;
+                    Self::deliver_msg(rt, &st, rto, msg)?;
                 }
             }
             _ => {
@@ -637,6 +867,80 @@ impl Actor {
         Ok(())
     }
 
+    /// Delivers a cross-message directed to the current network to its destination.
+    ///
+    /// Native funds are sent along with the message, while the value of wrapped
+    /// assets is minted for the destination through the token actor of the asset.
+    /// Calls to actors in `CROSS_ENVELOPE_RECEIVERS` get their params wrapped in a
+    /// `CrossMsgEnvelope` with the sender of the message.
+    fn deliver_msg<BS, RT>(
+        rt: &mut RT,
+        st: &State,
+        to: Address,
+        mut msg: StorableMsg,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let asset = st.check_asset(rt.store(), msg.asset).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "error checking cross-msg asset")
+        })?;
+
+        // some actors need to know who sent the cross-message to them.
+        if msg.method != METHOD_SEND {
+            let typ = rt
+                .resolve_address(&to)
+                .and_then(|id| rt.get_actor_code_cid(&id))
+                .and_then(|code| rt.resolve_builtin_actor_type(&code));
+            if matches!(typ, Some(t) if CROSS_ENVELOPE_RECEIVERS.contains(&t)) {
+                let envelope = CrossMsgEnvelope { from: msg.from, params: msg.params };
+                msg.params = RawBytes::serialize(envelope)?;
+            }
+        }
+
+        match asset {
+            None => {
+                // FIXME: Should we handle return in some way?
+                let _ = rt.send(to, msg.method, msg.params, msg.value)?;
+            }
+            Some(asset) => {
+                if msg.value > TokenAmount::zero() {
+                    let params = ext::token::MintParams { to, value: msg.value };
+                    rt.send(
+                        asset.token,
+                        ext::token::MINT_METHOD,
+                        RawBytes::serialize(params)?,
+                        TokenAmount::zero(),
+                    )?;
+                }
+                if msg.method != METHOD_SEND {
+                    let _ = rt.send(to, msg.method, msg.params, TokenAmount::zero())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// RegisterAsset registers a wrapped asset that can be moved through cross-messages.
+    ///
+    /// Assets need to be registered with the same ID in every subnet they are
+    /// bridged to. It can only be called by the system actor.
+    fn register_asset<BS, RT>(rt: &mut RT, params: RegisterAssetParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_is(std::iter::once(&*SYSTEM_ACTOR_ADDR))?;
+
+        rt.transaction(|st: &mut State, rt| {
+            let asset = Asset { id: params.id, symbol: params.symbol, token: params.token };
+            st.register_asset(rt.store(), asset).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "error registering asset")
+            })
+        })
+    }
+
     /// Initializes an atomic execution to be orchestrated by the current subnet.
     /// This method verifies that the execution is being orchestrated by the right subnet
     /// and that its semantics and inputs are correct.
@@ -727,6 +1031,7 @@ impl Actor {
         Ok(())
     })?;
 
+        rt.emit_event(&events::atomic_exec_init(&cid)?)?;
         // return cid for the execution
         Ok(LockedOutput { cid })
     }
@@ -747,9 +1052,8 @@ impl Actor {
             actor_error!(illegal_argument, "error translating caller address to ID")
         })?;
 
+        let cid = params.cid;
         let status = rt.transaction(|st: &mut State, rt| {
-            let cid = params.cid;
-
             match st.get_atomic_exec(rt.store(), &cid.into()).map_err(|e| {
                 e.downcast_default(
                     ExitCode::USR_ILLEGAL_ARGUMENT,
@@ -787,7 +1091,7 @@ impl Actor {
                     // check if the address already submitted an output
                     // FIXME: At this point we don't support the atomic execution between
                     // the same address in different subnets. This can be easily supported if needed.
-                    match exec.submitted().get(&caller.addr().to_string()) {
+                    match exec.submitted().get(&TAddressKey(caller.clone())) {
                         Some(_) => {
                             return Err(actor_error!(
                                 illegal_argument,
@@ -802,6 +1106,10 @@ impl Actor {
                         // mutate status
                         exec.set_status(ExecStatus::Aborted);
                         //  propagate result to subnet
+                        rt.charge_gas(
+                            "OnPropagateExecResult",
+                            gas::GAS_PER_EXEC_INPUT * exec.params().inputs.len() as i64,
+                        );
                         st.propagate_exec_result(
                             rt.store(),
                             &cid.into(),
@@ -829,10 +1137,14 @@ impl Actor {
                             format!("cid provided not equal to the ones submitted: {}", &cid)
                         ));
                     }
-                    exec.submitted_mut().insert(caller.addr().to_string(), output_cid);
+                    exec.submitted_mut().insert(TAddressKey(caller.clone()), output_cid);
                     // if all submissions collected
                     if exec.submitted().len() == exec.params().inputs.len() {
                         exec.set_status(ExecStatus::Success);
+                        rt.charge_gas(
+                            "OnPropagateExecResult",
+                            gas::GAS_PER_EXEC_INPUT * exec.params().inputs.len() as i64,
+                        );
                         st.propagate_exec_result(
                             rt.store(),
                             &cid.into(),
@@ -862,71 +1174,239 @@ impl Actor {
             }
         })?;
 
+        let event = events::atomic_exec_submit(&cid, &rt.message().caller(), status)?;
+        rt.emit_event(&event)?;
         // return cid for the execution
         Ok(SubmitOutput { status })
     }
-}
 
-impl ActorCode for Actor {
-    fn invoke_method<BS, RT>(
-        rt: &mut RT,
-        method: MethodNum,
-        params: &RawBytes,
-    ) -> Result<RawBytes, ActorError>
+    /// ClaimFunds is the escape hatch for users to recover their funds from a subnet
+    /// that stopped committing checkpoints.
+    ///
+    /// Users prove their balance against the balances snapshot of the last checkpoint
+    /// committed by the subnet. Claims are paid from the funds locked in the SCA and
+    /// reduce the circulating supply of the subnet. Addresses can only claim once.
+    fn claim_funds<BS, RT>(rt: &mut RT, params: ClaimFundsParams) -> Result<(), ActorError>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
-            Some(Method::Constructor) => {
-                Self::constructor(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Register) => {
-                let res = Self::register(rt)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::AddStake) => {
-                Self::add_stake(rt)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::ReleaseStake) => {
-                Self::release_stake(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Kill) => {
-                Self::kill(rt)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::CommitChildCheckpoint) => {
-                Self::commit_child_check(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Fund) => {
-                Self::fund(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Release) => {
-                Self::release(rt)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::SendCross) => {
-                Self::send_cross(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::ApplyMessage) => {
-                Self::apply_msg(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
+        rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
+        if params.value <= TokenAmount::zero() {
+            return Err(actor_error!(illegal_argument, "no funds to claim in params"));
+        }
+
+        let caller = rt.message().caller();
+        let sig_addr = resolve_secp_bls(rt, caller)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            let sub = st.get_subnet(rt.store(), &params.subnet).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
+            })?;
+            let mut sub = match sub {
+                Some(sub) => sub,
+                None => {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "subnet with id {} not registered",
+                        params.subnet
+                    ))
+                }
+            };
+            sub.check_status(Method::ClaimFunds)?;
+            if !sub.escape_hatch_open(st, rt.curr_epoch()) {
+                return Err(actor_error!(
+                    illegal_state,
+                    "subnet {} still committing checkpoints",
+                    params.subnet
+                ));
             }
-            Some(Method::InitAtomicExec) => {
-                let res = Self::init_atomic_exec(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
+
+            // verify the balance against the last checkpoint committed
+            let root = match &sub.prev_checkpoint {
+                Some(ch) => &ch.data.balances_root,
+                None => {
+                    return Err(actor_error!(
+                        illegal_state,
+                        "no checkpoint committed by subnet {} to claim funds from",
+                        params.subnet
+                    ))
+                }
+            };
+            let balance = SnapshotBalance { addr: sig_addr, value: params.value.clone() };
+            let valid = merkle::verify(root, &balance, &params.proof).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "error verifying balance proof")
+            })?;
+            if !valid {
+                return Err(actor_error!(illegal_argument, "invalid proof for balance claimed"));
             }
-            Some(Method::SubmitAtomicExec) => {
-                let res = Self::submit_atomic_exec(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
+
+            // sanity-check: see if the actor has enough balance.
+            if rt.current_balance() < params.value {
+                return Err(actor_error!(
+                    illegal_state,
+                    "something went really wrong! the actor doesn't have enough balance to release"
+                ));
             }
-            None => Err(actor_error!(unhandled_message; "Invalid method")),
+            sub.claim_funds(rt.store(), &sig_addr, &params.value, rt.curr_epoch()).map_err(
+                |e| e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "failed to claim funds"),
+            )?;
+            st.flush_subnet(rt.store(), &sub).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error flushing subnet")
+            })?;
+            Ok(())
+        })?;
+
+        rt.send(caller, METHOD_SEND, RawBytes::default(), params.value)?;
+        Ok(())
+    }
+
+    /// ListSubnets returns a page of the child subnets registered in the SCA.
+    fn list_subnets<BS, RT>(
+        rt: &mut RT,
+        params: ListSubnetsParams,
+    ) -> Result<ListSubnetsReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let (subnets, next_offset) =
+            st.list_subnets(rt.store(), params.offset, params.limit).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to list subnets")
+            })?;
+        Ok(ListSubnetsReturn { subnets, next_offset })
+    }
+
+    /// GetSubnet returns the information of a child subnet.
+    fn get_subnet<BS, RT>(rt: &mut RT, params: SubnetID) -> Result<Subnet, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let sub = st.get_subnet(rt.store(), &params).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
+        })?;
+        sub.ok_or_else(|| actor_error!(not_found, "subnet with id {} not registered", params))
+    }
+
+    /// SetValidators is called by a subnet actor to report its current validators,
+    /// their collateral and voting weight. The new set is in effect from the
+    /// current epoch on.
+    fn set_validators<BS, RT>(rt: &mut RT, params: SetValidatorsParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(std::iter::once(&Type::Subnet))?;
+        let subnet_addr = rt.message().caller();
+        let set = ValidatorSet { epoch: rt.curr_epoch(), validators: params.validators };
+
+        rt.transaction(|st: &mut State, rt| {
+            let shid = SubnetID::new(&st.network_name, subnet_addr);
+            let mut sub = st
+                .get_subnet(rt.store(), &shid)
+                .map_err(|e| {
+                    e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet")
+                })?
+                .ok_or_else(|| {
+                    actor_error!(illegal_argument, "subnet with id {} not registered", shid)
+                })?;
+            sub.check_status(Method::SetValidators)?;
+            sub.set_validators(rt.store(), set).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "error setting validators")
+            })?;
+            st.flush_subnet(rt.store(), &sub).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to flush subnet")
+            })
+        })
+    }
+
+    /// ValidatorSet returns the validator set of a child subnet in effect at an epoch,
+    /// so the signatures of its checkpoints can be verified against it.
+    fn validator_set<BS, RT>(
+        rt: &mut RT,
+        params: ValidatorSetParams,
+    ) -> Result<ValidatorSet, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let sub = st
+            .get_subnet(rt.store(), &params.subnet)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load subnet"))?
+            .ok_or_else(|| {
+                actor_error!(not_found, "subnet with id {} not registered", params.subnet)
+            })?;
+        let set = sub.validator_set(rt.store(), params.epoch).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load validator sets")
+        })?;
+        set.ok_or_else(|| {
+            actor_error!(
+                not_found,
+                "no validator set for subnet {} at epoch {}",
+                params.subnet,
+                params.epoch
+            )
+        })
+    }
+
+    /// NetworkName returns the ID of the subnet the SCA governs, so that actors
+    /// can tell whether a hierarchical address lives in the current subnet.
+    fn network_name<BS, RT>(rt: &mut RT) -> Result<SubnetID, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(st.network_name)
+    }
+}
+
+impl ActorCode for Actor {
+    actor_dispatch! {
+        restricted Method {
+            Constructor => constructor(ConstructorParams),
+            Register => register(RawParams) -> SubnetID,
+            AddStake => add_stake(),
+            ReleaseStake => release_stake(FundParams),
+            Kill => kill(),
+            CommitChildCheckpoint => commit_child_check(Checkpoint),
+            Fund => fund(SubnetID),
+            Release => release(),
+            SendCross => send_cross(CrossMsgParams),
+            ApplyMessage => apply_msg(ApplyMsgParams),
+            InitAtomicExec => init_atomic_exec(AtomicExecParamsRaw) -> LockedOutput,
+            SubmitAtomicExec => submit_atomic_exec(SubmitExecParams) -> SubmitOutput,
+            ClaimFunds => claim_funds(ClaimFundsParams),
+            ListSubnets => list_subnets(ListSubnetsParams) -> ListSubnetsReturn,
+            GetSubnet => get_subnet(SubnetID) -> Subnet,
+            RegisterAsset => register_asset(RegisterAssetParams),
+            SetValidators => set_validators(SetValidatorsParams),
+            ValidatorSet => validator_set(ValidatorSetParams) -> ValidatorSet,
+            NetworkName => network_name() -> SubnetID,
+            RegisterExported => register(RawParams) -> SubnetID,
+            AddStakeExported => add_stake(),
+            ReleaseStakeExported => release_stake(FundParams),
+            KillExported => kill(),
+            CommitChildCheckpointExported => commit_child_check(Checkpoint),
+            FundExported => fund(SubnetID),
+            ReleaseExported => release(),
+            SendCrossExported => send_cross(CrossMsgParams),
+            InitAtomicExecExported => init_atomic_exec(AtomicExecParamsRaw) -> LockedOutput,
+            SubmitAtomicExecExported => submit_atomic_exec(SubmitExecParams) -> SubmitOutput,
+            ClaimFundsExported => claim_funds(ClaimFundsParams),
+            ListSubnetsExported => list_subnets(ListSubnetsParams) -> ListSubnetsReturn,
+            GetSubnetExported => get_subnet(SubnetID) -> Subnet,
+            SetValidatorsExported => set_validators(SetValidatorsParams),
+            ValidatorSetExported => validator_set(ValidatorSetParams) -> ValidatorSet,
+            NetworkNameExported => network_name() -> SubnetID,
         }
     }
 }
@@ -0,0 +1,182 @@
+use anyhow::anyhow;
+use cid::multihash::Code;
+use cid::multihash::MultihashDigest;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{serde_bytes, to_vec, Cbor};
+use serde::Serialize;
+use std::convert::TryInto;
+
+/// Domain separation prefixes so a leaf can never be passed off as an inner node.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+type Hash = [u8; 32];
+
+/// Inclusion proof of an item in the binary Merkle tree built over
+/// a list of items (e.g. the cross-msg metas of a checkpoint).
+///
+/// The tree hashes the CBOR serialization of each item with Blake2b256.
+/// When a level has an odd number of nodes, the last one is promoted to
+/// the next level as-is, so the number of leaves is needed to verify a proof.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct MerkleProof {
+    /// Position of the item in the list.
+    pub index: u64,
+    /// Total number of items in the list.
+    pub leaves: u64,
+    /// Sibling hashes from the leaf up to the root.
+    pub path: Vec<ProofNode>,
+}
+impl Cbor for MerkleProof {}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ProofNode {
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+fn blake2b(data: &[u8]) -> Hash {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(Code::Blake2b256.digest(data).digest());
+    out
+}
+
+fn leaf_hash<T: Serialize>(item: &T) -> anyhow::Result<Hash> {
+    let mut data = vec![LEAF_PREFIX];
+    data.extend(to_vec(item)?);
+    Ok(blake2b(&data))
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut data = Vec::with_capacity(1 + 2 * 32);
+    data.push(NODE_PREFIX);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    blake2b(&data)
+}
+
+/// compute the next level of the tree.
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [l, r] => node_hash(l, r),
+            [l] => *l,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Compute the root of the Merkle tree for a list of items.
+///
+/// The root of an empty list is empty.
+pub fn merkle_root<T: Serialize>(items: &[T]) -> anyhow::Result<Vec<u8>> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut level = items.iter().map(leaf_hash).collect::<anyhow::Result<Vec<_>>>()?;
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    Ok(level[0].to_vec())
+}
+
+/// Generate the inclusion proof for an item of the list.
+pub fn prove<T: Serialize + PartialEq>(items: &[T], item: &T) -> anyhow::Result<MerkleProof> {
+    let index = items
+        .iter()
+        .position(|i| i == item)
+        .ok_or_else(|| anyhow!("item not included in the list"))?;
+
+    let mut level = items.iter().map(leaf_hash).collect::<anyhow::Result<Vec<_>>>()?;
+    let mut path = Vec::new();
+    let mut idx = index;
+    while level.len() > 1 {
+        let sibling = idx ^ 1;
+        if sibling < level.len() {
+            path.push(ProofNode { hash: level[sibling].to_vec() });
+        }
+        level = next_level(&level);
+        idx /= 2;
+    }
+
+    Ok(MerkleProof { index: index as u64, leaves: items.len() as u64, path })
+}
+
+/// Verify that an item is included in the list committed by `root`.
+pub fn verify<T: Serialize>(root: &[u8], item: &T, proof: &MerkleProof) -> anyhow::Result<bool> {
+    if proof.index >= proof.leaves {
+        return Ok(false);
+    }
+
+    let mut hash = leaf_hash(item)?;
+    let mut path = proof.path.iter();
+    let mut idx = proof.index;
+    let mut len = proof.leaves;
+    while len > 1 {
+        if idx ^ 1 < len {
+            let sibling: Hash = match path.next() {
+                Some(node) => match node.hash.as_slice().try_into() {
+                    Ok(h) => h,
+                    Err(_) => return Ok(false),
+                },
+                None => return Ok(false),
+            };
+            hash = match idx % 2 {
+                0 => node_hash(&hash, &sibling),
+                _ => node_hash(&sibling, &hash),
+            };
+        }
+        idx /= 2;
+        len = (len + 1) / 2;
+    }
+
+    // the proof shouldn't include more nodes than the ones used.
+    if path.next().is_some() {
+        return Ok(false);
+    }
+    Ok(hash.as_slice() == root)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merkle::*;
+
+    #[test]
+    fn test_prove_verify() {
+        for n in 1..=9u64 {
+            let items: Vec<u64> = (0..n).collect();
+            let root = merkle_root(&items).unwrap();
+            for item in items.iter() {
+                let proof = prove(&items, item).unwrap();
+                assert!(verify(&root, item, &proof).unwrap());
+                // wrong item for the proof
+                assert!(!verify(&root, &(n + 1), &proof).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let items: Vec<u64> = (0..5).collect();
+        let root = merkle_root(&items).unwrap();
+        let mut proof = prove(&items, &2).unwrap();
+        proof.index = 3;
+        assert!(!verify(&root, &2, &proof).unwrap());
+
+        let mut proof = prove(&items, &2).unwrap();
+        proof.path.push(ProofNode { hash: vec![0; 32] });
+        assert!(!verify(&root, &2, &proof).unwrap());
+
+        let mut proof = prove(&items, &2).unwrap();
+        proof.path[0].hash = vec![0; 31];
+        assert!(!verify(&root, &2, &proof).unwrap());
+    }
+
+    #[test]
+    fn test_empty_root() {
+        let items: Vec<u64> = Vec::new();
+        assert!(merkle_root(&items).unwrap().is_empty());
+        assert!(prove(&items, &0).is_err());
+    }
+}
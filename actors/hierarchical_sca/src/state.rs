@@ -18,6 +18,7 @@ use num_traits::Zero;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+use crate::asset::{Asset, AssetID, NATIVE_ASSET};
 use crate::atomic;
 use crate::exec::{AtomicExec, AtomicExecParams, AtomicExecParamsMeta};
 use crate::tcid::{TAmt, TCid, THamt, TLink};
@@ -36,6 +37,7 @@ pub struct State {
     pub min_stake: TokenAmount,
     pub subnets: TCid<THamt<Cid, Subnet>>,
     pub check_period: ChainEpoch,
+    pub escape_hatch_periods: ChainEpoch,
     pub checkpoints: TCid<THamt<ChainEpoch, Checkpoint>>,
     pub check_msg_registry: TCid<THamt<TCid<TLink<CrossMsgs>>, CrossMsgs>>,
     pub nonce: u64,
@@ -44,6 +46,7 @@ pub struct State {
     pub applied_bottomup_nonce: u64,
     pub applied_topdown_nonce: u64,
     pub atomic_exec_registry: TCid<THamt<Cid, AtomicExec>>,
+    pub assets: TCid<THamt<AssetID, Asset>>,
 }
 
 lazy_static! {
@@ -63,6 +66,10 @@ impl State {
                 true => params.checkpoint_period,
                 false => DEFAULT_CHECKPOINT_PERIOD,
             },
+            escape_hatch_periods: match params.escape_hatch_periods > 0 {
+                true => params.escape_hatch_periods,
+                false => DEFAULT_ESCAPE_HATCH_PERIODS,
+            },
             checkpoints: TCid::new_hamt(store)?,
             check_msg_registry: TCid::new_hamt(store)?,
             nonce: Default::default(),
@@ -71,6 +78,7 @@ impl State {
             applied_bottomup_nonce: MAX_NONCE,
             applied_topdown_nonce: Default::default(),
             atomic_exec_registry: TCid::new_hamt(store)?,
+            assets: TCid::new_hamt(store)?,
         })
     }
 
@@ -85,8 +93,89 @@ impl State {
         Ok(subnet.cloned())
     }
 
+    /// List a page of the subnets registered.
+    ///
+    /// Subnets are listed in the iteration order of the subnets map, which is
+    /// stable as long as no subnet is registered between calls.
+    pub fn list_subnets<BS: Blockstore>(
+        &self,
+        store: &BS,
+        offset: u64,
+        limit: u64,
+    ) -> anyhow::Result<(Vec<Subnet>, Option<u64>)> {
+        let limit = match limit > 0 && limit < MAX_LIST_SUBNETS_LIMIT {
+            true => limit,
+            false => MAX_LIST_SUBNETS_LIMIT,
+        };
+        let mut subnets = Vec::new();
+        let mut next_offset = None;
+        let mut index = 0;
+        // the HAMT can't be iterated from an offset, so the iteration is stopped
+        // with an error as soon as the page is full.
+        let res = self.subnets.load(store)?.for_each(|_, sub| {
+            if index >= offset {
+                if (subnets.len() as u64) == limit {
+                    next_offset = Some(index);
+                    return Err(anyhow!("page of subnets full"));
+                }
+                subnets.push(sub.clone());
+            }
+            index += 1;
+            Ok(())
+        });
+        if next_offset.is_none() {
+            res?;
+        }
+        Ok((subnets, next_offset))
+    }
+
+    /// Get a wrapped asset from the registry.
+    pub fn get_asset<BS: Blockstore>(
+        &self,
+        store: &BS,
+        id: AssetID,
+    ) -> anyhow::Result<Option<Asset>> {
+        self.assets.get(store, &id)
+    }
+
+    /// Register a wrapped asset in the registry.
+    pub(crate) fn register_asset<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        asset: Asset,
+    ) -> anyhow::Result<()> {
+        if asset.id == NATIVE_ASSET {
+            return Err(anyhow!("can't register an asset with the id of the native token"));
+        }
+        if self.get_asset(store, asset.id)?.is_some() {
+            return Err(anyhow!("asset with id {} already registered", asset.id));
+        }
+        self.assets.set(store, &asset.id, asset)?;
+        Ok(())
+    }
+
+    /// check that the asset of a cross-message is registered in the subnet.
+    pub(crate) fn check_asset<BS: Blockstore>(
+        &self,
+        store: &BS,
+        asset: AssetID,
+    ) -> anyhow::Result<Option<Asset>> {
+        match asset {
+            NATIVE_ASSET => Ok(None),
+            _ => match self.get_asset(store, asset)? {
+                Some(a) => Ok(Some(a)),
+                None => Err(anyhow!("asset with id {} not registered", asset)),
+            },
+        }
+    }
+
     /// Register a subnet in the map of subnets and flush.
-    pub(crate) fn register_subnet<BS, RT>(&mut self, rt: &RT, id: &SubnetID) -> anyhow::Result<()>
+    pub(crate) fn register_subnet<BS, RT>(
+        &mut self,
+        rt: &RT,
+        id: &SubnetID,
+        params: RegisterParams,
+    ) -> anyhow::Result<()>
     where
         BS: Blockstore,
         RT: Runtime<BS>,
@@ -95,6 +184,9 @@ impl State {
         if val < self.min_stake {
             return Err(anyhow!("call to register doesn't include enough funds"));
         }
+        let code = rt
+            .get_actor_code_cid(&rt.message().caller())
+            .ok_or_else(|| anyhow!("failed to get code of subnet actor"))?;
 
         let inserted = self.subnets.modify(rt.store(), |subnets| {
             if get_subnet(subnets, id)?.is_some() {
@@ -108,6 +200,18 @@ impl State {
                     status: Status::Active,
                     nonce: 0,
                     prev_checkpoint: None,
+                    prev_checkpoint_cid: TCid::default(),
+                    committed_tipsets: TCid::new_hamt(rt.store())?,
+                    grace_period_end: 0,
+                    last_checkpoint_epoch: rt.curr_epoch(),
+                    inactive_since: 0,
+                    claimed: TCid::new_hamt(rt.store())?,
+                    name: params.name,
+                    consensus: params.consensus,
+                    genesis_epoch: rt.curr_epoch(),
+                    code,
+                    asset_supply: Vec::new(),
+                    validator_sets: TCid::new_amt(rt.store())?,
                 };
                 set_subnet(subnets, &id, subnet)?;
                 Ok(true)
@@ -120,21 +224,32 @@ impl State {
         Ok(())
     }
 
-    /// Remove a subnet from the map of subnets and flush.
-    pub(crate) fn rm_subnet<BS: Blockstore>(
+    /// Start the termination of a subnet, giving it a grace period
+    /// to commit its final checkpoints.
+    pub(crate) fn terminate_subnet<BS: Blockstore>(
         &mut self,
         store: &BS,
-        id: &SubnetID,
+        sub: &mut Subnet,
+        curr_epoch: ChainEpoch,
     ) -> anyhow::Result<()> {
-        let deleted = self.subnets.modify(store, |subnets| {
-            subnets
-                .delete(&id.to_bytes())
-                .map_err(|e| e.downcast_wrap(format!("failed to delete subnet for id {}", id)))
-                .map(|x| x.is_some())
-        })?;
-        if deleted {
-            self.total_subnets -= 1;
-        }
+        sub.status = Status::Terminating;
+        sub.grace_period_end = curr_epoch + self.check_period * TERMINATION_GRACE_CHECKPOINTS;
+        self.flush_subnet(store, sub)
+    }
+
+    /// Mark a subnet as killed, releasing its stake, and flush.
+    ///
+    /// The subnet is kept in the map of subnets so its id can't be
+    /// registered again, but it no longer counts as a registered subnet.
+    pub(crate) fn kill_subnet<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        sub: &mut Subnet,
+    ) -> anyhow::Result<()> {
+        sub.status = Status::Killed;
+        sub.stake = TokenAmount::zero();
+        self.flush_subnet(store, sub)?;
+        self.total_subnets -= 1;
         Ok(())
     }
 
@@ -147,13 +262,15 @@ impl State {
         self.subnets.update(store, |subnets| set_subnet(subnets, &sub.id, sub.clone()))
     }
 
-    /// flush a checkpoint
+    /// flush a checkpoint updating its Merkle roots
     pub(crate) fn flush_checkpoint<BS: Blockstore>(
         &mut self,
         store: &BS,
-        ch: &Checkpoint,
+        ch: &mut Checkpoint,
     ) -> anyhow::Result<()> {
-        self.checkpoints.update(store, |checkpoints| set_checkpoint(checkpoints, ch.clone()))
+        ch.data.update_roots()?;
+        self.checkpoints.set(store, &ch.epoch(), ch.clone())?;
+        Ok(())
     }
 
     /// get checkpoint being populated in the current window.
@@ -166,10 +283,9 @@ impl State {
             return Err(anyhow!("epoch can't be negative"));
         }
         let ch_epoch = checkpoint_epoch(epoch, self.check_period);
-        let checkpoints = self.checkpoints.load(store)?;
 
-        let out_ch = match get_checkpoint(&checkpoints, &ch_epoch)? {
-            Some(ch) => ch.clone(),
+        let out_ch = match self.checkpoints.get(store, &ch_epoch)? {
+            Some(ch) => ch,
             None => Checkpoint::new(self.network_name.clone(), ch_epoch),
         };
 
@@ -200,7 +316,10 @@ impl State {
                 (*meta).push(mm);
             }
             burn_val += &mm.value;
-            self.release_circ_supply(store, sub, &mm.from, &mm.value)?;
+            self.release_circ_supply(store, sub, &mm.from, NATIVE_ASSET, &mm.value)?;
+            for a in mm.assets.iter() {
+                self.release_circ_supply(store, sub, &mm.from, a.asset, &a.value)?;
+            }
         }
 
         Ok((burn_val, aux))
@@ -216,7 +335,13 @@ impl State {
     ) -> anyhow::Result<()> {
         for (to, mm) in aux.into_iter() {
             // aggregate values inside msgmeta
-            let value = mm.iter().fold(TokenAmount::zero(), |acc, x| acc + &x.value);
+            let mut agg = CrossMsgMeta::default();
+            for m in mm.iter() {
+                agg.add_value(NATIVE_ASSET, &m.value);
+                for a in m.assets.iter() {
+                    agg.add_value(a.asset, &a.value);
+                }
+            }
             let metas = mm.into_iter().cloned().collect();
 
             match ch.crossmsg_meta_index(&self.network_name, &to) {
@@ -225,7 +350,10 @@ impl State {
                     let prev_cid = &msgmeta.msgs_cid;
                     let m_cid = self.append_metas_to_meta(store, prev_cid, metas)?;
                     msgmeta.msgs_cid = m_cid;
-                    msgmeta.value += value;
+                    msgmeta.add_value(NATIVE_ASSET, &agg.value);
+                    for a in agg.assets.iter() {
+                        msgmeta.add_value(a.asset, &a.value);
+                    }
                 }
                 None => {
                     let mut msgmeta = CrossMsgMeta::new(&self.network_name, &to);
@@ -234,7 +362,8 @@ impl State {
                     let meta_cid = self
                         .check_msg_registry
                         .modify(store, |cross_reg| put_msgmeta(cross_reg, n_mt))?;
-                    msgmeta.value += &value;
+                    msgmeta.value = agg.value;
+                    msgmeta.assets = agg.assets;
                     msgmeta.msgs_cid = meta_cid;
                     ch.append_msgmeta(msgmeta)?;
                 }
@@ -262,7 +391,7 @@ impl State {
                 let prev_cid = &msgmeta.msgs_cid;
                 let m_cid = self.append_msg_to_meta(store, prev_cid, msg)?;
                 msgmeta.msgs_cid = m_cid;
-                msgmeta.value += &msg.value;
+                msgmeta.add_value(msg.asset, &msg.value);
             }
             None => {
                 let mut msgmeta = CrossMsgMeta::new(&sfrom, &sto);
@@ -271,14 +400,14 @@ impl State {
                 let meta_cid = self
                     .check_msg_registry
                     .modify(store, |cross_reg| put_msgmeta(cross_reg, n_mt))?;
-                msgmeta.value += &msg.value;
+                msgmeta.add_value(msg.asset, &msg.value);
                 msgmeta.msgs_cid = meta_cid;
                 ch.append_msgmeta(msgmeta)?;
             }
         };
 
         // flush checkpoint
-        self.flush_checkpoint(store, &ch).map_err(|e| {
+        self.flush_checkpoint(store, &mut ch).map_err(|e| {
             e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "error flushing checkpoint")
         })?;
 
@@ -346,12 +475,13 @@ impl State {
         store: &BS,
         curr: &mut Subnet,
         id: &SubnetID,
+        asset: AssetID,
         val: &TokenAmount,
     ) -> anyhow::Result<()> {
         // if current subnet, we don't need to get the
         // subnet again
         if curr.id == *id {
-            curr.release_supply(val)?;
+            curr.release_supply(asset, val)?;
             return Ok(());
         }
 
@@ -359,7 +489,7 @@ impl State {
             self.get_subnet(store, id).map_err(|e| anyhow!("failed to load subnet: {}", e))?;
         match sub {
             Some(mut sub) => {
-                sub.release_supply(val)?;
+                sub.release_supply(asset, val)?;
                 self.flush_subnet(store, &sub)
             }
             None => return Err(anyhow!("subnet with id {} not registered", id)),
@@ -376,11 +506,9 @@ impl State {
         let mut new_meta = meta.clone();
         new_meta.nonce = self.bottomup_nonce;
         self.bottomup_nonce += 1;
-        self.bottomup_msg_meta.update(store, |crossmsgs| {
-            crossmsgs
-                .set(new_meta.nonce, new_meta)
-                .map_err(|e| anyhow!("failed to set crossmsg meta array: {}", e))
-        })
+        self.bottomup_msg_meta
+            .set(store, new_meta.nonce, new_meta)
+            .map_err(|e| anyhow!("failed to set crossmsg meta array: {}", e))
     }
 
     /// commit topdown messages for their execution in the subnet
@@ -388,6 +516,7 @@ impl State {
         &mut self,
         store: &BS,
         msg: &mut StorableMsg,
+        curr_epoch: ChainEpoch,
     ) -> anyhow::Result<()> {
         let sto = msg.to.subnet()?;
         // let sfrom = msg.from.subnet()?;
@@ -405,10 +534,24 @@ impl State {
             })?;
         match sub {
             Some(mut sub) => {
+                if !sub.status.accepts_topdown() {
+                    return Err(anyhow!(
+                        "subnet {} not accepting top-down messages in status {:?}",
+                        sub.id,
+                        sub.status
+                    ));
+                }
+                // funds sent to a subnet with its escape hatch open couldn't be claimed
+                if sub.escape_hatch_open(self, curr_epoch) {
+                    return Err(anyhow!(
+                        "subnet {} not accepting top-down messages with its escape hatch open",
+                        sub.id
+                    ));
+                }
                 msg.nonce = sub.nonce;
                 sub.store_topdown_msg(store, &msg)?;
                 sub.nonce += 1;
-                sub.circ_supply += &msg.value;
+                sub.add_supply(msg.asset, &msg.value);
                 self.flush_subnet(store, &sub)?;
             }
             None => {
@@ -446,7 +589,7 @@ impl State {
     ) -> anyhow::Result<HCMsgType> {
         let tp = msg.hc_type()?;
         match tp {
-            HCMsgType::TopDown => self.commit_topdown_msg(store, msg)?,
+            HCMsgType::TopDown => self.commit_topdown_msg(store, msg, curr_epoch)?,
             HCMsgType::BottomUp => self.commit_bottomup_msg(store, msg, curr_epoch)?,
             _ => return Err(anyhow!("cross-msg is not of the right type")),
         };
@@ -486,9 +629,9 @@ impl State {
         store: &BS,
         cid: &TCid<TLink<AtomicExecParams>>,
     ) -> anyhow::Result<Option<AtomicExec>> {
-        let registry = self.atomic_exec_registry.load(store)?;
-        let exec = get_atomic_exec(&registry, cid)?;
-        Ok(exec.cloned())
+        self.atomic_exec_registry
+            .get(store, &cid.cid())
+            .map_err(|e| e.downcast_wrap(format!("failed to get atomic exec for cid {}", cid)))
     }
 
     /// Sets a new atomic exec with Cid
@@ -498,12 +641,9 @@ impl State {
         cid: &TCid<TLink<AtomicExecParamsMeta>>,
         exec: AtomicExec,
     ) -> anyhow::Result<()> {
-        self.atomic_exec_registry.update(store, |registry| {
-            registry
-                .set(cid.cid().to_bytes().into(), exec)
-                .map_err(|e| e.downcast_wrap(format!("failed to set atomic exec")))?;
-            Ok(())
-        })?;
+        self.atomic_exec_registry
+            .set(store, &cid.cid(), exec)
+            .map_err(|e| e.downcast_wrap(format!("failed to set atomic exec")))?;
         Ok(())
     }
 
@@ -512,12 +652,9 @@ impl State {
         store: &BS,
         cid: &TCid<TLink<AtomicExecParamsMeta>>,
     ) -> anyhow::Result<()> {
-        self.atomic_exec_registry.update(store, |registry| {
-            registry
-                .delete(&cid.cid().to_bytes())
-                .map_err(|e| e.downcast_wrap(format!("failed to delete atomic exec")))?;
-            Ok(())
-        })?;
+        self.atomic_exec_registry
+            .delete(store, &cid.cid())
+            .map_err(|e| e.downcast_wrap(format!("failed to delete atomic exec")))?;
         Ok(())
     }
 
@@ -575,6 +712,7 @@ impl State {
                 nonce: self.nonce,
                 method,
                 params: enc,
+                asset: NATIVE_ASSET,
             });
         }
 
@@ -588,6 +726,7 @@ impl State {
             nonce: self.nonce,
             method,
             params: enc,
+            asset: NATIVE_ASSET,
         });
     }
 }
@@ -618,20 +757,11 @@ pub fn set_checkpoint<BS: Blockstore>(
 ) -> anyhow::Result<()> {
     let epoch = ch.epoch();
     checkpoints
-        .set(BytesKey::from(epoch.to_ne_bytes().to_vec()), ch)
+        .set(BytesKey::from(epoch.to_le_bytes().to_vec()), ch)
         .map_err(|e| e.downcast_wrap(format!("failed to set checkpoint for epoch {}", epoch)))?;
     Ok(())
 }
 
-fn get_checkpoint<'m, BS: Blockstore>(
-    checkpoints: &'m Map<BS, Checkpoint>,
-    epoch: &ChainEpoch,
-) -> anyhow::Result<Option<&'m Checkpoint>> {
-    checkpoints
-        .get(&BytesKey::from(epoch.to_ne_bytes().to_vec()))
-        .map_err(|e| e.downcast_wrap(format!("failed to get checkpoint for id {}", epoch)))
-}
-
 fn put_msgmeta<BS: Blockstore>(
     registry: &mut Map<BS, CrossMsgs>,
     metas: CrossMsgs,
@@ -669,13 +799,3 @@ pub fn get_topdown_msg<'m, BS: Blockstore>(
 ) -> anyhow::Result<Option<&'m StorableMsg>> {
     crossmsgs.get(nonce).map_err(|e| anyhow!("failed to get msg by nonce: {}", e))
 }
-
-fn get_atomic_exec<'m, BS: Blockstore>(
-    registry: &'m Map<BS, AtomicExec>,
-    cid: &TCid<TLink<AtomicExecParams>>,
-) -> anyhow::Result<Option<&'m AtomicExec>> {
-    let c = cid.cid();
-    registry
-        .get(&c.to_bytes())
-        .map_err(|e| e.downcast_wrap(format!("failed to get atomic exec for cid {}", c)))
-}
@@ -1,15 +1,20 @@
 use anyhow::anyhow;
+use cid::Cid;
 use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{actor_error, ActorError};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::repr::*;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::Cbor;
-use fvm_shared::address::SubnetID;
-use fvm_shared::bigint::bigint_ser;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::{bigint_ser, Zero};
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 
-use crate::tcid::{TAmt, TCid};
-use crate::CROSSMSG_AMT_BITWIDTH;
+use crate::asset::{add_amount, amount_of, sub_amount, AssetAmount, AssetID, NATIVE_ASSET};
+use crate::tcid::{TAmt, TCid, THamt, TLink};
+use crate::validators::ValidatorSet;
+use crate::{Method, CROSSMSG_AMT_BITWIDTH, VALIDATOR_SETS_AMT_BITWIDTH};
 
 use super::checkpoint::*;
 use super::cross::StorableMsg;
@@ -18,9 +23,54 @@ use super::state::State;
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize_repr, Serialize_repr)]
 #[repr(i32)]
 pub enum Status {
-    Active,
-    Inactive,
-    Killed,
+    /// The subnet holds enough stake and operates normally.
+    Active = 0,
+    /// The stake of the subnet fell below the minimum. It is
+    /// reactivated as soon as its stake is topped up again.
+    Inactive = 1,
+    /// The subnet has been killed and its stake released.
+    Killed = 2,
+    /// The subnet signaled its kill, and it is in the grace period
+    /// where it can still commit its final checkpoints.
+    Terminating = 3,
+}
+
+/// Consensus algorithm run by a subnet.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize_repr, Serialize_repr)]
+#[repr(i32)]
+pub enum ConsensusType {
+    Delegated,
+    PoW,
+    Tendermint,
+    FilecoinEC,
+    Dummy,
+    Mir,
+    /// Consensus of subnets that registered without reporting it.
+    Unknown,
+}
+
+impl Status {
+    /// check if top-down messages can be directed to a subnet in this status.
+    pub fn accepts_topdown(&self) -> bool {
+        matches!(self, Status::Active | Status::Inactive)
+    }
+
+    /// check if a subnet in this status can be targeted by `method`.
+    pub fn allows(&self, method: &Method) -> bool {
+        match self {
+            Status::Active => true,
+            // inactive subnets can't commit checkpoints until they
+            // are reactivated.
+            Status::Inactive => !matches!(method, Method::CommitChildCheckpoint),
+            // terminating subnets can only commit their final checkpoints,
+            // let users claim their funds, and finish their kill once the
+            // grace period is over.
+            Status::Terminating => {
+                matches!(method, Method::CommitChildCheckpoint | Method::Kill | Method::ClaimFunds)
+            }
+            Status::Killed => false,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple, PartialEq)]
@@ -34,6 +84,34 @@ pub struct Subnet {
     pub circ_supply: TokenAmount,
     pub status: Status,
     pub prev_checkpoint: Option<Checkpoint>,
+    /// CID the next checkpoint of the subnet points to in `prev_check`. It's the
+    /// CID of `prev_checkpoint`, unless it was migrated from a previous layout
+    /// and keeps the CID it was committed with.
+    pub prev_checkpoint_cid: TCid<TLink<Checkpoint>>,
+    /// Tipset keys anchored by the checkpoints committed by the subnet,
+    /// and the epoch of their checkpoint.
+    pub committed_tipsets: TCid<THamt<Vec<u8>, ChainEpoch>>,
+    /// Last epoch in which a terminating subnet can commit checkpoints.
+    pub grace_period_end: ChainEpoch,
+    /// Epoch in which the last checkpoint of the subnet was committed.
+    pub last_checkpoint_epoch: ChainEpoch,
+    /// Epoch in which an inactive subnet became inactive.
+    pub inactive_since: ChainEpoch,
+    /// Addresses that recovered their funds through the escape hatch,
+    /// and the epoch of the claim.
+    pub claimed: TCid<THamt<Address, ChainEpoch>>,
+    /// Human-readable name of the subnet.
+    pub name: String,
+    pub consensus: ConsensusType,
+    /// Epoch in which the subnet was registered.
+    pub genesis_epoch: ChainEpoch,
+    /// Code CID of the subnet actor governing the subnet.
+    pub code: Cid,
+    /// Circulating supply of wrapped assets in the subnet.
+    pub asset_supply: Vec<AssetAmount>,
+    /// History of the validator sets reported by the subnet actor,
+    /// sorted by the epoch from which they are in effect.
+    pub validator_sets: TCid<TAmt<ValidatorSet, VALIDATOR_SETS_AMT_BITWIDTH>>,
 }
 
 impl Cbor for Subnet {}
@@ -50,33 +128,168 @@ impl Subnet {
         RT: Runtime<BS>,
     {
         self.stake += value;
-        if self.stake < st.min_stake {
-            self.status = Status::Inactive;
+        match self.status {
+            Status::Active if self.stake < st.min_stake => {
+                self.status = Status::Inactive;
+                self.inactive_since = rt.curr_epoch();
+            }
+            Status::Inactive if self.stake >= st.min_stake => {
+                self.status = Status::Active;
+                // the time the subnet couldn't commit checkpoints doesn't count
+                // towards opening the escape hatch.
+                self.last_checkpoint_epoch += rt.curr_epoch() - self.inactive_since;
+                self.inactive_since = 0;
+            }
+            _ => {}
         }
         st.flush_subnet(rt.store(), self)?;
         Ok(())
     }
 
+    /// check if the subnet missed enough checkpoints for users to recover
+    /// their funds through the escape hatch.
+    ///
+    /// Opening the escape hatch is final. Claims are paid against the balances of
+    /// the last checkpoint committed, so later checkpoints are rejected, and once
+    /// users claimed the circulating supply the subnet actor can only kill the subnet.
+    /// The clock is paused while the subnet is inactive, as it can't commit checkpoints.
+    pub fn escape_hatch_open(&self, st: &State, curr_epoch: ChainEpoch) -> bool {
+        if self.status == Status::Terminating && curr_epoch > self.grace_period_end {
+            return true;
+        }
+        let epoch = if self.status == Status::Inactive { self.inactive_since } else { curr_epoch };
+        epoch > self.last_checkpoint_epoch + st.check_period * st.escape_hatch_periods
+    }
+
+    /// record the claim of funds by an address through the escape hatch,
+    /// releasing them from the circulating supply.
+    pub(crate) fn claim_funds<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        addr: &Address,
+        value: &TokenAmount,
+        curr_epoch: ChainEpoch,
+    ) -> anyhow::Result<()> {
+        if self.claimed.get(store, addr)?.is_some() {
+            return Err(anyhow!("funds for {} already claimed", addr));
+        }
+        self.release_supply(NATIVE_ASSET, value)?;
+        self.claimed.set(store, addr, curr_epoch)?;
+        Ok(())
+    }
+
+    /// check that the subnet can be targeted by `method` in its current status.
+    pub(crate) fn check_status(&self, method: Method) -> Result<(), ActorError> {
+        if !self.status.allows(&method) {
+            return Err(actor_error!(
+                illegal_state,
+                "method not allowed for subnet {} in status {:?}",
+                self.id,
+                self.status
+            ));
+        }
+        Ok(())
+    }
+
+    /// record a new validator set for the subnet, in effect from `set.epoch`.
+    ///
+    /// A set reported in the same epoch as the latest one replaces it.
+    pub(crate) fn set_validators<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        set: ValidatorSet,
+    ) -> anyhow::Result<()> {
+        set.check()?;
+        if set.total_collateral() > self.stake {
+            return Err(anyhow!("collateral of validators exceeds the stake of the subnet"));
+        }
+        let count = self.validator_sets.load(store)?.count();
+        let mut idx = count;
+        if count > 0 {
+            let last = self
+                .validator_sets
+                .get(store, count - 1)?
+                .ok_or_else(|| anyhow!("validator set {} not found", count - 1))?;
+            if set.epoch < last.epoch {
+                return Err(anyhow!("validator set older than the latest one"));
+            }
+            if set.epoch == last.epoch {
+                idx = count - 1;
+            }
+        }
+        self.validator_sets.set(store, idx, set)
+    }
+
+    /// return the validator set of the subnet in effect at `epoch`, if any.
+    pub fn validator_set<BS: Blockstore>(
+        &self,
+        store: &BS,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<Option<ValidatorSet>> {
+        // binary search for the latest set in effect at `epoch`.
+        let (mut lo, mut hi) = (0, self.validator_sets.load(store)?.count());
+        let mut found = None;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let set = self
+                .validator_sets
+                .get(store, mid)?
+                .ok_or_else(|| anyhow!("validator set {} not found", mid))?;
+            if set.epoch <= epoch {
+                found = Some(set);
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(found)
+    }
+
     /// store topdown messages for their execution in the subnet
     pub(crate) fn store_topdown_msg<BS: Blockstore>(
         &mut self,
         store: &BS,
         msg: &StorableMsg,
     ) -> anyhow::Result<()> {
-        self.top_down_msgs.update(store, |crossmsgs| {
-            crossmsgs
-                .set(msg.nonce, msg.clone())
-                .map_err(|e| anyhow!("failed to set crossmsg meta array: {}", e))
-        })
+        self.top_down_msgs
+            .set(store, msg.nonce, msg.clone())
+            .map_err(|e| anyhow!("failed to set crossmsg meta array: {}", e))
     }
 
-    pub(crate) fn release_supply(&mut self, value: &TokenAmount) -> anyhow::Result<()> {
-        if &self.circ_supply < value {
+    /// return the circulating supply of an asset in the subnet.
+    pub fn supply_of(&self, asset: AssetID) -> TokenAmount {
+        match asset {
+            NATIVE_ASSET => self.circ_supply.clone(),
+            _ => amount_of(&self.asset_supply, asset),
+        }
+    }
+
+    /// check if the subnet still holds funds of any asset.
+    pub fn has_supply(&self) -> bool {
+        self.circ_supply > TokenAmount::zero() || !self.asset_supply.is_empty()
+    }
+
+    pub(crate) fn add_supply(&mut self, asset: AssetID, value: &TokenAmount) {
+        match asset {
+            NATIVE_ASSET => self.circ_supply += value,
+            _ => add_amount(&mut self.asset_supply, asset, value),
+        }
+    }
+
+    pub(crate) fn release_supply(
+        &mut self,
+        asset: AssetID,
+        value: &TokenAmount,
+    ) -> anyhow::Result<()> {
+        if self.supply_of(asset) < *value {
             return Err(anyhow!(
                 "wtf! we can't release funds below circ, supply. something went really wrong"
             ));
         }
-        self.circ_supply -= value;
+        match asset {
+            NATIVE_ASSET => self.circ_supply -= value,
+            _ => sub_amount(&mut self.asset_supply, asset, value)?,
+        }
         Ok(())
     }
 }
@@ -0,0 +1,111 @@
+use fil_actors_runtime::MessageAccumulator;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use num_traits::{Signed, Zero};
+
+use crate::subnet::Status;
+use crate::{State, Subnet};
+
+pub struct StateSummary {
+    /// Number of subnets that haven't been killed.
+    pub subnet_count: u64,
+    pub total_stake: TokenAmount,
+    /// Native tokens locked in the SCA for the subnets.
+    pub total_circ_supply: TokenAmount,
+}
+
+/// Checks internal invariants of SCA state.
+pub fn check_state_invariants<BS: Blockstore>(
+    state: &State,
+    store: &BS,
+    balance: &TokenAmount,
+) -> (StateSummary, MessageAccumulator) {
+    let acc = MessageAccumulator::default();
+    let mut subnet_count = 0;
+    let mut total_stake = TokenAmount::zero();
+    let mut total_circ_supply = TokenAmount::zero();
+
+    match state.subnets.load(store) {
+        Ok(subnets) => {
+            let ret = subnets.for_each(|_, sub: &Subnet| {
+                let acc = acc.with_prefix(format!("subnet {}: ", sub.id));
+                check_subnet(state, store, sub, &acc);
+                if sub.status != Status::Killed {
+                    subnet_count += 1;
+                }
+                total_stake += &sub.stake;
+                total_circ_supply += &sub.circ_supply;
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating subnets");
+        }
+        Err(e) => acc.add(format!("error loading subnets: {}", e)),
+    }
+
+    acc.require(
+        subnet_count == state.total_subnets,
+        format!("total subnets {} doesn't match {} subnets", state.total_subnets, subnet_count),
+    );
+    acc.require(
+        *balance >= &total_stake + &total_circ_supply,
+        format!(
+            "balance {} is less than the stake {} and circulating supply {} of subnets",
+            balance, total_stake, total_circ_supply
+        ),
+    );
+
+    match state.bottomup_msg_meta.load(store) {
+        Ok(metas) => {
+            let ret = metas.for_each(|i, meta| {
+                acc.require(
+                    meta.nonce == i && meta.nonce < state.bottomup_nonce,
+                    format!(
+                        "bottom-up meta at {} has nonce {}, next nonce {}",
+                        i, meta.nonce, state.bottomup_nonce
+                    ),
+                );
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating bottom-up metas");
+        }
+        Err(e) => acc.add(format!("error loading bottom-up metas: {}", e)),
+    }
+
+    (StateSummary { subnet_count, total_stake, total_circ_supply }, acc)
+}
+
+fn check_subnet<BS: Blockstore>(state: &State, store: &BS, sub: &Subnet, acc: &MessageAccumulator) {
+    acc.require(
+        sub.id.parent().as_ref() == Some(&state.network_name),
+        format!("subnet isn't a child of {}", state.network_name),
+    );
+    acc.require(!sub.stake.is_negative(), format!("negative stake {}", sub.stake));
+    acc.require(
+        !sub.circ_supply.is_negative(),
+        format!("negative circulating supply {}", sub.circ_supply),
+    );
+    if sub.status == Status::Killed {
+        acc.require(sub.stake.is_zero(), format!("killed subnet holds stake {}", sub.stake));
+    }
+
+    let ret = sub.top_down_msgs.for_each(store, |_, msg| {
+        acc.require(
+            msg.nonce < sub.nonce,
+            format!("top-down message with nonce {} ahead of nonce {}", msg.nonce, sub.nonce),
+        );
+        Ok(())
+    });
+    acc.require_no_error(ret, "error iterating top-down messages");
+
+    let mut prev_epoch: Option<ChainEpoch> = None;
+    let ret = sub.validator_sets.for_each(store, |_, set| {
+        acc.require(
+            prev_epoch.map_or(true, |prev| prev < set.epoch),
+            format!("validator sets out of order at epoch {}", set.epoch),
+        );
+        prev_epoch = Some(set.epoch);
+        Ok(())
+    });
+    acc.require_no_error(ret, "error iterating validator sets");
+}
@@ -1,40 +1,164 @@
 use fil_actors_runtime::Array;
 use fvm_ipld_encoding::tuple::*;
-use fvm_shared::address::SubnetID;
+use fvm_ipld_encoding::Cbor;
+use fvm_shared::actor::builtin::Type;
+use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::bigint::bigint_ser;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 
+use crate::asset::AssetID;
 use crate::checkpoint::{Checkpoint, CrossMsgMeta};
+use crate::merkle::MerkleProof;
+use crate::subnet::{ConsensusType, Subnet};
+use crate::validators::Validator;
 use crate::StorableMsg;
 
 pub const CROSSMSG_AMT_BITWIDTH: u32 = 3;
 pub const DEFAULT_CHECKPOINT_PERIOD: ChainEpoch = 10;
 pub const MAX_NONCE: u64 = u64::MAX;
 pub const MIN_COLLATERAL_AMOUNT: u64 = 10_u64.pow(18);
+/// Number of checkpoint periods a terminating subnet has to commit
+/// its final checkpoints before it can be killed.
+pub const TERMINATION_GRACE_CHECKPOINTS: ChainEpoch = 2;
+/// Default number of checkpoint periods a subnet can miss before
+/// users can recover their funds through the escape hatch.
+pub const DEFAULT_ESCAPE_HATCH_PERIODS: ChainEpoch = 10;
+/// Maximum number of subnets returned in a single call to ListSubnets.
+pub const MAX_LIST_SUBNETS_LIMIT: u64 = 100;
+/// Maximum length in bytes of the name of a subnet.
+pub const MAX_SUBNET_NAME_LENGTH: usize = 64;
+/// Maximum number of cross-msg metas a child checkpoint can include.
+pub const MAX_CHECKPOINT_CROSS_MSGS: usize = 1000;
+/// Maximum number of child checkpoints a child checkpoint can include.
+pub const MAX_CHECKPOINT_CHILDREN: usize = 200;
+/// Maximum number of checkpoints of a single child subnet a checkpoint can include.
+pub const MAX_CHILD_CHECKS: usize = 100;
+pub const VALIDATOR_SETS_AMT_BITWIDTH: u32 = 3;
+/// Maximum number of validators in the validator set of a subnet.
+pub const MAX_VALIDATORS: usize = 500;
+/// Actors allowed to send cross-messages. Payment channels use them to settle
+/// payouts to counterparties in other subnets.
+pub const CALLER_TYPES_SEND_CROSS: &[Type] = &[Type::Account, Type::Multisig, Type::PaymentChannel];
+/// Actors that receive cross-messages wrapped in a `CrossMsgEnvelope`,
+/// so they can authenticate their sender.
+pub const CROSS_ENVELOPE_RECEIVERS: &[Type] = &[Type::Multisig];
+
+/// Gas charged for the work the SCA performs on each item of variable
+/// length inputs, so the cost of processing them is paid by the sender.
+pub mod gas {
+    /// Gas charged for each cross-msg meta applied from a child checkpoint.
+    pub const GAS_PER_CROSS_MSG_META: i64 = 250_000;
+    /// Gas charged for each child checkpoint included in a child checkpoint.
+    pub const GAS_PER_CHILD_CHECK: i64 = 100_000;
+    /// Gas charged for each checkpoint CID listed by the children of a child checkpoint.
+    pub const GAS_PER_CHILD_CHECK_CID: i64 = 10_000;
+    /// Gas charged for each input an atomic execution result is propagated to.
+    pub const GAS_PER_EXEC_INPUT: i64 = 300_000;
+}
 
 pub type CrossMsgMetaArray<'bs, BS> = Array<'bs, CrossMsgMeta, BS>;
 pub type CrossMsgArray<'bs, BS> = Array<'bs, StorableMsg, BS>;
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
     pub network_name: String,
     pub checkpoint_period: ChainEpoch,
+    pub escape_hatch_periods: ChainEpoch,
+}
+
+/// Params of `Register`. Subnet actors deployed before they were introduced call it
+/// without params, and are registered with the defaults of `RegisterParams::legacy`.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct RegisterParams {
+    pub name: String,
+    pub consensus: ConsensusType,
+}
+impl Cbor for RegisterParams {}
+
+impl RegisterParams {
+    /// Params of a subnet registered without them: the subnet is named after its id
+    /// and its consensus is unknown to the SCA.
+    pub fn legacy(id: &SubnetID) -> Self {
+        RegisterParams { name: id.to_string(), consensus: ConsensusType::Unknown }
+    }
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct RegisterAssetParams {
+    pub id: AssetID,
+    pub symbol: String,
+    /// Token actor of the asset in the current subnet.
+    pub token: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct ListSubnetsParams {
+    /// Number of subnets to skip.
+    pub offset: u64,
+    /// Maximum number of subnets to return, capped to `MAX_LIST_SUBNETS_LIMIT`.
+    /// A zero limit returns the maximum.
+    pub limit: u64,
+}
+impl Cbor for ListSubnetsParams {}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ListSubnetsReturn {
+    pub subnets: Vec<Subnet>,
+    /// Offset to get the next page of subnets, if any.
+    pub next_offset: Option<u64>,
+}
+impl Cbor for ListSubnetsReturn {}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct SetValidatorsParams {
+    pub validators: Vec<Validator>,
+}
+impl Cbor for SetValidatorsParams {}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct ValidatorSetParams {
+    pub subnet: SubnetID,
+    /// Epoch for which the validator set in effect is returned.
+    pub epoch: ChainEpoch,
+}
+impl Cbor for ValidatorSetParams {}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
 pub struct FundParams {
     #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
 }
+impl Cbor for FundParams {}
 
 #[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct CheckpointParams {
     pub checkpoint: Checkpoint,
 }
 
-#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct ClaimFundsParams {
+    pub subnet: SubnetID,
+    #[serde(with = "bigint_ser")]
+    pub value: TokenAmount,
+    /// Proof of the balance of the caller against the balances
+    /// snapshot of the last checkpoint committed by the subnet.
+    pub proof: MerkleProof,
+}
+
+/// Params of `ApplyMessage`.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+pub struct ApplyMsgParams {
+    pub msg: StorableMsg,
+    /// Source of the cross-msg meta the message was committed in by a child
+    /// subnet. Only set, and required, for bottom-up messages.
+    pub meta_from: Option<SubnetID>,
+}
+impl Cbor for ApplyMsgParams {}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
 pub struct CrossMsgParams {
     pub msg: StorableMsg,
     pub destination: SubnetID,
 }
+impl Cbor for CrossMsgParams {}
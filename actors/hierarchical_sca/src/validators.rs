@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::Cbor;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::{bigint_ser, BigInt};
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use num_traits::{Signed, Zero};
+
+use crate::MAX_VALIDATORS;
+
+/// Validator of a subnet running a PoS-style consensus.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct Validator {
+    pub addr: Address,
+    /// Collateral staked by the validator in the subnet actor.
+    #[serde(with = "bigint_ser")]
+    pub collateral: TokenAmount,
+    /// Voting weight of the validator in the consensus of the subnet.
+    #[serde(with = "bigint_ser")]
+    pub weight: BigInt,
+}
+impl Cbor for Validator {}
+
+/// Set of validators of a subnet, in effect from `epoch` until
+/// the epoch of the next set reported by the subnet actor.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct ValidatorSet {
+    pub epoch: ChainEpoch,
+    pub validators: Vec<Validator>,
+}
+impl Cbor for ValidatorSet {}
+
+impl ValidatorSet {
+    /// get a validator of the set by its address.
+    pub fn get(&self, addr: &Address) -> Option<&Validator> {
+        self.validators.iter().find(|v| &v.addr == addr)
+    }
+
+    pub fn total_weight(&self) -> BigInt {
+        self.validators.iter().map(|v| &v.weight).sum()
+    }
+
+    pub fn total_collateral(&self) -> TokenAmount {
+        self.validators.iter().map(|v| &v.collateral).sum()
+    }
+
+    /// weight of the validators of the set in `signers`, ignoring
+    /// addresses that are not validators and duplicates.
+    pub fn weight_of(&self, signers: &[Address]) -> BigInt {
+        let signers: HashSet<&Address> = signers.iter().collect();
+        self.validators.iter().filter(|v| signers.contains(&v.addr)).map(|v| &v.weight).sum()
+    }
+
+    /// check if `signers` hold more than two thirds of the weight of the set,
+    /// the quorum required for a checkpoint to be signed by the subnet.
+    pub fn has_quorum(&self, signers: &[Address]) -> bool {
+        let total = self.total_weight();
+        !total.is_zero() && self.weight_of(signers) * 3 > total * 2
+    }
+
+    /// check that the set is well-formed.
+    pub(crate) fn check(&self) -> anyhow::Result<()> {
+        if self.validators.len() > MAX_VALIDATORS {
+            return Err(anyhow!(
+                "validator set can't have more than {} validators",
+                MAX_VALIDATORS
+            ));
+        }
+        let mut seen = HashSet::new();
+        for v in self.validators.iter() {
+            if !seen.insert(&v.addr) {
+                return Err(anyhow!("duplicate validator {} in set", v.addr));
+            }
+            if !v.weight.is_positive() {
+                return Err(anyhow!("validator {} must have a positive weight", v.addr));
+            }
+            if v.collateral.is_negative() {
+                return Err(anyhow!("validator {} can't have negative collateral", v.addr));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validators::*;
+
+    fn validator(id: u64, weight: u64) -> Validator {
+        Validator {
+            addr: Address::new_id(id),
+            collateral: TokenAmount::from(weight),
+            weight: BigInt::from(weight),
+        }
+    }
+
+    #[test]
+    fn test_quorum() {
+        let set = ValidatorSet {
+            epoch: 0,
+            validators: vec![validator(1, 1), validator(2, 1), validator(3, 2)],
+        };
+        set.check().unwrap();
+        assert_eq!(set.total_weight(), BigInt::from(4));
+        assert!(!set.has_quorum(&[Address::new_id(3)]));
+        assert!(!set.has_quorum(&[Address::new_id(1), Address::new_id(2)]));
+        assert!(!set.has_quorum(&[Address::new_id(3), Address::new_id(3), Address::new_id(4)]));
+        assert!(set.has_quorum(&[Address::new_id(1), Address::new_id(3)]));
+        assert!(!ValidatorSet::default().has_quorum(&[]));
+
+        let dup = ValidatorSet { epoch: 0, validators: vec![validator(1, 1), validator(1, 2)] };
+        assert!(dup.check().is_err());
+        let zero = ValidatorSet { epoch: 0, validators: vec![validator(1, 0)] };
+        assert!(zero.check().is_err());
+    }
+}
@@ -1,5 +1,4 @@
 use std::borrow::Borrow;
-use std::str::FromStr;
 
 use actor_primitives::atomic::{UnlockParams, METHOD_ABORT, METHOD_UNLOCK};
 use actor_primitives::tcid::{TCid, TCidContent};
@@ -11,8 +10,8 @@ use fil_actors_runtime::builtin::HAMT_BIT_WIDTH;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::expect_abort;
 use fil_actors_runtime::test_utils::{
-    MockRuntime, ACCOUNT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, SUBNET_ACTOR_CODE_ID,
-    SYSTEM_ACTOR_CODE_ID,
+    MockRuntime, ACCOUNT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID,
+    SUBNET_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
 };
 use fil_actors_runtime::Array;
 use fil_actors_runtime::{
@@ -25,6 +24,7 @@ use fvm_shared::address::subnet::ROOTNET_ID;
 use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::bigint::bigint_ser::BigIntDe;
 use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
@@ -35,11 +35,13 @@ use fil_actor_hierarchical_sca::checkpoint::ChildCheck;
 use fil_actor_hierarchical_sca::exec::{
     AtomicExecParamsRaw, ExecStatus, LockedOutput, SubmitExecParams, SubmitOutput,
 };
-use fil_actor_hierarchical_sca::ext;
 use fil_actor_hierarchical_sca::{
-    get_topdown_msg, is_bottomup, Checkpoint, ConstructorParams, CrossMsgMeta, CrossMsgParams,
-    CrossMsgs, FundParams, HCMsgType, Method, State, StorableMsg, Subnet, CROSSMSG_AMT_BITWIDTH,
-    DEFAULT_CHECKPOINT_PERIOD, MAX_NONCE, MIN_COLLATERAL_AMOUNT,
+    events, ext, gas, get_topdown_msg, is_bottomup, ApplyMsgParams, Asset, Checkpoint,
+    ClaimFundsParams, ConsensusType, ConstructorParams, CrossMsgMeta, CrossMsgParams, CrossMsgs,
+    FundParams, HCMsgType, ListSubnetsParams, ListSubnetsReturn, Method, RegisterAssetParams,
+    RegisterParams, SetValidatorsParams, State, StorableMsg, Subnet, Validator, ValidatorSet,
+    ValidatorSetParams, CROSSMSG_AMT_BITWIDTH, DEFAULT_CHECKPOINT_PERIOD,
+    DEFAULT_ESCAPE_HATCH_PERIODS, MAX_NONCE, MIN_COLLATERAL_AMOUNT, NATIVE_ASSET,
 };
 
 use crate::SCAActor;
@@ -53,12 +55,9 @@ lazy_static! {
 }
 
 pub fn new_runtime() -> MockRuntime {
-    MockRuntime {
-        receiver: *STORAGE_POWER_ACTOR_ADDR,
-        caller: *SYSTEM_ACTOR_ADDR,
-        caller_type: *SYSTEM_ACTOR_CODE_ID,
-        ..Default::default()
-    }
+    let mut rt = MockRuntime { receiver: *STORAGE_POWER_ACTOR_ADDR, ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    rt
 }
 
 pub fn new_harness(id: SubnetID) -> Harness {
@@ -84,8 +83,11 @@ pub struct Harness {
 impl Harness {
     pub fn construct(&self, rt: &mut MockRuntime) {
         rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
-        let params =
-            ConstructorParams { network_name: self.net_name.to_string(), checkpoint_period: 10 };
+        let params = ConstructorParams {
+            network_name: self.net_name.to_string(),
+            checkpoint_period: 10,
+            escape_hatch_periods: 0,
+        };
         rt.call::<SCAActor>(
             Method::Constructor as MethodNum,
             &RawBytes::serialize(params).unwrap(),
@@ -105,6 +107,7 @@ impl Harness {
         assert_eq!(st.network_name, self.net_name);
         assert_eq!(st.min_stake, TokenAmount::from(MIN_COLLATERAL_AMOUNT));
         assert_eq!(st.check_period, DEFAULT_CHECKPOINT_PERIOD);
+        assert_eq!(st.escape_hatch_periods, DEFAULT_ESCAPE_HATCH_PERIODS);
         assert_eq!(st.applied_bottomup_nonce, MAX_NONCE);
         assert_eq!(st.bottomup_msg_meta.cid(), empty_bottomup_array);
         verify_empty_map(rt, st.subnets.cid());
@@ -124,18 +127,27 @@ impl Harness {
         rt.set_value(value.clone());
         rt.set_balance(value.clone());
         rt.expect_validate_caller_type(vec![*SUBNET_ACTOR_CODE_ID]);
+        let params = RegisterParams {
+            name: format!("subnet-{}", subnet_addr),
+            consensus: ConsensusType::Delegated,
+        };
 
         if code != ExitCode::OK {
             expect_abort(
                 code,
-                rt.call::<SCAActor>(Method::Register as MethodNum, &RawBytes::default()),
+                rt.call::<SCAActor>(
+                    Method::Register as MethodNum,
+                    &RawBytes::serialize(params).unwrap(),
+                ),
             );
             rt.verify();
             return Ok(());
         }
 
         let register_ret = SubnetID::new(&self.net_name, *subnet_addr);
-        let ret = rt.call::<SCAActor>(Method::Register as MethodNum, &RawBytes::default()).unwrap();
+        let ret = rt
+            .call::<SCAActor>(Method::Register as MethodNum, &RawBytes::serialize(params).unwrap())
+            .unwrap();
         rt.verify();
         let ret: SubnetID = RawBytes::deserialize(&ret).unwrap();
         assert_eq!(ret, register_ret);
@@ -228,14 +240,16 @@ impl Harness {
             return Ok(());
         }
 
-        rt.expect_send(
-            id.subnet_actor(),
-            METHOD_SEND,
-            RawBytes::default(),
-            release_value.clone(),
-            RawBytes::default(),
-            ExitCode::OK,
-        );
+        if release_value > &TokenAmount::zero() {
+            rt.expect_send(
+                id.subnet_actor(),
+                METHOD_SEND,
+                RawBytes::default(),
+                release_value.clone(),
+                RawBytes::default(),
+                ExitCode::OK,
+            );
+        }
         rt.call::<SCAActor>(Method::Kill as MethodNum, &RawBytes::default()).unwrap();
         rt.verify();
 
@@ -265,6 +279,12 @@ impl Harness {
             return Ok(());
         }
 
+        let child_checks: usize = ch.data.children.iter().map(|c| c.checks.len()).sum();
+        rt.expect_gas_charge(
+            gas::GAS_PER_CROSS_MSG_META * ch.data.cross_msgs.len() as i64
+                + gas::GAS_PER_CHILD_CHECK * ch.data.children.len() as i64
+                + gas::GAS_PER_CHILD_CHECK_CID * child_checks as i64,
+        );
         if burn_value > TokenAmount::zero() {
             rt.expect_send(
                 *BURNT_FUNDS_ACTOR_ADDR,
@@ -275,6 +295,9 @@ impl Harness {
                 ExitCode::OK,
             );
         }
+        rt.expect_emitted_event(
+            events::checkpoint_commit(id, ch.epoch(), &ch.cid(), &burn_value).unwrap(),
+        );
         rt.call::<SCAActor>(
             Method::CommitChildCheckpoint as MethodNum,
             &RawBytes::serialize(ch).unwrap(),
@@ -319,6 +342,17 @@ impl Harness {
             RawBytes::serialize(*TEST_BLS).unwrap(),
             ExitCode::OK,
         );
+        let from = Address::new_hierarchical(&self.net_name, &TEST_BLS).unwrap();
+        let to = Address::new_hierarchical(&id, &TEST_BLS).unwrap();
+        let expected_msg = StorableMsg {
+            from,
+            to,
+            method: METHOD_SEND,
+            value: value.clone(),
+            nonce: expected_nonce - 1,
+            ..Default::default()
+        };
+        rt.expect_emitted_event(events::fund(id, &expected_msg).unwrap());
         rt.call::<SCAActor>(Method::Fund as MethodNum, &RawBytes::serialize(id.clone()).unwrap())
             .unwrap();
         rt.verify();
@@ -328,12 +362,52 @@ impl Harness {
         let msg = get_topdown_msg(&crossmsgs, expected_nonce - 1).unwrap().unwrap();
         assert_eq!(&sub.circ_supply, expected_circ_sup);
         assert_eq!(sub.nonce, expected_nonce);
-        let from = Address::new_hierarchical(&self.net_name, &TEST_BLS).unwrap();
-        let to = Address::new_hierarchical(&id, &TEST_BLS).unwrap();
-        assert_eq!(msg.from, from);
-        assert_eq!(msg.to, to);
-        assert_eq!(msg.nonce, expected_nonce - 1);
-        assert_eq!(msg.value, value);
+        assert_eq!(msg, expected_msg);
+
+        Ok(())
+    }
+
+    pub fn claim_funds(
+        &self,
+        rt: &mut MockRuntime,
+        claimer: &Address,
+        params: ClaimFundsParams,
+        code: ExitCode,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *claimer);
+        rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+        rt.expect_send(
+            *claimer,
+            ext::account::PUBKEY_ADDRESS_METHOD,
+            RawBytes::default(),
+            TokenAmount::zero(),
+            RawBytes::serialize(*TEST_BLS).unwrap(),
+            ExitCode::OK,
+        );
+
+        if code != ExitCode::OK {
+            expect_abort(
+                code,
+                rt.call::<SCAActor>(
+                    Method::ClaimFunds as MethodNum,
+                    &RawBytes::serialize(params).unwrap(),
+                ),
+            );
+            rt.verify();
+            return Ok(());
+        }
+
+        rt.expect_send(
+            *claimer,
+            METHOD_SEND,
+            RawBytes::default(),
+            params.value.clone(),
+            RawBytes::default(),
+            ExitCode::OK,
+        );
+        rt.call::<SCAActor>(Method::ClaimFunds as MethodNum, &RawBytes::serialize(params).unwrap())
+            .unwrap();
+        rt.verify();
 
         Ok(())
     }
@@ -376,14 +450,23 @@ impl Harness {
             RawBytes::default(),
             ExitCode::OK,
         );
+        let parent = &self.net_name.parent().unwrap();
+        let from = Address::new_hierarchical(&self.net_name, &BURNT_FUNDS_ACTOR_ADDR).unwrap();
+        let to = Address::new_hierarchical(&parent, &TEST_BLS).unwrap();
+        let expected_msg = StorableMsg {
+            from,
+            to,
+            method: METHOD_SEND,
+            value: value.clone(),
+            nonce: expected_nonce,
+            ..Default::default()
+        };
+        rt.expect_emitted_event(events::release(&expected_msg).unwrap());
         rt.call::<SCAActor>(Method::Release as MethodNum, &RawBytes::default()).unwrap();
         rt.verify();
 
         let st: State = rt.get_state();
 
-        let parent = &self.net_name.parent().unwrap();
-        let from = Address::new_hierarchical(&self.net_name, &BURNT_FUNDS_ACTOR_ADDR).unwrap();
-        let to = Address::new_hierarchical(&parent, &TEST_BLS).unwrap();
         rt.set_epoch(0);
         let ch = st.get_window_checkpoint(rt.store(), 0).unwrap();
         let chmeta_ind = ch.crossmsg_meta_index(&self.net_name, &parent).unwrap();
@@ -421,7 +504,11 @@ impl Harness {
         expected_circ_sup: &TokenAmount,
     ) -> Result<(), ActorError> {
         rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *from);
-        rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+        rt.expect_validate_caller_type(vec![
+            *ACCOUNT_ACTOR_CODE_ID,
+            *MULTISIG_ACTOR_CODE_ID,
+            *PAYCH_ACTOR_CODE_ID,
+        ]);
 
         rt.set_value(value.clone());
 
@@ -432,6 +519,7 @@ impl Harness {
             method: METHOD_SEND,
             params: RawBytes::default(),
             value: value.clone(),
+            asset: NATIVE_ASSET,
         };
         let dest = sub.clone();
         let params = CrossMsgParams { destination: sub, msg };
@@ -467,6 +555,14 @@ impl Harness {
                 ExitCode::OK,
             );
         }
+        // bottom-up messages keep the nonce they were sent with
+        let expected_msg = StorableMsg {
+            from: Address::new_hierarchical(&self.net_name, &TEST_BLS).unwrap(),
+            to: Address::new_hierarchical(&dest, &to).unwrap(),
+            nonce: if is_bu { nonce } else { nonce - 1 },
+            ..params.msg.clone()
+        };
+        rt.expect_emitted_event(events::send_cross(&dest, &expected_msg).unwrap());
         rt.call::<SCAActor>(Method::SendCross as MethodNum, &RawBytes::serialize(params).unwrap())
             .unwrap();
         rt.verify();
@@ -522,18 +618,24 @@ impl Harness {
         rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
 
         rt.set_balance(value.clone());
-        let params = StorableMsg {
+        let msg = StorableMsg {
             to: to.clone(),
             from: from.clone(),
             method: METHOD_SEND,
             value: value.clone(),
             params: RawBytes::default(),
             nonce: msg_nonce,
+            asset: NATIVE_ASSET,
         };
 
         let st: State = rt.get_state();
-        let sto = params.to.subnet().unwrap();
+        let sto = msg.to.subnet().unwrap();
         let rto = to.raw_addr().unwrap();
+        let bottomup = msg.apply_type(&st.network_name).unwrap() == HCMsgType::BottomUp;
+        // bottom-up messages come in the meta committed by the child in their route
+        let meta_from =
+            if bottomup { msg.from.subnet().unwrap().down(&st.network_name) } else { None };
+        let params = ApplyMsgParams { msg: msg.clone(), meta_from };
 
         // if expected code is not ok
         if code != ExitCode::OK {
@@ -548,13 +650,13 @@ impl Harness {
             return Ok(());
         }
 
-        if params.apply_type(&st.network_name).unwrap() == HCMsgType::BottomUp {
+        if bottomup {
             if sto == st.network_name {
                 rt.expect_send(
                     rto,
                     METHOD_SEND,
                     RawBytes::default(),
-                    params.value.clone(),
+                    msg.value.clone(),
                     RawBytes::default(),
                     ExitCode::OK,
                 );
@@ -569,7 +671,7 @@ impl Harness {
             assert_eq!(st.applied_bottomup_nonce, msg_nonce);
         } else {
             let rew_params =
-                ext::reward::FundingParams { addr: *SCA_ACTOR_ADDR, value: params.value.clone() };
+                ext::reward::FundingParams { addr: *SCA_ACTOR_ADDR, value: msg.value.clone() };
             rt.expect_send(
                 *REWARD_ACTOR_ADDR,
                 ext::reward::EXTERNAL_FUNDING_METHOD,
@@ -583,7 +685,7 @@ impl Harness {
                     rto,
                     METHOD_SEND,
                     RawBytes::default(),
-                    params.value.clone(),
+                    msg.value.clone(),
                     RawBytes::default(),
                     ExitCode::OK,
                 );
@@ -636,6 +738,7 @@ impl Harness {
             return Ok(());
         }
 
+        rt.expect_emitted_event(events::atomic_exec_init(&result.cid).unwrap());
         let ret = rt
             .call::<SCAActor>(
                 Method::InitAtomicExec as MethodNum,
@@ -680,6 +783,12 @@ impl Harness {
             return Ok(());
         }
 
+        if result.status != ExecStatus::Initialized {
+            rt.expect_gas_charge(gas::GAS_PER_EXEC_INPUT * exec_params.inputs.len() as i64);
+        }
+        rt.expect_emitted_event(
+            events::atomic_exec_submit(&submit_params.cid, caller, result.status).unwrap(),
+        );
         let ret = rt
             .call::<SCAActor>(
                 Method::SubmitAtomicExec as MethodNum,
@@ -697,8 +806,7 @@ impl Harness {
                 panic!("execution should have been cleaned when finalized");
             }
             for (k, _) in exec_params.inputs.iter() {
-                let addr = Address::from_str(k).unwrap();
-                let sn = addr.subnet().unwrap();
+                let sn = k.0.subnet();
                 let sub = st.get_subnet(rt.store(), &sn).unwrap().unwrap();
                 let crossmsgs = sub.top_down_msgs.load(rt.store()).unwrap();
                 let msg = get_topdown_msg(&crossmsgs, 0).unwrap().unwrap();
@@ -722,6 +830,193 @@ impl Harness {
         Ok(())
     }
 
+    pub fn list_subnets(
+        &self,
+        rt: &mut MockRuntime,
+        offset: u64,
+        limit: u64,
+    ) -> Result<ListSubnetsReturn, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1000));
+        rt.expect_validate_caller_any();
+        let params = ListSubnetsParams { offset, limit };
+        let ret = rt.call::<SCAActor>(
+            Method::ListSubnets as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+        Ok(RawBytes::deserialize(&ret).unwrap())
+    }
+
+    pub fn register_asset(
+        &self,
+        rt: &mut MockRuntime,
+        caller: &Address,
+        params: RegisterAssetParams,
+        code: ExitCode,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *caller);
+        rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+
+        if code != ExitCode::OK {
+            expect_abort(
+                code,
+                rt.call::<SCAActor>(
+                    Method::RegisterAsset as MethodNum,
+                    &RawBytes::serialize(params).unwrap(),
+                ),
+            );
+            rt.verify();
+            return Ok(());
+        }
+
+        rt.call::<SCAActor>(
+            Method::RegisterAsset as MethodNum,
+            &RawBytes::serialize(params.clone()).unwrap(),
+        )?;
+        rt.verify();
+
+        let st: State = rt.get_state();
+        let asset = st.get_asset(rt.store(), params.id).unwrap().unwrap();
+        assert_eq!(asset.symbol, params.symbol);
+        assert_eq!(asset.token, params.token);
+        Ok(())
+    }
+
+    pub fn send_asset_cross(
+        &self,
+        rt: &mut MockRuntime,
+        from: &Address,
+        to: &Address,
+        sub: SubnetID,
+        asset: &Asset,
+        value: TokenAmount,
+        code: ExitCode,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, *from);
+        rt.expect_validate_caller_type(vec![
+            *ACCOUNT_ACTOR_CODE_ID,
+            *MULTISIG_ACTOR_CODE_ID,
+            *PAYCH_ACTOR_CODE_ID,
+        ]);
+        rt.expect_send(
+            *from,
+            ext::account::PUBKEY_ADDRESS_METHOD,
+            RawBytes::default(),
+            TokenAmount::zero(),
+            RawBytes::serialize(*TEST_BLS).unwrap(),
+            ExitCode::OK,
+        );
+
+        let msg = StorableMsg {
+            from: *from,
+            to: *to,
+            method: METHOD_SEND,
+            value: value.clone(),
+            asset: asset.id,
+            ..Default::default()
+        };
+        let params = CrossMsgParams { destination: sub.clone(), msg };
+        if code != ExitCode::OK {
+            expect_abort(
+                code,
+                rt.call::<SCAActor>(
+                    Method::SendCross as MethodNum,
+                    &RawBytes::serialize(params).unwrap(),
+                ),
+            );
+            rt.verify();
+            return Ok(());
+        }
+
+        // the message is committed top-down with the next nonce of the child
+        let child = self.get_subnet(rt, &sub.down(&self.net_name).unwrap()).unwrap();
+        let expected_msg = StorableMsg {
+            from: Address::new_hierarchical(&self.net_name, &TEST_BLS).unwrap(),
+            to: Address::new_hierarchical(&sub, to).unwrap(),
+            nonce: child.nonce,
+            ..params.msg.clone()
+        };
+        rt.expect_emitted_event(events::send_cross(&sub, &expected_msg).unwrap());
+
+        // the value of the asset is burnt from the sender
+        let burn = ext::token::BurnParams { owner: *TEST_BLS, value };
+        rt.expect_send(
+            asset.token,
+            ext::token::BURN_METHOD,
+            RawBytes::serialize(burn).unwrap(),
+            TokenAmount::zero(),
+            RawBytes::default(),
+            ExitCode::OK,
+        );
+        rt.call::<SCAActor>(Method::SendCross as MethodNum, &RawBytes::serialize(params).unwrap())?;
+        rt.verify();
+        Ok(())
+    }
+
+    pub fn query_subnet(&self, rt: &mut MockRuntime, id: &SubnetID) -> Result<Subnet, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1000));
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<SCAActor>(
+            Method::GetSubnet as MethodNum,
+            &RawBytes::serialize(id.clone()).unwrap(),
+        );
+        rt.verify();
+        Ok(RawBytes::deserialize(&ret?).unwrap())
+    }
+
+    pub fn set_validators(
+        &self,
+        rt: &mut MockRuntime,
+        id: &SubnetID,
+        validators: Vec<Validator>,
+        code: ExitCode,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*SUBNET_ACTOR_CODE_ID, id.subnet_actor());
+        rt.expect_validate_caller_type(vec![*SUBNET_ACTOR_CODE_ID]);
+        let params = SetValidatorsParams { validators: validators.clone() };
+
+        if code != ExitCode::OK {
+            expect_abort(
+                code,
+                rt.call::<SCAActor>(
+                    Method::SetValidators as MethodNum,
+                    &RawBytes::serialize(params).unwrap(),
+                ),
+            );
+            rt.verify();
+            return Ok(());
+        }
+
+        rt.call::<SCAActor>(
+            Method::SetValidators as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        )?;
+        rt.verify();
+
+        let sub = self.get_subnet(rt, id).unwrap();
+        let set = sub.validator_set(rt.store(), rt.epoch).unwrap().unwrap();
+        assert_eq!(set.epoch, rt.epoch);
+        assert_eq!(set.validators, validators);
+        Ok(())
+    }
+
+    pub fn query_validator_set(
+        &self,
+        rt: &mut MockRuntime,
+        id: &SubnetID,
+        epoch: ChainEpoch,
+    ) -> Result<ValidatorSet, ActorError> {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1000));
+        rt.expect_validate_caller_any();
+        let params = ValidatorSetParams { subnet: id.clone(), epoch };
+        let ret = rt.call::<SCAActor>(
+            Method::ValidatorSet as MethodNum,
+            &RawBytes::serialize(params).unwrap(),
+        );
+        rt.verify();
+        Ok(RawBytes::deserialize(&ret?).unwrap())
+    }
+
     pub fn check_state(&self) {
         // TODO: https://github.com/filecoin-project/builtin-actors/issues/44
     }
@@ -739,6 +1034,24 @@ pub fn verify_empty_map(rt: &MockRuntime, key: Cid) {
     map.for_each(|_key, _val| panic!("expected no keys")).unwrap();
 }
 
+/// create a checkpoint anchoring a dummy tipset and state root for the epoch.
+pub fn new_checkpoint(id: &SubnetID, epoch: ChainEpoch) -> Checkpoint {
+    checkpoint_with_block(id, epoch, "block")
+}
+
+/// create a checkpoint anchoring a dummy tipset with the named block for the epoch,
+/// so that different tipsets can be committed in the same epoch.
+pub fn checkpoint_with_block(id: &SubnetID, epoch: ChainEpoch, block: &str) -> Checkpoint {
+    let mut ch = Checkpoint::new(id.clone(), epoch);
+    let cid =
+        |s: String| Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, Code::Blake2b256.digest(s.as_bytes()));
+    ch.set_tipset(
+        &[cid(format!("{}-{}-{}", block, id, epoch))],
+        cid(format!("root-{}-{}-{}", block, id, epoch)),
+    );
+    ch
+}
+
 pub fn has_childcheck_source<'a>(
     children: &'a Vec<ChildCheck>,
     source: &SubnetID,
@@ -759,8 +1072,14 @@ pub fn add_msg_meta(
 ) {
     let mh_code = Code::Blake2b256;
     let c = TCid::from(Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, mh_code.digest(&rand)));
-    let meta =
-        CrossMsgMeta { from: from.clone(), to: to.clone(), msgs_cid: c, nonce: 0, value: value };
+    let meta = CrossMsgMeta {
+        from: from.clone(),
+        to: to.clone(),
+        msgs_cid: c,
+        nonce: 0,
+        value: value,
+        assets: Vec::new(),
+    };
     ch.append_msgmeta(meta).unwrap();
 }
 
@@ -1,28 +1,40 @@
 use actor_primitives::atomic::SerializedState;
+use actor_primitives::taddress::{TAddress, TAddressKey};
 use actor_primitives::tcid::TCid;
 use cid::multihash::Code;
 use cid::multihash::MultihashDigest;
 use cid::Cid;
+use fil_actor_hierarchical_sca::checkpoint::ChildCheck;
 use fil_actor_hierarchical_sca::exec::AtomicExecParamsRaw;
 use fil_actors_runtime::runtime::Runtime;
-use fil_actors_runtime::BURNT_FUNDS_ACTOR_ADDR;
+use fil_actors_runtime::test_utils::{
+    expect_abort, MockRuntime, ACCOUNT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID,
+    SUBNET_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+};
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
 use fvm_ipld_encoding::RawBytes;
 use fvm_ipld_encoding::DAG_CBOR;
 use fvm_shared::address::subnet::ROOTNET_ID;
 use fvm_shared::address::{Address, SubnetID};
-use fvm_shared::bigint::Zero;
+use fvm_shared::bigint::{BigInt, Zero};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_SEND};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
 use fil_actor_hierarchical_sca::exec::{
-    ExecStatus, LockedOutput, LockedStateInfo, SubmitExecParams, SubmitOutput,
+    ExecStatus, HierarchicalAddr, LockedOutput, LockedStateInfo, SubmitExecParams, SubmitOutput,
 };
 use fil_actor_hierarchical_sca::{
-    get_bottomup_msg, subnet, Actor as SCAActor, Checkpoint, State, StorableMsg,
-    DEFAULT_CHECKPOINT_PERIOD,
+    events, ext, gas, get_bottomup_msg, get_topdown_msg, merkle, subnet, Actor as SCAActor,
+    ApplyMsgParams, Asset, AssetAmount, Checkpoint, ClaimFundsParams, CrossMsgEnvelope,
+    CrossMsgMeta, CrossMsgParams, Method, RegisterAssetParams, RegisterParams, SnapshotBalance,
+    State, StorableMsg, Validator, DEFAULT_CHECKPOINT_PERIOD, MAX_CHECKPOINT_CHILDREN,
+    MAX_CHECKPOINT_CROSS_MSGS, MAX_CHILD_CHECKS, MAX_NONCE, MAX_SUBNET_NAME_LENGTH, NATIVE_ASSET,
+    TERMINATION_GRACE_CHECKPOINTS,
 };
 
 use crate::harness::*;
@@ -34,6 +46,13 @@ fn construct() {
     let h = new_harness(ROOTNET_ID.clone());
     h.construct_and_verify(&mut rt);
     h.check_state();
+
+    // any actor can query the subnet governed by the SCA
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1000));
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<SCAActor>(Method::NetworkName as MethodNum, &RawBytes::default()).unwrap();
+    rt.verify();
+    assert_eq!(RawBytes::deserialize::<SubnetID>(&ret).unwrap(), *ROOTNET_ID);
 }
 
 #[test]
@@ -82,6 +101,93 @@ fn register_subnet() {
     h.check_state();
 }
 
+#[test]
+fn invalid_params_and_method() {
+    let (_, mut rt) = setup_root();
+
+    // params are decoded before the method runs
+    rt.set_caller(*SUBNET_ACTOR_CODE_ID, *SUBNET_ONE);
+    let err =
+        rt.call::<SCAActor>(Method::Register as MethodNum, &RawBytes::new(vec![0xff])).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::USR_SERIALIZATION);
+
+    let err = rt.call::<SCAActor>(MethodNum::MAX, &RawBytes::default()).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::USR_UNHANDLED_MESSAGE);
+    rt.verify();
+}
+
+#[test]
+fn list_and_get_subnets() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    rt.set_epoch(5);
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    h.register(&mut rt, &SUBNET_TWO, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+
+    // get subnet with registration metadata
+    let subnet = h.query_subnet(&mut rt, &shid).unwrap();
+    assert_eq!(subnet.id, shid);
+    assert_eq!(subnet.stake, value);
+    assert_eq!(subnet.name, format!("subnet-{}", *SUBNET_ONE));
+    assert_eq!(subnet.consensus, subnet::ConsensusType::Delegated);
+    assert_eq!(subnet.genesis_epoch, 5);
+    assert_eq!(subnet.code, *SUBNET_ACTOR_CODE_ID);
+
+    // unknown subnet
+    let unknown = SubnetID::new(&h.net_name, Address::new_id(1003));
+    let err = h.query_subnet(&mut rt, &unknown).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::USR_NOT_FOUND);
+
+    // list all subnets
+    let ret = h.list_subnets(&mut rt, 0, 0).unwrap();
+    assert_eq!(ret.subnets.len(), 2);
+    assert_eq!(ret.next_offset, None);
+
+    // paginate subnets
+    let first = h.list_subnets(&mut rt, 0, 1).unwrap();
+    assert_eq!(first.subnets.len(), 1);
+    assert_eq!(first.next_offset, Some(1));
+    let second = h.list_subnets(&mut rt, 1, 1).unwrap();
+    assert_eq!(second.subnets.len(), 1);
+    assert_eq!(second.next_offset, None);
+    assert_ne!(first.subnets[0].id, second.subnets[0].id);
+}
+
+#[test]
+fn register_params() {
+    let (h, mut rt) = setup_root();
+    let value = TokenAmount::from(10_u64.pow(18));
+
+    // subnet actors deployed before the params were introduced register without them
+    rt.set_caller(*SUBNET_ACTOR_CODE_ID, *SUBNET_ONE);
+    rt.set_value(value.clone());
+    rt.set_balance(value.clone());
+    rt.expect_validate_caller_type(vec![*SUBNET_ACTOR_CODE_ID]);
+    let ret = rt.call::<SCAActor>(Method::Register as MethodNum, &RawBytes::default()).unwrap();
+    rt.verify();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+    assert_eq!(RawBytes::deserialize::<SubnetID>(&ret).unwrap(), shid);
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.name, shid.to_string());
+    assert_eq!(subnet.consensus, subnet::ConsensusType::Unknown);
+
+    // names are capped
+    rt.set_caller(*SUBNET_ACTOR_CODE_ID, *SUBNET_TWO);
+    rt.expect_validate_caller_type(vec![*SUBNET_ACTOR_CODE_ID]);
+    let params = RegisterParams {
+        name: "a".repeat(MAX_SUBNET_NAME_LENGTH + 1),
+        consensus: subnet::ConsensusType::Mir,
+    };
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<SCAActor>(Method::Register as MethodNum, &RawBytes::serialize(params).unwrap()),
+    );
+    rt.verify();
+    h.check_state();
+}
+
 #[test]
 fn add_stake() {
     let (h, mut rt) = setup_root();
@@ -174,6 +280,23 @@ fn release_stake() {
     // Not enough funds to release
     h.release_stake(&mut rt, &shid, &value, ExitCode::USR_ILLEGAL_STATE).unwrap();
 
+    // Inactive subnets can't commit checkpoints
+    let ch = new_checkpoint(&shid, 9);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_STATE, TokenAmount::zero())
+        .unwrap();
+
+    // Topping up the stake reactivates the subnet
+    h.add_stake(&mut rt, &shid, &TokenAmount::from(5u64.pow(17)), ExitCode::OK).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.stake, value.clone());
+    assert_eq!(subnet.status, subnet::Status::Active);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    // Release enough to inactivate again
+    h.release_stake(&mut rt, &shid, &TokenAmount::from(5u64.pow(17)), ExitCode::OK).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.status, subnet::Status::Inactive);
+
     // Balance is not enough to release
     //, ExitCode::OK).unwrap();
     rt.set_balance(TokenAmount::zero());
@@ -199,11 +322,47 @@ fn test_kill() {
     assert_eq!(subnet.status, subnet::Status::Active);
     h.check_state();
 
-    // Add some stake
+    // Kill starts the grace period
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+    h.kill(&mut rt, &shid, &TokenAmount::zero(), ExitCode::OK).unwrap();
+    let st: State = rt.get_state();
+    assert_eq!(st.total_subnets, 1);
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.status, subnet::Status::Terminating);
+    assert_eq!(subnet.stake, value);
+    let grace_period_end = epoch + st.check_period * TERMINATION_GRACE_CHECKPOINTS;
+    assert_eq!(subnet.grace_period_end, grace_period_end);
+
+    // Terminating subnets can't add or release stake
+    h.add_stake(&mut rt, &shid, &value, ExitCode::USR_ILLEGAL_STATE).unwrap();
+    h.release_stake(&mut rt, &shid, &value, ExitCode::USR_ILLEGAL_STATE).unwrap();
+
+    // Final checkpoints are accepted during the grace period
+    let ch = new_checkpoint(&shid, epoch + 9);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    // Subnet can't be killed before the grace period is over
+    h.kill(&mut rt, &shid, &value, ExitCode::USR_ILLEGAL_STATE).unwrap();
+
+    // Checkpoints are not accepted after the grace period
+    rt.set_epoch(grace_period_end + 1);
+    let mut ch = new_checkpoint(&shid, epoch + 19);
+    ch.data.prev_check = TCid::from(new_checkpoint(&shid, epoch + 9).cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_STATE, TokenAmount::zero())
+        .unwrap();
+
+    // Kill the subnet and release its stake
     h.kill(&mut rt, &shid, &value, ExitCode::OK).unwrap();
     let st: State = rt.get_state();
     assert_eq!(st.total_subnets, 0);
-    assert!(h.get_subnet(&rt, &shid).is_none());
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.status, subnet::Status::Killed);
+    assert_eq!(subnet.stake, TokenAmount::zero());
+
+    // Killed subnets can't be killed again nor register
+    h.kill(&mut rt, &shid, &value, ExitCode::USR_ILLEGAL_STATE).unwrap();
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::USR_ILLEGAL_ARGUMENT).unwrap();
 }
 
 #[test]
@@ -227,7 +386,7 @@ fn checkpoint_commit() {
     // Commit first checkpoint for first window in first subnet
     let epoch: ChainEpoch = 10;
     rt.set_epoch(epoch);
-    let ch = Checkpoint::new(shid.clone(), epoch + 9);
+    let ch = new_checkpoint(&shid, epoch + 9);
 
     h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
     let st: State = rt.get_state();
@@ -243,7 +402,7 @@ fn checkpoint_commit() {
     let prev_cid = ch.cid();
 
     // Append a new checkpoint for the same subnet
-    let mut ch = Checkpoint::new(shid.clone(), epoch + 11);
+    let mut ch = new_checkpoint(&shid, epoch + 11);
     ch.data.prev_check = TCid::from(prev_cid);
     h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
     let st: State = rt.get_state();
@@ -264,7 +423,7 @@ fn checkpoint_commit() {
     h.check_state();
 
     // Trying to commit from the wrong subnet
-    let ch = Checkpoint::new(shid.clone(), epoch + 9);
+    let ch = new_checkpoint(&shid, epoch + 9);
     h.commit_child_check(
         &mut rt,
         &shid_two,
@@ -277,7 +436,7 @@ fn checkpoint_commit() {
     // Commit first checkpoint for first window in second subnet
     let epoch: ChainEpoch = 10;
     rt.set_epoch(epoch);
-    let ch = Checkpoint::new(shid_two.clone(), epoch + 9);
+    let ch = new_checkpoint(&shid_two, epoch + 9);
 
     h.commit_child_check(&mut rt, &shid_two, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
     let st: State = rt.get_state();
@@ -288,6 +447,70 @@ fn checkpoint_commit() {
     assert_eq!(has_cid(&child_check.checks, &ch.cid()), true);
 }
 
+#[test]
+fn checkpoint_tipset() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+
+    // checkpoints need to include a tipset
+    let ch = Checkpoint::new(shid.clone(), epoch + 9);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // malformed tipset key
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    ch.data.tip_set.pop();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // no state root
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    ch.data.state_root = Cid::default();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    let ch = new_checkpoint(&shid, epoch + 9);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    let prev = subnet.prev_checkpoint.unwrap();
+    assert_eq!(prev.data.tipset_cids().unwrap().len(), 1);
+    assert_eq!(prev.data.state_root, ch.data.state_root);
+
+    // checkpoints can't go back to a previous epoch
+    let mut ch = new_checkpoint(&shid, epoch + 5);
+    ch.data.prev_check = TCid::from(prev.cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // nor roll back to a tipset already committed
+    let mut ch = new_checkpoint(&shid, epoch + 19);
+    ch.data.tip_set = prev.data.tip_set.clone();
+    ch.data.prev_check = TCid::from(prev.cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // a different tipset can be committed in the same epoch
+    let mut ch = checkpoint_with_block(&shid, epoch + 9, "reorg");
+    ch.data.prev_check = TCid::from(prev.cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    let mut next = new_checkpoint(&shid, epoch + 19);
+    next.data.prev_check = TCid::from(ch.cid());
+    h.commit_child_check(&mut rt, &shid, &next, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    // but not one committed before the previous checkpoint
+    let mut ch = new_checkpoint(&shid, epoch + 29);
+    ch.data.tip_set = prev.data.tip_set.clone();
+    ch.data.prev_check = TCid::from(next.cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+}
+
 #[test]
 fn checkpoint_crossmsgs() {
     let (h, mut rt) = setup_root();
@@ -309,7 +532,7 @@ fn checkpoint_crossmsgs() {
     // Commit first checkpoint for first window in first subnet
     let epoch: ChainEpoch = 10;
     rt.set_epoch(epoch);
-    let mut ch = Checkpoint::new(shid.clone(), epoch + 9);
+    let mut ch = new_checkpoint(&shid, epoch + 9);
     // Directed to other subnets
     add_msg_meta(
         &mut ch,
@@ -364,7 +587,7 @@ fn checkpoint_crossmsgs() {
     let amount = TokenAmount::from(10_u64.pow(18));
     h.fund(&mut rt, &funder, &shid, ExitCode::OK, amount.clone(), 1, &amount).unwrap();
 
-    let mut ch = Checkpoint::new(shid.clone(), epoch + 9);
+    let mut ch = checkpoint_with_block(&shid, epoch + 9, "reorg");
     ch.data.prev_check = TCid::from(prev_cid);
     add_msg_meta(
         &mut ch,
@@ -405,6 +628,307 @@ fn checkpoint_crossmsgs() {
     // TODO: More extensive tests?
 }
 
+#[test]
+fn checkpoint_merkle_roots() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    for to in ["/root/f0102/f0101", "/root/f0102/f0102", "/root/f0102/f0103"] {
+        add_msg_meta(
+            &mut ch,
+            &shid,
+            &SubnetID::from_str(to).unwrap(),
+            to.as_bytes().to_vec(),
+            TokenAmount::zero(),
+        );
+    }
+
+    // every meta can be proven against the root committed in the checkpoint
+    for meta in ch.data.cross_msgs.iter() {
+        let proof = ch.prove_crossmsg_meta(meta).unwrap();
+        assert!(merkle::verify(&ch.data.cross_msgs_root, meta, &proof).unwrap());
+    }
+
+    // tampered roots are rejected
+    let mut wrong = ch.clone();
+    wrong.data.cross_msgs_root = vec![0; 32];
+    h.commit_child_check(
+        &mut rt,
+        &shid,
+        &wrong,
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        TokenAmount::zero(),
+    )
+    .unwrap();
+    let mut wrong = ch.clone();
+    wrong.data.cross_msgs.pop();
+    h.commit_child_check(
+        &mut rt,
+        &shid,
+        &wrong,
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        TokenAmount::zero(),
+    )
+    .unwrap();
+
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+    let st: State = rt.get_state();
+    let commit = st.get_window_checkpoint(rt.store(), epoch).unwrap();
+    commit.data.check_roots().unwrap();
+    let child_check = has_childcheck_source(&commit.data.children, &shid).unwrap();
+    let proof = commit.prove_child_check(child_check).unwrap();
+    assert!(merkle::verify(&commit.data.children_root, child_check, &proof).unwrap());
+    for meta in commit.data.cross_msgs.iter() {
+        let proof = commit.prove_crossmsg_meta(meta).unwrap();
+        assert!(merkle::verify(&commit.data.cross_msgs_root, meta, &proof).unwrap());
+    }
+}
+
+#[test]
+fn checkpoint_limits() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+
+    // too many cross-msg metas
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    let to = SubnetID::from_str("/root/f0102").unwrap();
+    ch.data.cross_msgs = vec![CrossMsgMeta::new(&shid, &to); MAX_CHECKPOINT_CROSS_MSGS + 1];
+    ch.data.update_roots().unwrap();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // too many children
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    let child = ChildCheck { source: SubnetID::new(&shid, *SUBNET_ONE), checks: Vec::new() };
+    ch.data.children = vec![child.clone(); MAX_CHECKPOINT_CHILDREN + 1];
+    ch.data.update_roots().unwrap();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // too many checkpoints from a single child
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    let checks = vec![TCid::from(ch.cid()); MAX_CHILD_CHECKS + 1];
+    ch.data.children = vec![ChildCheck { checks, ..child.clone() }];
+    ch.data.update_roots().unwrap();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+
+    // gas is charged for every child and checkpoint in checkpoints within the limits
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    let checks = vec![TCid::from(ch.cid()); MAX_CHILD_CHECKS];
+    ch.data.children = vec![child; MAX_CHECKPOINT_CHILDREN - 1];
+    ch.data.children.push(ChildCheck { source: SubnetID::new(&shid, *SUBNET_TWO), checks });
+    ch.data.update_roots().unwrap();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    // the window checkpoint only takes a bounded number of checkpoints from a child
+    let mut prev = ch;
+    for i in 1..MAX_CHILD_CHECKS {
+        let mut ch = checkpoint_with_block(&shid, epoch + 9, &i.to_string());
+        ch.data.prev_check = TCid::from(prev.cid());
+        h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+        prev = ch;
+    }
+    let mut ch = checkpoint_with_block(&shid, epoch + 9, "full");
+    ch.data.prev_check = TCid::from(prev.cid());
+    // the checkpoint is rejected once its processing was charged
+    rt.expect_gas_charge(0);
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+    let st: State = rt.get_state();
+    let window = st.get_window_checkpoint(rt.store(), epoch).unwrap();
+    let child_check = has_childcheck_source(&window.data.children, &shid).unwrap();
+    assert_eq!(MAX_CHILD_CHECKS, child_check.checks.len());
+}
+
+#[test]
+fn test_claim_funds() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+
+    // funding subnet so it has some funds
+    let funder = Address::new_id(1001);
+    let amount = TokenAmount::from(10_u64.pow(18));
+    h.fund(&mut rt, &funder, &shid, ExitCode::OK, amount.clone(), 1, &amount).unwrap();
+    rt.set_balance(&value + &amount);
+
+    // commit checkpoint with the snapshot of balances in the subnet
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+    let balances = vec![
+        SnapshotBalance { addr: *TEST_BLS, value: amount.clone() },
+        SnapshotBalance { addr: Address::new_id(1002), value: TokenAmount::zero() },
+    ];
+    let mut ch = new_checkpoint(&shid, epoch + 9);
+    ch.set_balances(&balances).unwrap();
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+
+    let proof = merkle::prove(&balances, &balances[0]).unwrap();
+    let params =
+        ClaimFundsParams { subnet: shid.clone(), value: amount.clone(), proof: proof.clone() };
+
+    // subnet still committing checkpoints
+    h.claim_funds(&mut rt, &funder, params.clone(), ExitCode::USR_ILLEGAL_STATE).unwrap();
+
+    // subnet missed enough checkpoints
+    let st: State = rt.get_state();
+    rt.set_epoch(epoch + st.check_period * st.escape_hatch_periods + 1);
+
+    // funds can't be sent to the subnet once users can claim theirs
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, funder);
+    rt.expect_validate_caller_type(vec![*ACCOUNT_ACTOR_CODE_ID, *MULTISIG_ACTOR_CODE_ID]);
+    rt.expect_send(
+        funder,
+        ext::account::PUBKEY_ADDRESS_METHOD,
+        RawBytes::default(),
+        TokenAmount::zero(),
+        RawBytes::serialize(*TEST_BLS).unwrap(),
+        ExitCode::OK,
+    );
+    rt.set_value(amount.clone());
+    expect_abort(
+        ExitCode::USR_ILLEGAL_STATE,
+        rt.call::<SCAActor>(Method::Fund as MethodNum, &RawBytes::serialize(&shid).unwrap()),
+    );
+    rt.verify();
+    rt.set_caller(*PAYCH_ACTOR_CODE_ID, Address::new_id(1003));
+    rt.expect_validate_caller_type(vec![
+        *ACCOUNT_ACTOR_CODE_ID,
+        *MULTISIG_ACTOR_CODE_ID,
+        *PAYCH_ACTOR_CODE_ID,
+    ]);
+    let msg = StorableMsg { to: *TEST_BLS, value: amount.clone(), ..Default::default() };
+    let cross = CrossMsgParams { destination: shid.clone(), msg };
+    expect_abort(
+        ExitCode::USR_ILLEGAL_STATE,
+        rt.call::<SCAActor>(Method::SendCross as MethodNum, &RawBytes::serialize(cross).unwrap()),
+    );
+    rt.verify();
+
+    // claiming a different balance fails
+    let wrong = ClaimFundsParams {
+        subnet: shid.clone(),
+        value: TokenAmount::from(2_u64) * &amount,
+        proof: proof.clone(),
+    };
+    h.claim_funds(&mut rt, &funder, wrong, ExitCode::USR_ILLEGAL_ARGUMENT).unwrap();
+
+    h.claim_funds(&mut rt, &funder, params.clone(), ExitCode::OK).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.circ_supply, TokenAmount::zero());
+
+    // funds can only be claimed once
+    h.claim_funds(&mut rt, &funder, params, ExitCode::USR_ILLEGAL_ARGUMENT).unwrap();
+
+    // checkpoints are not accepted anymore
+    let mut ch = new_checkpoint(&shid, epoch + 19);
+    ch.data.prev_check =
+        TCid::from(h.get_subnet(&rt, &shid).unwrap().prev_checkpoint.unwrap().cid());
+    h.commit_child_check(&mut rt, &shid, &ch, ExitCode::USR_ILLEGAL_STATE, TokenAmount::zero())
+        .unwrap();
+}
+
+#[test]
+fn test_escape_hatch_paused_while_inactive() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+    let st: State = rt.get_state();
+    let window = st.check_period * st.escape_hatch_periods;
+
+    // the subnet becomes inactive half-way through the window
+    rt.set_epoch(window / 2);
+    rt.set_balance(value.clone());
+    h.release_stake(&mut rt, &shid, &TokenAmount::from(5u64.pow(17)), ExitCode::OK).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.status, subnet::Status::Inactive);
+
+    // the escape hatch doesn't open while it can't commit checkpoints
+    rt.set_epoch(3 * window);
+    assert!(!subnet.escape_hatch_open(&st, rt.epoch));
+
+    // once reactivated, the subnet has the rest of the window to commit a checkpoint
+    h.add_stake(&mut rt, &shid, &TokenAmount::from(5u64.pow(17)), ExitCode::OK).unwrap();
+    let subnet = h.get_subnet(&rt, &shid).unwrap();
+    assert_eq!(subnet.status, subnet::Status::Active);
+    assert!(!subnet.escape_hatch_open(&st, 3 * window + window / 2));
+    assert!(subnet.escape_hatch_open(&st, 3 * window + window / 2 + 1));
+}
+
+#[test]
+fn test_validator_sets() {
+    let (h, mut rt) = setup_root();
+
+    let value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &value, ExitCode::OK).unwrap();
+    let shid = SubnetID::new(&h.net_name, *SUBNET_ONE);
+    let validator = |id: u64, weight: u64| Validator {
+        addr: Address::new_id(id),
+        collateral: TokenAmount::from(weight * 10_u64.pow(17)),
+        weight: BigInt::from(weight),
+    };
+
+    // no validator set reported yet
+    let res = h.query_validator_set(&mut rt, &shid, 0);
+    assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_NOT_FOUND);
+
+    rt.set_epoch(10);
+    let first = vec![validator(1001, 1), validator(1002, 1), validator(1003, 2)];
+    h.set_validators(&mut rt, &shid, first.clone(), ExitCode::OK).unwrap();
+    rt.set_epoch(20);
+    let second = vec![validator(1001, 2), validator(1004, 3)];
+    h.set_validators(&mut rt, &shid, second.clone(), ExitCode::OK).unwrap();
+
+    // the set in effect at each epoch is returned
+    let res = h.query_validator_set(&mut rt, &shid, 5);
+    assert_eq!(res.unwrap_err().exit_code(), ExitCode::USR_NOT_FOUND);
+    let set = h.query_validator_set(&mut rt, &shid, 15).unwrap();
+    assert_eq!(set.epoch, 10);
+    assert_eq!(set.validators, first);
+    assert!(set.has_quorum(&[Address::new_id(1001), Address::new_id(1003)]));
+    assert!(!set.has_quorum(&[Address::new_id(1001), Address::new_id(1002)]));
+    let set = h.query_validator_set(&mut rt, &shid, 100).unwrap();
+    assert_eq!(set.validators, second);
+
+    // a set reported in the same epoch replaces the latest one
+    let third = vec![validator(1005, 1)];
+    h.set_validators(&mut rt, &shid, third.clone(), ExitCode::OK).unwrap();
+    let set = h.query_validator_set(&mut rt, &shid, 20).unwrap();
+    assert_eq!(set.validators, third);
+
+    // the collateral of validators can't exceed the stake of the subnet
+    h.set_validators(&mut rt, &shid, vec![validator(1001, 11)], ExitCode::USR_ILLEGAL_ARGUMENT)
+        .unwrap();
+    // duplicate validators are rejected
+    h.set_validators(
+        &mut rt,
+        &shid,
+        vec![validator(1001, 1), validator(1001, 1)],
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    )
+    .unwrap();
+    // unregistered subnets can't report validators
+    let other = SubnetID::new(&h.net_name, *SUBNET_TWO);
+    h.set_validators(&mut rt, &other, first, ExitCode::USR_ILLEGAL_ARGUMENT).unwrap();
+}
+
 #[test]
 fn test_fund() {
     let (h, mut rt) = setup_root();
@@ -505,6 +1029,142 @@ fn test_send_cross() {
     h.send_cross(&mut rt, &from, &to, sub, ExitCode::OK, value.clone(), 0, &zero).unwrap();
 }
 
+#[test]
+fn test_send_cross_from_paych() {
+    let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
+    let (h, mut rt) = setup(shid.clone());
+
+    // register subnet
+    let reg_value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &reg_value, ExitCode::OK).unwrap();
+
+    // payment channels settle payouts in other subnets through cross-messages,
+    // and they are identified by their ID address as they don't have a key.
+    let paych = Address::new_id(1001);
+    let to = Address::new_bls(&[4; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let sub = SubnetID::new(&shid, *SUBNET_ONE);
+    let value = TokenAmount::from(10_u64.pow(17));
+    rt.set_caller(*PAYCH_ACTOR_CODE_ID, paych);
+    rt.expect_validate_caller_type(vec![
+        *ACCOUNT_ACTOR_CODE_ID,
+        *MULTISIG_ACTOR_CODE_ID,
+        *PAYCH_ACTOR_CODE_ID,
+    ]);
+    rt.set_value(value.clone());
+    let msg = StorableMsg { to, method: METHOD_SEND, value: value.clone(), ..Default::default() };
+    let params = CrossMsgParams { destination: sub.clone(), msg: msg.clone() };
+    let expected_msg = StorableMsg {
+        from: Address::new_hierarchical(&shid, &paych).unwrap(),
+        to: Address::new_hierarchical(&sub, &to).unwrap(),
+        ..msg
+    };
+    rt.expect_emitted_event(events::send_cross(&sub, &expected_msg).unwrap());
+    rt.call::<SCAActor>(Method::SendCross as MethodNum, &RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+
+    let subnet = h.get_subnet(&rt, &sub).unwrap();
+    assert_eq!(subnet.circ_supply, value);
+    let crossmsgs = subnet.top_down_msgs.load(rt.store()).unwrap();
+    let msg = get_topdown_msg(&crossmsgs, 0).unwrap().unwrap();
+    assert_eq!(msg, expected_msg);
+}
+
+#[test]
+fn test_apply_msg_envelope() {
+    let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
+    let (_, mut rt) = setup(shid.clone());
+
+    // multisigs get the sender of cross-messages in an envelope,
+    // so they can count approvals from signers in other subnets.
+    let msig = Address::new_id(1001);
+    rt.actor_code_cids.insert(msig, *MULTISIG_ACTOR_CODE_ID);
+
+    let sub1 = SubnetID::new(&shid, *SUBNET_ONE);
+    let signer = Address::new_bls(&[3; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let from = Address::new_hierarchical(&sub1, &signer).unwrap();
+    let to = Address::new_hierarchical(&shid, &msig).unwrap();
+    let params = RawBytes::new(vec![1, 2, 3]);
+    let msg = StorableMsg {
+        from,
+        to,
+        method: 3,
+        params: params.clone(),
+        value: TokenAmount::zero(),
+        nonce: 0,
+        asset: NATIVE_ASSET,
+    };
+
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    rt.expect_send(
+        msig,
+        3,
+        RawBytes::serialize(CrossMsgEnvelope { from, params }).unwrap(),
+        TokenAmount::zero(),
+        RawBytes::default(),
+        ExitCode::OK,
+    );
+    let params = ApplyMsgParams { msg, meta_from: Some(sub1) };
+    rt.call::<SCAActor>(Method::ApplyMessage as MethodNum, &RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+}
+
+#[test]
+fn test_apply_msg_forged_sender() {
+    let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
+    let (h, mut rt) = setup(shid.clone());
+
+    let msig = Address::new_id(1001);
+    rt.actor_code_cids.insert(msig, *MULTISIG_ACTOR_CODE_ID);
+
+    let reg_value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &reg_value, ExitCode::OK).unwrap();
+    h.register(&mut rt, &SUBNET_TWO, &reg_value, ExitCode::OK).unwrap();
+    let sub1 = SubnetID::new(&shid, *SUBNET_ONE);
+    let sub2 = SubnetID::new(&shid, *SUBNET_TWO);
+
+    // a message committed by the first subnet claiming to come from a signer in its sibling
+    let signer = Address::new_bls(&[3; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let msg = StorableMsg {
+        from: Address::new_hierarchical(&sub2, &signer).unwrap(),
+        to: Address::new_hierarchical(&shid, &msig).unwrap(),
+        method: 3,
+        params: RawBytes::new(vec![1, 2, 3]),
+        value: TokenAmount::zero(),
+        nonce: 0,
+        asset: NATIVE_ASSET,
+    };
+    let apply = |rt: &mut MockRuntime, meta_from: Option<SubnetID>, code: ExitCode| {
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+        let params = ApplyMsgParams { msg: msg.clone(), meta_from };
+        expect_abort(
+            code,
+            rt.call::<SCAActor>(
+                Method::ApplyMessage as MethodNum,
+                &RawBytes::serialize(params).unwrap(),
+            ),
+        );
+        rt.verify();
+    };
+    apply(&mut rt, Some(sub1.clone()), ExitCode::USR_FORBIDDEN);
+    // the origin of bottom-up messages needs to be known, and be a child subnet
+    apply(&mut rt, None, ExitCode::USR_ILLEGAL_ARGUMENT);
+    apply(&mut rt, Some(shid.clone()), ExitCode::USR_ILLEGAL_ARGUMENT);
+    let st: State = rt.get_state();
+    assert_eq!(st.applied_bottomup_nonce, MAX_NONCE);
+
+    // children can't commit metas of cross-msgs coming from other subnets
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+    let mut ch = new_checkpoint(&sub1, epoch + 9);
+    add_msg_meta(&mut ch, &sub2, &shid, "forged".as_bytes().to_vec(), TokenAmount::zero());
+    h.commit_child_check(&mut rt, &sub1, &ch, ExitCode::USR_ILLEGAL_ARGUMENT, TokenAmount::zero())
+        .unwrap();
+}
+
 #[test]
 fn test_apply_routing() {
     let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
@@ -624,6 +1284,174 @@ fn test_apply_msg() {
     // TODO: Trying to release over circulating supply
 }
 
+#[test]
+fn test_cross_assets() {
+    let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
+    let (h, mut rt) = setup(shid.clone());
+
+    let token = Address::new_id(1200);
+    let params = RegisterAssetParams { id: 1, symbol: "WETH".to_string(), token };
+
+    // only the system actor can register assets
+    h.register_asset(&mut rt, &Address::new_id(1001), params.clone(), ExitCode::USR_FORBIDDEN)
+        .unwrap();
+    h.register_asset(&mut rt, &SYSTEM_ACTOR_ADDR, params.clone(), ExitCode::OK).unwrap();
+    // can't register the same asset twice or the native asset
+    h.register_asset(&mut rt, &SYSTEM_ACTOR_ADDR, params.clone(), ExitCode::USR_ILLEGAL_ARGUMENT)
+        .unwrap();
+    let mut native = params.clone();
+    native.id = NATIVE_ASSET;
+    h.register_asset(&mut rt, &SYSTEM_ACTOR_ADDR, native, ExitCode::USR_ILLEGAL_ARGUMENT).unwrap();
+
+    let reg_value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &reg_value, ExitCode::OK).unwrap();
+    let sub1 = SubnetID::new(&shid, *SUBNET_ONE);
+
+    // top-down with a wrapped asset
+    let asset = Asset { id: params.id, symbol: params.symbol.clone(), token };
+    let from = Address::new_id(1001);
+    let to = Address::new_id(1002);
+    let value = TokenAmount::from(10_u64.pow(17));
+    h.send_asset_cross(&mut rt, &from, &to, sub1.clone(), &asset, value.clone(), ExitCode::OK)
+        .unwrap();
+    h.send_asset_cross(&mut rt, &from, &to, sub1.clone(), &asset, value.clone(), ExitCode::OK)
+        .unwrap();
+    let sub = h.get_subnet(&rt, &sub1).unwrap();
+    assert_eq!(sub.supply_of(asset.id), 2 * &value);
+    assert_eq!(sub.circ_supply, TokenAmount::zero());
+    let crossmsgs = sub.top_down_msgs.load(rt.store()).unwrap();
+    let msg = get_topdown_msg(&crossmsgs, 1).unwrap().unwrap();
+    assert_eq!(msg.asset, asset.id);
+    assert_eq!(msg.value, value);
+
+    // unregistered assets are rejected
+    let unknown = Asset { id: 2, symbol: "UNK".to_string(), token };
+    h.send_asset_cross(
+        &mut rt,
+        &from,
+        &to,
+        sub1.clone(),
+        &unknown,
+        value.clone(),
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    )
+    .unwrap();
+
+    // a subnet holding wrapped assets can't be killed
+    rt.set_balance(reg_value.clone());
+    h.kill(&mut rt, &sub1, &TokenAmount::zero(), ExitCode::OK).unwrap();
+    let st: State = rt.get_state();
+    rt.set_epoch(st.check_period * TERMINATION_GRACE_CHECKPOINTS + 1);
+    h.kill(&mut rt, &sub1, &reg_value, ExitCode::USR_ILLEGAL_STATE).unwrap();
+
+    // applying a top-down message with a wrapped asset mints it for the
+    // destination instead of funding the SCA with native tokens.
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    let rto = Address::new_bls(&[4; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let msg = StorableMsg {
+        from: Address::new_hierarchical(&ROOTNET_ID, &from).unwrap(),
+        to: Address::new_hierarchical(&shid, &rto).unwrap(),
+        method: METHOD_SEND,
+        value: value.clone(),
+        nonce: 0,
+        asset: asset.id,
+        ..Default::default()
+    };
+    let mint = ext::token::MintParams { to: rto, value: value.clone() };
+    rt.expect_send(
+        token,
+        ext::token::MINT_METHOD,
+        RawBytes::serialize(mint).unwrap(),
+        TokenAmount::zero(),
+        RawBytes::default(),
+        ExitCode::OK,
+    );
+    let params = ApplyMsgParams { msg, meta_from: None };
+    rt.call::<SCAActor>(Method::ApplyMessage as MethodNum, &RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+    let st: State = rt.get_state();
+    assert_eq!(st.applied_topdown_nonce, 1);
+}
+
+#[test]
+fn test_bottomup_assets() {
+    let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
+    let (h, mut rt) = setup(shid.clone());
+
+    let token = Address::new_id(1200);
+    let params = RegisterAssetParams { id: 1, symbol: "WETH".to_string(), token };
+    h.register_asset(&mut rt, &SYSTEM_ACTOR_ADDR, params.clone(), ExitCode::OK).unwrap();
+    let asset = Asset { id: params.id, symbol: params.symbol, token };
+
+    let reg_value = TokenAmount::from(10_u64.pow(18));
+    h.register(&mut rt, &SUBNET_ONE, &reg_value, ExitCode::OK).unwrap();
+    let sub1 = SubnetID::new(&shid, *SUBNET_ONE);
+
+    // the subnet holds some of the wrapped asset
+    let from = Address::new_id(1001);
+    let value = TokenAmount::from(10_u64.pow(17));
+    h.send_asset_cross(&mut rt, &from, &from, sub1.clone(), &asset, value.clone(), ExitCode::OK)
+        .unwrap();
+
+    let epoch: ChainEpoch = 10;
+    rt.set_epoch(epoch);
+    let meta_with = |amount: &TokenAmount| {
+        let mut ch = new_checkpoint(&sub1, epoch + 9);
+        add_msg_meta(&mut ch, &sub1, &shid, "assets".as_bytes().to_vec(), TokenAmount::zero());
+        ch.data.cross_msgs[0].add_value(asset.id, amount);
+        ch.data.update_roots().unwrap();
+        ch
+    };
+
+    // the subnet can't release more than its supply of the asset
+    let ch = meta_with(&(2 * &value));
+    rt.expect_gas_charge(gas::GAS_PER_CROSS_MSG_META);
+    h.commit_child_check(&mut rt, &sub1, &ch, ExitCode::USR_ILLEGAL_STATE, TokenAmount::zero())
+        .unwrap();
+
+    // committing the checkpoint releases the supply of the asset, without burning native funds
+    let ch = meta_with(&value);
+    h.commit_child_check(&mut rt, &sub1, &ch, ExitCode::OK, TokenAmount::zero()).unwrap();
+    let sub = h.get_subnet(&rt, &sub1).unwrap();
+    assert_eq!(sub.supply_of(asset.id), TokenAmount::zero());
+    let st: State = rt.get_state();
+    let metas = st.bottomup_msg_meta.load(rt.store()).unwrap();
+    let meta = get_bottomup_msg(&metas, 0).unwrap().unwrap();
+    assert_eq!(meta.assets, vec![AssetAmount { asset: asset.id, value: value.clone() }]);
+    assert_eq!(meta.value, TokenAmount::zero());
+
+    // applying the bottom-up message mints the asset for the destination
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    let rto = Address::new_bls(&[4; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let msg = StorableMsg {
+        from: Address::new_hierarchical(&sub1, &from).unwrap(),
+        to: Address::new_hierarchical(&shid, &rto).unwrap(),
+        method: METHOD_SEND,
+        value: value.clone(),
+        nonce: 0,
+        asset: asset.id,
+        ..Default::default()
+    };
+    let mint = ext::token::MintParams { to: rto, value };
+    rt.expect_send(
+        token,
+        ext::token::MINT_METHOD,
+        RawBytes::serialize(mint).unwrap(),
+        TokenAmount::zero(),
+        RawBytes::default(),
+        ExitCode::OK,
+    );
+    let params = ApplyMsgParams { msg, meta_from: Some(sub1) };
+    rt.call::<SCAActor>(Method::ApplyMessage as MethodNum, &RawBytes::serialize(params).unwrap())
+        .unwrap();
+    rt.verify();
+    let st: State = rt.get_state();
+    assert_eq!(st.applied_bottomup_nonce, 0);
+}
+
 #[test]
 fn test_atomic_exec() {
     let shid = SubnetID::new(&ROOTNET_ID, *SUBNET_ONE);
@@ -847,6 +1675,7 @@ fn gen_exec_msgs(addr: Address) -> Vec<StorableMsg> {
             method: 2,
             params: RawBytes::default(),
             nonce: 0,
+            asset: NATIVE_ASSET,
         },
         StorableMsg {
             from: addr,
@@ -855,6 +1684,7 @@ fn gen_exec_msgs(addr: Address) -> Vec<StorableMsg> {
             method: 2,
             params: RawBytes::default(),
             nonce: 0,
+            asset: NATIVE_ASSET,
         },
     ];
 }
@@ -864,7 +1694,7 @@ fn gen_locked_state(
     sn2: &SubnetID,
     caller: &Address,
     other: &Address,
-) -> HashMap<String, LockedStateInfo> {
+) -> HashMap<HierarchicalAddr, LockedStateInfo> {
     let lock_cid1 = Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(b"test1"));
     let lock_cid2 = Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(b"test2"));
     let addr1 = Address::new_hierarchical(sn1, caller).unwrap();
@@ -872,7 +1702,13 @@ fn gen_locked_state(
     let act1 = Address::new_id(900);
     let act2 = Address::new_id(901);
     let mut m = HashMap::new();
-    m.insert(addr1.to_string(), LockedStateInfo { cid: lock_cid1, actor: act1 });
-    m.insert(addr2.to_string(), LockedStateInfo { cid: lock_cid2, actor: act2 });
+    m.insert(
+        TAddressKey(TAddress::try_from(addr1).unwrap()),
+        LockedStateInfo { cid: lock_cid1, actor: act1 },
+    );
+    m.insert(
+        TAddressKey(TAddress::try_from(addr2).unwrap()),
+        LockedStateInfo { cid: lock_cid2, actor: act2 },
+    );
     m
 }
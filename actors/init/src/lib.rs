@@ -3,15 +3,17 @@
 
 use cid::Cid;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
-use fil_actors_runtime::{actor_error, cbor, ActorDowncast, ActorError, SYSTEM_ACTOR_ADDR};
+use fil_actors_runtime::{
+    actor_dispatch, actor_error, ActorDowncast, ActorError, SYSTEM_ACTOR_ADDR,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
-use fvm_shared::actor::builtin::Type;
+use fvm_shared::actor::builtin::{Type, CALLER_TYPES_SIGNABLE};
 use fvm_shared::address::Address;
 use fvm_shared::error::ExitCode;
-use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR};
+use fvm_shared::{ActorID, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
+use serde::de::IgnoredAny;
 
 pub use self::state::State;
 pub use self::types::*;
@@ -26,7 +28,7 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 // * Updated to specs-actors commit: 999e57a151cc7ada020ca2844b651499ab8c0dec (v3.0.1)
 
 /// Init actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
@@ -76,6 +78,13 @@ impl Actor {
             ));
         }
 
+        // Subnet actors are deployed by end-users, so at least make sure that
+        // they are constructed with well-formed parameters. The rest of the
+        // validation is performed by the subnet actor's constructor.
+        if rt.resolve_builtin_actor_type(&params.code_cid) == Some(Type::Subnet) {
+            check_subnet_params(&params.constructor_params)?;
+        }
+
         // Compute a re-org-stable address.
         // This address exists for use by messages coming from outside the system, in order to
         // stably address the newly created actor even if a chain re-org causes it to end up with
@@ -109,29 +118,26 @@ impl Actor {
 }
 
 impl ActorCode for Actor {
-    fn invoke_method<BS, RT>(
-        rt: &mut RT,
-        method: MethodNum,
-        params: &RawBytes,
-    ) -> Result<RawBytes, ActorError>
-    where
-        BS: Blockstore,
-        RT: Runtime<BS>,
-    {
-        match FromPrimitive::from_u64(method) {
-            Some(Method::Constructor) => {
-                Self::constructor(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Exec) => {
-                let res = Self::exec(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            None => Err(actor_error!(unhandled_message; "Invalid method")),
+    actor_dispatch! {
+        Method {
+            Constructor => constructor(ConstructorParams),
+            Exec => exec(ExecParams) -> ExecReturn,
         }
     }
 }
 
+/// Checks that the constructor params of a subnet actor decode as a tuple with
+/// some fields, as the constructor params of every actor are serialized.
+fn check_subnet_params(params: &RawBytes) -> Result<(), ActorError> {
+    let fields: Vec<IgnoredAny> = params.deserialize().map_err(|e| {
+        actor_error!(illegal_argument, "malformed constructor params for subnet actor: {}", e)
+    })?;
+    if fields.is_empty() {
+        return Err(actor_error!(illegal_argument, "no constructor params for subnet actor"));
+    }
+    Ok(())
+}
+
 fn can_exec<BS, RT>(rt: &RT, caller: &Cid, exec: &Cid) -> bool
 where
     BS: Blockstore,
@@ -141,6 +147,10 @@ where
         .map(|typ| match typ {
             Type::Multisig | Type::PaymentChannel => true,
             Type::Miner if rt.resolve_builtin_actor_type(caller) == Some(Type::Power) => true,
+            Type::Subnet => rt
+                .resolve_builtin_actor_type(caller)
+                .map(|t| CALLER_TYPES_SIGNABLE.contains(&t))
+                .unwrap_or(false),
             _ => false,
         })
         .unwrap_or(false)
@@ -184,6 +184,87 @@ fn create_multisig_actor() {
     check_state(&rt);
 }
 
+#[test]
+fn create_subnet_actor() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    // any signable account can deploy a subnet actor
+    let some_acc_actor = Address::new_id(1234);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, some_acc_actor);
+
+    let unique_address = Address::new_actor(b"subnet");
+    rt.new_actor_addr = Some(unique_address);
+
+    let expected_id = 100;
+    let expected_id_addr = Address::new_id(expected_id);
+    rt.expect_create_actor(*SUBNET_ACTOR_CODE_ID, expected_id);
+
+    let fake_params = ConstructorParams { network_name: String::from("fake_param") };
+    rt.expect_send(
+        expected_id_addr,
+        METHOD_CONSTRUCTOR,
+        RawBytes::serialize(&fake_params).unwrap(),
+        0u8.into(),
+        RawBytes::default(),
+        ExitCode::OK,
+    );
+
+    let exec_ret = exec_and_verify(&mut rt, *SUBNET_ACTOR_CODE_ID, &fake_params).unwrap();
+    let exec_ret: ExecReturn = RawBytes::deserialize(&exec_ret).unwrap();
+    assert_eq!(unique_address, exec_ret.robust_address, "Robust address does not macth");
+    assert_eq!(expected_id_addr, exec_ret.id_address, "Id address does not match");
+
+    let state: State = rt.get_state();
+    let returned_address = state
+        .resolve_address(&rt.store, &unique_address)
+        .expect("Resolve should not error")
+        .expect("Address should be able to be resolved");
+    assert_eq!(expected_id_addr, returned_address);
+    check_state(&rt);
+}
+
+#[test]
+fn abort_non_signable_create_subnet_actor() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    // only signable actors can deploy subnet actors
+    rt.set_caller(*POWER_ACTOR_CODE_ID, *STORAGE_POWER_ACTOR_ADDR);
+
+    let fake_params = ConstructorParams { network_name: String::from("fake_param") };
+    let err = exec_and_verify(&mut rt, *SUBNET_ACTOR_CODE_ID, &fake_params)
+        .expect_err("Exec should have failed");
+    assert_eq!(err.exit_code(), ExitCode::USR_FORBIDDEN);
+    check_state(&rt);
+}
+
+#[test]
+fn abort_create_subnet_actor_with_malformed_params() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    let some_acc_actor = Address::new_id(1234);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, some_acc_actor);
+
+    // params need to decode as a tuple with some fields
+    for constructor_params in [
+        RawBytes::default(),
+        RawBytes::serialize(Vec::<u64>::new()).unwrap(),
+        RawBytes::serialize("fake_param").unwrap(),
+        RawBytes::new(vec![0x82, 0x01]),
+    ] {
+        rt.expect_validate_caller_any();
+        let exec_params = ExecParams { code_cid: *SUBNET_ACTOR_CODE_ID, constructor_params };
+        let err = rt
+            .call::<InitActor>(Method::Exec as u64, &RawBytes::serialize(&exec_params).unwrap())
+            .expect_err("Exec should have failed");
+        rt.verify();
+        assert_eq!(err.exit_code(), ExitCode::USR_ILLEGAL_ARGUMENT);
+    }
+    check_state(&rt);
+}
+
 #[test]
 fn sending_constructor_failure() {
     let mut rt = construct_runtime();
@@ -16,16 +16,17 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PieceInfo;
 use fvm_shared::reward::ThisEpochRewardReturn;
 use fvm_shared::sector::StoragePower;
-use fvm_shared::{ActorID, MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
+use fvm_shared::{ActorID, METHOD_CONSTRUCTOR, METHOD_SEND};
 use log::info;
 use num_derive::FromPrimitive;
-use num_traits::{FromPrimitive, Signed, Zero};
+use num_traits::{Signed, Zero};
 
 use fil_actors_runtime::cbor::serialize_vec;
 use fil_actors_runtime::runtime::{ActorCode, Policy, Runtime};
 use fil_actors_runtime::{
-    actor_error, cbor, ActorDowncast, ActorError, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR,
-    REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
+    actor_dispatch, actor_error, method_hash, ActorDowncast, ActorError, BURNT_FUNDS_ACTOR_ADDR,
+    CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 
 use crate::ext::verifreg::UseBytesParams;
@@ -72,7 +73,7 @@ where
 // * Updated to specs-actors commit: e195950ba98adb8ce362030356bf4a3809b7ec77 (v2.3.2)
 
 /// Market actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
@@ -84,6 +85,10 @@ pub enum Method {
     OnMinerSectorsTerminate = 7,
     ComputeDataCommitment = 8,
     CronTick = 9,
+    // Methods exported to user actors, under their FRC-0042 numbers.
+    AddBalanceExported = method_hash!("AddBalance"),
+    WithdrawBalanceExported = method_hash!("WithdrawBalance"),
+    PublishStorageDealsExported = method_hash!("PublishStorageDeals"),
 }
 
 /// Market Actor
@@ -1325,53 +1330,20 @@ where
 }
 
 impl ActorCode for Actor {
-    fn invoke_method<BS, RT>(
-        rt: &mut RT,
-        method: MethodNum,
-        params: &RawBytes,
-    ) -> Result<RawBytes, ActorError>
-    where
-        BS: Blockstore,
-        RT: Runtime<BS>,
-    {
-        match FromPrimitive::from_u64(method) {
-            Some(Method::Constructor) => {
-                Self::constructor(rt)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::AddBalance) => {
-                Self::add_balance(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::WithdrawBalance) => {
-                let res = Self::withdraw_balance(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::PublishStorageDeals) => {
-                let res = Self::publish_storage_deals(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::VerifyDealsForActivation) => {
-                let res = Self::verify_deals_for_activation(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::ActivateDeals) => {
-                Self::activate_deals(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::OnMinerSectorsTerminate) => {
-                Self::on_miner_sectors_terminate(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::ComputeDataCommitment) => {
-                let res = Self::compute_data_commitment(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::CronTick) => {
-                Self::cron_tick(rt)?;
-                Ok(RawBytes::default())
-            }
-            None => Err(actor_error!(unhandled_message, "Invalid method")),
+    actor_dispatch! {
+        restricted Method {
+            Constructor => constructor(),
+            AddBalance => add_balance(Address),
+            WithdrawBalance => withdraw_balance(WithdrawBalanceParams) -> WithdrawBalanceReturn,
+            PublishStorageDeals => publish_storage_deals(PublishStorageDealsParams) -> PublishStorageDealsReturn,
+            VerifyDealsForActivation => verify_deals_for_activation(VerifyDealsForActivationParams) -> VerifyDealsForActivationReturn,
+            ActivateDeals => activate_deals(ActivateDealsParams),
+            OnMinerSectorsTerminate => on_miner_sectors_terminate(OnMinerSectorsTerminateParams),
+            ComputeDataCommitment => compute_data_commitment(ComputeDataCommitmentParams) -> ComputeDataCommitmentReturn,
+            CronTick => cron_tick(),
+            AddBalanceExported => add_balance(Address),
+            WithdrawBalanceExported => withdraw_balance(WithdrawBalanceParams) -> WithdrawBalanceReturn,
+            PublishStorageDealsExported => publish_storage_deals(PublishStorageDealsParams) -> PublishStorageDealsReturn,
         }
     }
 }
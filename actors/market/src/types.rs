@@ -18,7 +18,7 @@ use super::deal::{ClientDealProposal, DealProposal, DealState};
 pub const PROPOSALS_AMT_BITWIDTH: u32 = 5;
 pub const STATES_AMT_BITWIDTH: u32 = 6;
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct WithdrawBalanceParams {
     pub provider_or_client: Address,
     #[serde(with = "bigint_ser")]
@@ -27,7 +27,7 @@ pub struct WithdrawBalanceParams {
 
 impl Cbor for WithdrawBalanceParams {}
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 #[serde(transparent)]
 pub struct WithdrawBalanceReturn {
     #[serde(with = "bigint_ser")]
@@ -47,7 +47,7 @@ pub struct OnMinerSectorsTerminateParamsRef<'a> {
     pub deal_ids: &'a [DealID],
 }
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct PublishStorageDealsParams {
     pub deals: Vec<ClientDealProposal>,
 }
@@ -93,7 +93,7 @@ pub struct SectorWeights {
     pub verified_deal_weight: DealWeight,
 }
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ActivateDealsParams {
     pub deal_ids: Vec<DealID>,
     pub sector_expiry: ChainEpoch,
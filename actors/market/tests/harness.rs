@@ -80,12 +80,11 @@ pub fn setup() -> MockRuntime {
 
     let mut rt = MockRuntime {
         receiver: *STORAGE_MARKET_ACTOR_ADDR,
-        caller: *SYSTEM_ACTOR_ADDR,
-        caller_type: *INIT_ACTOR_CODE_ID,
         actor_code_cids,
         balance: RefCell::new(10u64.pow(19).into()),
         ..Default::default()
     };
+    rt.set_caller(*INIT_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
 
     construct_and_verify(&mut rt);
 
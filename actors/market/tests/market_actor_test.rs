@@ -50,12 +50,8 @@ fn test_remove_all_error() {
 // TODO add array stuff
 #[test]
 fn simple_construction() {
-    let mut rt = MockRuntime {
-        receiver: Address::new_id(100),
-        caller: *SYSTEM_ACTOR_ADDR,
-        caller_type: *INIT_ACTOR_CODE_ID,
-        ..Default::default()
-    };
+    let mut rt = MockRuntime { receiver: Address::new_id(100), ..Default::default() };
+    rt.set_caller(*INIT_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
 
     rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
 
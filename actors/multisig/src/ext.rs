@@ -0,0 +1,15 @@
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+
+pub mod sca {
+    use super::*;
+
+    /// Envelope in which the SCA delivers cross-messages to the multisig,
+    /// carrying the hierarchical address of their sender.
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+    pub struct CrossMsgEnvelope {
+        pub from: Address,
+        pub params: RawBytes,
+    }
+}
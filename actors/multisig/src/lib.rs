@@ -4,21 +4,22 @@
 use std::collections::BTreeSet;
 
 use fil_actors_runtime::cbor::serialize_vec;
+use fil_actors_runtime::dispatch::RawParams;
 use fil_actors_runtime::runtime::{ActorCode, Primitives, Runtime};
 use fil_actors_runtime::{
-    actor_error, cbor, make_empty_map, make_map_with_root, resolve_to_id_addr, ActorDowncast,
-    ActorError, Map, INIT_ACTOR_ADDR,
+    actor_dispatch, actor_error, cbor, make_empty_map, make_map_with_root, method_hash,
+    resolve_to_id_addr, ActorDowncast, ActorError, Map, INIT_ACTOR_ADDR, SCA_ACTOR_ADDR,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::actor::builtin::CALLER_TYPES_SIGNABLE;
-use fvm_shared::address::Address;
+use fvm_shared::address::{Address, Protocol};
 use fvm_shared::bigint::Sign;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::{MethodNum, HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR};
+use fvm_shared::{HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
-use num_traits::{FromPrimitive, Signed};
+use num_traits::Signed;
 
 pub use self::state::*;
 pub use self::types::*;
@@ -26,6 +27,7 @@ pub use self::types::*;
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+pub mod ext;
 mod state;
 pub mod testing;
 mod types;
@@ -33,7 +35,7 @@ mod types;
 // * Updated to specs-actors commit: 845089a6d2580e46055c24415a6c32ee688e5186 (v3.0.0)
 
 /// Multisig actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
@@ -45,6 +47,15 @@ pub enum Method {
     SwapSigner = 7,
     ChangeNumApprovalsThreshold = 8,
     LockBalance = 9,
+    // Methods exported to user actors, under their FRC-0042 numbers.
+    ProposeExported = method_hash!("Propose"),
+    ApproveExported = method_hash!("Approve"),
+    CancelExported = method_hash!("Cancel"),
+    AddSignerExported = method_hash!("AddSigner"),
+    RemoveSignerExported = method_hash!("RemoveSigner"),
+    SwapSignerExported = method_hash!("SwapSigner"),
+    ChangeNumApprovalsThresholdExported = method_hash!("ChangeNumApprovalsThreshold"),
+    LockBalanceExported = method_hash!("LockBalance"),
 }
 
 /// Multisig Actor
@@ -74,13 +85,8 @@ impl Actor {
         let mut resolved_signers = Vec::with_capacity(params.signers.len());
         let mut dedup_signers = BTreeSet::new();
         for signer in &params.signers {
-            let resolved = resolve_to_id_addr(rt, signer).map_err(|e| {
-                e.downcast_default(
-                    ExitCode::USR_ILLEGAL_STATE,
-                    format!("failed to resolve addr {} to ID addr", signer),
-                )
-            })?;
-            if !dedup_signers.insert(resolved.id().expect("address should be resolved")) {
+            let resolved = Self::resolve_signer(rt, signer)?;
+            if !dedup_signers.insert(resolved.to_bytes()) {
                 return Err(
                     actor_error!(illegal_argument; "duplicate signer not allowed: {}", signer),
                 );
@@ -129,6 +135,98 @@ impl Actor {
         Ok(())
     }
 
+    /// Resolves a signer to its ID address. Signers in other subnets are kept as the
+    /// hierarchical address of their key, which is the sender of their cross-messages.
+    fn resolve_signer<BS, RT>(rt: &mut RT, signer: &Address) -> Result<Address, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        if signer.subnet().is_ok() {
+            let key = signer.raw_addr().map_err(|e| {
+                actor_error!(illegal_argument, "invalid hierarchical address {}: {}", signer, e)
+            })?;
+            return match key.protocol() {
+                Protocol::Secp256k1 | Protocol::BLS => Ok(*signer),
+                _ => Err(actor_error!(
+                    illegal_argument,
+                    "signer {} in another subnet must be a key address",
+                    signer
+                )),
+            };
+        }
+        resolve_to_id_addr(rt, signer).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_STATE,
+                format!("failed to resolve address {}", signer),
+            )
+        })
+    }
+
+    /// Returns the sender and params of a call relayed by the SCA for a signer
+    /// in another subnet, or `None` if the call doesn't come from the SCA.
+    fn cross_call<BS, RT>(
+        rt: &mut RT,
+        params: &RawBytes,
+    ) -> Result<Option<(Address, RawBytes)>, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        if rt.message().caller() != *SCA_ACTOR_ADDR {
+            return Ok(None);
+        }
+        rt.validate_immediate_caller_is(std::iter::once(&*SCA_ACTOR_ADDR))?;
+
+        // the SCA sets the subnet the cross-message comes from in its sender, and checks
+        // it against the child subnet that committed it, so it is only counted for a
+        // signer registered in that same subnet.
+        let envelope: ext::sca::CrossMsgEnvelope = cbor::deserialize_params(params)?;
+        if envelope.from.subnet().is_err() {
+            return Err(actor_error!(
+                forbidden,
+                "sender {} of cross-message is not a hierarchical address",
+                envelope.from
+            ));
+        }
+        Ok(Some((envelope.from, envelope.params)))
+    }
+
+    /// Propose, Approve and Cancel are called by signers in this subnet, or by the SCA
+    /// relaying the cross-messages of signers in other subnets.
+    fn propose_relayed<BS, RT>(rt: &mut RT, params: RawParams) -> Result<ProposeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        match Self::cross_call(rt, &params.0)? {
+            Some((from, params)) => Self::propose_as(rt, from, cbor::deserialize_params(&params)?),
+            None => Self::propose(rt, cbor::deserialize_params(&params.0)?),
+        }
+    }
+
+    fn approve_relayed<BS, RT>(rt: &mut RT, params: RawParams) -> Result<ApproveReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        match Self::cross_call(rt, &params.0)? {
+            Some((from, params)) => Self::approve_as(rt, from, cbor::deserialize_params(&params)?),
+            None => Self::approve(rt, cbor::deserialize_params(&params.0)?),
+        }
+    }
+
+    fn cancel_relayed<BS, RT>(rt: &mut RT, params: RawParams) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        match Self::cross_call(rt, &params.0)? {
+            Some((from, params)) => Self::cancel_as(rt, from, cbor::deserialize_params(&params)?),
+            None => Self::cancel(rt, cbor::deserialize_params(&params.0)?),
+        }
+    }
+
     /// Multisig actor propose function
     pub fn propose<BS, RT>(rt: &mut RT, params: ProposeParams) -> Result<ProposeReturn, ActorError>
     where
@@ -137,7 +235,19 @@ impl Actor {
     {
         rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
         let proposer: Address = rt.message().caller();
+        Self::propose_as(rt, proposer, params)
+    }
 
+    /// Proposes a transaction on behalf of `proposer`.
+    fn propose_as<BS, RT>(
+        rt: &mut RT,
+        proposer: Address,
+        params: ProposeParams,
+    ) -> Result<ProposeReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
         if params.value.sign() == Sign::Minus {
             return Err(actor_error!(
                 illegal_argument,
@@ -186,7 +296,7 @@ impl Actor {
             Ok((t_id, txn))
         })?;
 
-        let (applied, ret, code) = Self::approve_transaction(rt, txn_id, txn)?;
+        let (applied, ret, code) = Self::approve_transaction(rt, proposer, txn_id, txn)?;
 
         Ok(ProposeReturn { txn_id, applied, code, ret })
     }
@@ -199,7 +309,19 @@ impl Actor {
     {
         rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
         let approver: Address = rt.message().caller();
+        Self::approve_as(rt, approver, params)
+    }
 
+    /// Approves a transaction on behalf of `approver`.
+    fn approve_as<BS, RT>(
+        rt: &mut RT,
+        approver: Address,
+        params: TxnIDParams,
+    ) -> Result<ApproveReturn, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
         let id = params.id;
         let (st, txn) = rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&approver) {
@@ -224,7 +346,7 @@ impl Actor {
         if !applied {
             // if the transaction hasn't already been approved, "process" the approval
             // and see if the transaction can be executed
-            let (applied, ret, code) = Self::approve_transaction(rt, id, txn)?;
+            let (applied, ret, code) = Self::approve_transaction(rt, approver, id, txn)?;
             Ok(ApproveReturn { applied, code, ret })
         } else {
             Ok(ApproveReturn { applied, code, ret })
@@ -239,7 +361,19 @@ impl Actor {
     {
         rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
         let caller_addr: Address = rt.message().caller();
+        Self::cancel_as(rt, caller_addr, params)
+    }
 
+    /// Cancels a transaction on behalf of `caller_addr`, who must have proposed it.
+    fn cancel_as<BS, RT>(
+        rt: &mut RT,
+        caller_addr: Address,
+        params: TxnIDParams,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
         rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&caller_addr) {
                 return Err(actor_error!(forbidden; "{} is not a signer", caller_addr));
@@ -300,12 +434,7 @@ impl Actor {
     {
         let receiver = rt.message().receiver();
         rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
-        let resolved_new_signer = resolve_to_id_addr(rt, &params.signer).map_err(|e| {
-            e.downcast_default(
-                ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve address {}", params.signer),
-            )
-        })?;
+        let resolved_new_signer = Self::resolve_signer(rt, &params.signer)?;
 
         rt.transaction(|st: &mut State, _| {
             if st.signers.len() >= SIGNERS_MAX {
@@ -337,12 +466,7 @@ impl Actor {
     {
         let receiver = rt.message().receiver();
         rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
-        let resolved_old_signer = resolve_to_id_addr(rt, &params.signer).map_err(|e| {
-            e.downcast_default(
-                ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve address {}", params.signer),
-            )
-        })?;
+        let resolved_old_signer = Self::resolve_signer(rt, &params.signer)?;
 
         rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&resolved_old_signer) {
@@ -397,18 +521,8 @@ impl Actor {
     {
         let receiver = rt.message().receiver();
         rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
-        let from_resolved = resolve_to_id_addr(rt, &params.from).map_err(|e| {
-            e.downcast_default(
-                ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve address {}", params.from),
-            )
-        })?;
-        let to_resolved = resolve_to_id_addr(rt, &params.to).map_err(|e| {
-            e.downcast_default(
-                ExitCode::USR_ILLEGAL_STATE,
-                format!("failed to resolve address {}", params.to),
-            )
-        })?;
+        let from_resolved = Self::resolve_signer(rt, &params.from)?;
+        let to_resolved = Self::resolve_signer(rt, &params.to)?;
 
         rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&from_resolved) {
@@ -493,6 +607,7 @@ impl Actor {
 
     fn approve_transaction<BS, RT>(
         rt: &mut RT,
+        approver: Address,
         tx_id: TxnID,
         mut txn: Transaction,
     ) -> Result<(bool, RawBytes, ExitCode), ActorError>
@@ -501,7 +616,7 @@ impl Actor {
         RT: Runtime<BS>,
     {
         for previous_approver in &txn.approved {
-            if *previous_approver == rt.message().caller() {
+            if *previous_approver == approver {
                 return Err(actor_error!(
                     forbidden,
                     "{} already approved this message",
@@ -519,7 +634,7 @@ impl Actor {
             })?;
 
             // update approved on the transaction
-            txn.approved.push(rt.message().caller());
+            txn.approved.push(approver);
 
             ptx.set(tx_id.key(), txn.clone()).map_err(|e| {
                 e.downcast_default(
@@ -655,53 +770,25 @@ pub fn compute_proposal_hash(txn: &Transaction, sys: &dyn Primitives) -> anyhow:
 }
 
 impl ActorCode for Actor {
-    fn invoke_method<BS, RT>(
-        rt: &mut RT,
-        method: MethodNum,
-        params: &RawBytes,
-    ) -> Result<RawBytes, ActorError>
-    where
-        BS: Blockstore,
-        RT: Runtime<BS>,
-    {
-        match FromPrimitive::from_u64(method) {
-            Some(Method::Constructor) => {
-                Self::constructor(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::Propose) => {
-                let res = Self::propose(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::Approve) => {
-                let res = Self::approve(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::serialize(res)?)
-            }
-            Some(Method::Cancel) => {
-                Self::cancel(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::AddSigner) => {
-                Self::add_signer(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::RemoveSigner) => {
-                Self::remove_signer(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::SwapSigner) => {
-                Self::swap_signer(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::ChangeNumApprovalsThreshold) => {
-                Self::change_num_approvals_threshold(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            Some(Method::LockBalance) => {
-                Self::lock_balance(rt, cbor::deserialize_params(params)?)?;
-                Ok(RawBytes::default())
-            }
-            None => Err(actor_error!(unhandled_message, "Invalid method")),
+    actor_dispatch! {
+        restricted Method {
+            Constructor => constructor(ConstructorParams),
+            Propose => propose_relayed(RawParams) -> ProposeReturn,
+            Approve => approve_relayed(RawParams) -> ApproveReturn,
+            Cancel => cancel_relayed(RawParams),
+            AddSigner => add_signer(AddSignerParams),
+            RemoveSigner => remove_signer(RemoveSignerParams),
+            SwapSigner => swap_signer(SwapSignerParams),
+            ChangeNumApprovalsThreshold => change_num_approvals_threshold(ChangeNumApprovalsThresholdParams),
+            LockBalance => lock_balance(LockBalanceParams),
+            ProposeExported => propose_relayed(RawParams) -> ProposeReturn,
+            ApproveExported => approve_relayed(RawParams) -> ApproveReturn,
+            CancelExported => cancel_relayed(RawParams),
+            AddSignerExported => add_signer(AddSignerParams),
+            RemoveSignerExported => remove_signer(RemoveSignerParams),
+            SwapSignerExported => swap_signer(SwapSignerParams),
+            ChangeNumApprovalsThresholdExported => change_num_approvals_threshold(ChangeNumApprovalsThresholdParams),
+            LockBalanceExported => lock_balance(LockBalanceParams),
         }
     }
 }
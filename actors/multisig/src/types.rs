@@ -65,7 +65,7 @@ pub struct ProposalHashData<'a> {
 }
 
 /// Constructor parameters for multisig actor.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
     pub signers: Vec<Address>,
     pub num_approvals_threshold: u64,
@@ -75,7 +75,7 @@ pub struct ConstructorParams {
 }
 
 /// Propose method call parameters.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ProposeParams {
     pub to: Address,
     #[serde(with = "bigint_ser")]
@@ -85,7 +85,7 @@ pub struct ProposeParams {
 }
 
 /// Propose method call return.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ProposeReturn {
     /// TxnID is the ID of the proposed transaction.
     pub txn_id: TxnID,
@@ -113,7 +113,7 @@ pub struct TxnIDParams {
 }
 
 /// Parameters for approve and cancel multisig functions.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ApproveReturn {
     /// Applied indicates if the transaction was applied as opposed to proposed but not applied
     /// due to lack of approvals
@@ -129,14 +129,14 @@ impl Cbor for TxnIDParams {}
 impl Cbor for ApproveReturn {}
 
 /// Add signer params.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct AddSignerParams {
     pub signer: Address,
     pub increase: bool,
 }
 
 /// Remove signer params.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct RemoveSignerParams {
     pub signer: Address,
     pub decrease: bool,
@@ -145,7 +145,7 @@ pub struct RemoveSignerParams {
 impl Cbor for RemoveSignerParams {}
 
 /// Swap signer multisig method params
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct SwapSignerParams {
     pub from: Address,
     pub to: Address,
@@ -154,13 +154,13 @@ pub struct SwapSignerParams {
 impl Cbor for SwapSignerParams {}
 
 /// Propose method call parameters
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ChangeNumApprovalsThresholdParams {
     pub new_threshold: u64,
 }
 
 /// Lock balance call params.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct LockBalanceParams {
     pub start_epoch: ChainEpoch,
     pub unlock_duration: ChainEpoch,
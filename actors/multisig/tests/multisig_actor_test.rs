@@ -20,12 +20,9 @@ use fvm_shared::METHOD_SEND;
 mod util;
 
 fn construct_runtime(receiver: Address) -> MockRuntime {
-    MockRuntime {
-        receiver,
-        caller: *SYSTEM_ACTOR_ADDR,
-        caller_type: *SYSTEM_ACTOR_CODE_ID,
-        ..Default::default()
-    }
+    let mut rt = MockRuntime { receiver, ..Default::default() };
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, *SYSTEM_ACTOR_ADDR);
+    rt
 }
 
 fn check_state(rt: &MockRuntime) {
@@ -2399,3 +2396,104 @@ mod lock_balance_tests {
         check_state(&rt);
     }
 }
+
+mod cross_subnet_signers {
+    use std::str::FromStr;
+
+    use fvm_shared::address::SubnetID;
+
+    use super::*;
+
+    fn key(seed: u8) -> Address {
+        Address::new_bls(&[seed; BLS_PUB_LEN]).unwrap()
+    }
+
+    #[test]
+    fn signer_in_other_subnet_must_be_key_address() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let subnet = SubnetID::from_str("/root/f0101").unwrap();
+        let mut rt = construct_runtime(msig);
+
+        let params = ConstructorParams {
+            signers: vec![anne, Address::new_hierarchical(&subnet, &Address::new_id(102)).unwrap()],
+            num_approvals_threshold: 2,
+            unlock_duration: 0,
+            start_epoch: 0,
+        };
+        rt.set_caller(*INIT_ACTOR_CODE_ID, *INIT_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![*INIT_ACTOR_ADDR]);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            rt.call::<MultisigActor>(
+                Method::Constructor as u64,
+                &RawBytes::serialize(&params).unwrap(),
+            ),
+        );
+    }
+
+    #[test]
+    fn approve_from_other_subnet() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let chuck = Address::new_id(103);
+        let subnet = SubnetID::from_str("/root/f0101").unwrap();
+        let bob = Address::new_hierarchical(&subnet, &key(2)).unwrap();
+        let mut rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&mut rt, 2, 0, 0, vec![anne, bob]);
+        let st: State = rt.get_state();
+        assert_eq!(st.signers, vec![anne, bob]);
+
+        let fake_params = RawBytes::from(vec![1, 2, 3, 4]);
+        let fake_method = 42;
+        let send_value = TokenAmount::from(10u8);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        let proposal_hash =
+            h.propose_ok(&mut rt, chuck, send_value.clone(), fake_method, fake_params.clone());
+
+        // approvals from the same key in another subnet are not counted
+        let other = Address::new_hierarchical(&SubnetID::from_str("/root/f0102").unwrap(), &key(2))
+            .unwrap();
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.cross_approve(&mut rt, other, TxnID(0), proposal_hash),
+        );
+
+        // the approval of the signer in the subnet executes the transaction
+        rt.set_balance(send_value.clone());
+        rt.expect_send(
+            chuck,
+            fake_method,
+            fake_params,
+            send_value,
+            RawBytes::default(),
+            ExitCode::OK,
+        );
+        let ret = h.cross_approve(&mut rt, bob, TxnID(0), proposal_hash).unwrap();
+        let ret: fil_actor_multisig::ApproveReturn = ret.deserialize().unwrap();
+        assert!(ret.applied);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn cross_call_requires_hierarchical_sender() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let subnet = SubnetID::from_str("/root/f0101").unwrap();
+        let bob = Address::new_hierarchical(&subnet, &key(2)).unwrap();
+        let mut rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&mut rt, 2, 0, 0, vec![anne, bob]);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        let proposal_hash =
+            h.propose_ok(&mut rt, anne, TokenAmount::zero(), METHOD_SEND, RawBytes::default());
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.cross_approve(&mut rt, key(2), TxnID(0), proposal_hash),
+        );
+        check_state(&rt);
+    }
+}
@@ -1,3 +1,4 @@
+use fil_actor_multisig::ext::sca::CrossMsgEnvelope;
 use fil_actor_multisig::{
     compute_proposal_hash, Actor, AddSignerParams, ApproveReturn, ConstructorParams, Method,
     ProposeParams, ProposeReturn, RemoveSignerParams, State, SwapSignerParams, Transaction, TxnID,
@@ -5,8 +6,8 @@ use fil_actor_multisig::{
 };
 use fil_actor_multisig::{ChangeNumApprovalsThresholdParams, LockBalanceParams};
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::INIT_ACTOR_ADDR;
 use fil_actors_runtime::{make_map_with_root, ActorError};
+use fil_actors_runtime::{INIT_ACTOR_ADDR, SCA_ACTOR_ADDR};
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
@@ -148,6 +149,25 @@ impl ActorHarness {
         ret
     }
 
+    // approves a transaction through a cross-message relayed by the SCA from `from`.
+    pub fn cross_approve(
+        &self,
+        rt: &mut MockRuntime,
+        from: Address,
+        txn_id: TxnID,
+        proposal_hash: [u8; 32],
+    ) -> Result<RawBytes, ActorError> {
+        rt.set_caller(*SCA_ACTOR_CODE_ID, *SCA_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![*SCA_ACTOR_ADDR]);
+        let approve_params =
+            TxnIDParams { id: txn_id, proposal_hash: Vec::<u8>::from(proposal_hash) };
+        let envelope =
+            CrossMsgEnvelope { from, params: RawBytes::serialize(approve_params).unwrap() };
+        let ret = rt.call::<Actor>(Method::Approve as u64, &RawBytes::serialize(envelope).unwrap());
+        rt.verify();
+        ret
+    }
+
     pub fn cancel(
         &self,
         rt: &mut MockRuntime,
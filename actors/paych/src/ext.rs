@@ -0,0 +1,33 @@
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::bigint_ser;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+pub mod sca {
+    use super::*;
+
+    pub const SEND_CROSS_METHOD: u64 = 9;
+    pub const NETWORK_NAME_METHOD: u64 = 19;
+    /// ID of the native token of the subnet in the SCA's asset registry.
+    pub const NATIVE_ASSET: u64 = 0;
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+    pub struct StorableMsg {
+        pub from: Address,
+        pub to: Address,
+        pub method: MethodNum,
+        pub params: RawBytes,
+        #[serde(with = "bigint_ser")]
+        pub value: TokenAmount,
+        pub nonce: u64,
+        pub asset: u64,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+    pub struct CrossMsgParams {
+        pub msg: StorableMsg,
+        pub destination: SubnetID,
+    }
+}
@@ -2,17 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
-use fil_actors_runtime::{actor_error, cbor, resolve_to_id_addr, ActorDowncast, ActorError, Array};
+use fil_actors_runtime::{
+    actor_error, cbor, resolve_to_id_addr, ActorDowncast, ActorError, Array, SCA_ACTOR_ADDR,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::actor::builtin::Type;
-use fvm_shared::address::Address;
+use fvm_shared::address::{Address, Protocol, SubnetID};
 use fvm_shared::bigint::{BigInt, Sign};
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR, METHOD_SEND};
 use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, Zero};
 
 pub use self::state::{LaneState, Merge, State};
 pub use self::types::*;
@@ -20,6 +22,7 @@ pub use self::types::*;
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
 
+pub mod ext;
 mod state;
 pub mod testing;
 mod types;
@@ -27,7 +30,7 @@ mod types;
 // * Updated to specs-actors commit: f47f461b0588e9f0c20c999f6f129c85d669a7aa (v3.0.2)
 
 /// Payment Channel actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
@@ -51,8 +54,23 @@ impl Actor {
         // behalf of the payer/payee.
         rt.validate_immediate_caller_type(std::iter::once(&Type::Init))?;
 
-        // Check both parties are capable of signing vouchers
-        let to = Self::resolve_account(rt, &params.to)?;
+        // Check both parties are capable of signing vouchers. The recipient may live
+        // in another subnet, in which case it is kept as a hierarchical address.
+        let to = match params.to.subnet() {
+            Ok(subnet) => {
+                // the SCA can't send cross-messages to its own subnet, so the payout
+                // of the channel could never be collected.
+                if subnet == Self::network_name(rt)? {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "recipient {} is in the current subnet, use its local address",
+                        params.to
+                    ));
+                }
+                Self::check_cross_account(&params.to)?
+            }
+            Err(_) => Self::resolve_account(rt, &params.to)?,
+        };
 
         let from = Self::resolve_account(rt, &params.from)?;
 
@@ -99,6 +117,54 @@ impl Actor {
         }
     }
 
+    /// Checks that a hierarchical address points to a key address, as accounts
+    /// in other subnets can't be resolved locally.
+    fn check_cross_account(raw: &Address) -> Result<Address, ActorError> {
+        let key = raw.raw_addr().map_err(|e| {
+            actor_error!(illegal_argument, "invalid hierarchical address {}: {}", raw, e)
+        })?;
+        match key.protocol() {
+            Protocol::Secp256k1 | Protocol::BLS => Ok(*raw),
+            _ => Err(actor_error!(
+                forbidden,
+                "recipient {} in another subnet must be a key address",
+                raw
+            )),
+        }
+    }
+
+    /// Returns the ID of the current subnet, as governed by the SCA.
+    fn network_name<BS, RT>(rt: &mut RT) -> Result<SubnetID, ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let ret = rt
+            .send(
+                *SCA_ACTOR_ADDR,
+                ext::sca::NETWORK_NAME_METHOD,
+                RawBytes::default(),
+                TokenAmount::zero(),
+            )
+            .map_err(|e| e.wrap("failed to get the current subnet"))?;
+        ret.deserialize()
+            .map_err(|e| ActorError::serialization(format!("failed to decode subnet: {}", e)))
+    }
+
+    /// Checks that the caller can redeem vouchers or collect. A recipient in another
+    /// subnet has no account in this one, so anyone can act on its behalf: vouchers
+    /// are authorized by the signature of `from`, and payouts only go to the parties.
+    fn validate_parties<BS, RT>(rt: &mut RT, st: &State) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        match st.cross_to() {
+            Some(_) => rt.validate_immediate_caller_accept_any(),
+            None => rt.validate_immediate_caller_is([st.from, st.to].iter()),
+        }
+    }
+
     pub fn update_channel_state<BS, RT>(
         rt: &mut RT,
         params: UpdateChannelStateParams,
@@ -109,8 +175,12 @@ impl Actor {
     {
         let st: State = rt.state()?;
 
-        rt.validate_immediate_caller_is([st.from, st.to].iter())?;
-        let signer = if rt.message().caller() == st.from { st.to } else { st.from };
+        Self::validate_parties(rt, &st)?;
+        let signer = if rt.message().caller() == st.from {
+            st.cross_to().map(|(_, key)| key).unwrap_or(st.to)
+        } else {
+            st.from
+        };
         let sv = params.sv;
 
         // Pull signature from signed voucher
@@ -141,7 +211,29 @@ impl Actor {
         })?;
 
         let pch_addr = rt.message().receiver();
-        let svpch_id_addr = rt.resolve_address(&sv.channel_addr).ok_or_else(|| {
+        // vouchers for channels with a recipient in another subnet may
+        // use the hierarchical address of the channel in this subnet.
+        let channel_addr = match sv.channel_addr.subnet() {
+            Ok(subnet) => {
+                if subnet != Self::network_name(rt)? {
+                    return Err(actor_error!(
+                        illegal_argument,
+                        "voucher payment channel address {} is not in the current subnet",
+                        sv.channel_addr
+                    ));
+                }
+                sv.channel_addr.raw_addr().map_err(|e| {
+                    actor_error!(
+                        illegal_argument,
+                        "invalid voucher payment channel address {}: {}",
+                        sv.channel_addr,
+                        e
+                    )
+                })?
+            }
+            Err(_) => sv.channel_addr,
+        };
+        let svpch_id_addr = rt.resolve_address(&channel_addr).ok_or_else(|| {
             actor_error!(
                 illegal_argument,
                 "voucher payment channel address {} does not resolve to an ID address",
@@ -284,7 +376,12 @@ impl Actor {
         RT: Runtime<BS>,
     {
         rt.transaction(|st: &mut State, rt| {
-            rt.validate_immediate_caller_is([st.from, st.to].iter())?;
+            // Settling starts the clock on the recipient's vouchers, so only the
+            // payer may do it on behalf of a recipient in another subnet.
+            match st.cross_to() {
+                Some(_) => rt.validate_immediate_caller_is(std::iter::once(&st.from))?,
+                None => Self::validate_parties(rt, st)?,
+            }
 
             if st.settling_at != 0 {
                 return Err(actor_error!(illegal_state; "channel already settling"));
@@ -305,15 +402,40 @@ impl Actor {
         RT: Runtime<BS>,
     {
         let st: State = rt.state()?;
-        rt.validate_immediate_caller_is(&[st.from, st.to])?;
+        Self::validate_parties(rt, &st)?;
 
         if st.settling_at == 0 || rt.curr_epoch() < st.settling_at {
             return Err(actor_error!(forbidden; "payment channel not settling or settled"));
         }
 
-        // send ToSend to `to`
-        rt.send(st.to, METHOD_SEND, RawBytes::default(), st.to_send)
-            .map_err(|e| e.wrap("Failed to send funds to `to` address"))?;
+        match st.cross_to() {
+            // send ToSend to `to` in its subnet through a cross-message
+            Some((destination, key)) => {
+                if !st.to_send.is_zero() {
+                    let msg = ext::sca::StorableMsg {
+                        from: rt.message().receiver(),
+                        to: key,
+                        method: METHOD_SEND,
+                        params: RawBytes::default(),
+                        value: st.to_send.clone(),
+                        nonce: 0,
+                        asset: ext::sca::NATIVE_ASSET,
+                    };
+                    rt.send(
+                        *SCA_ACTOR_ADDR,
+                        ext::sca::SEND_CROSS_METHOD,
+                        RawBytes::serialize(ext::sca::CrossMsgParams { msg, destination })?,
+                        st.to_send,
+                    )
+                    .map_err(|e| e.wrap("Failed to send funds to `to` address in its subnet"))?;
+                }
+            }
+            // send ToSend to `to`
+            None => {
+                rt.send(st.to, METHOD_SEND, RawBytes::default(), st.to_send)
+                    .map_err(|e| e.wrap("Failed to send funds to `to` address"))?;
+            }
+        }
 
         // the remaining balance will be returned to "From" upon deletion.
         rt.delete_actor(&st.from)?;
@@ -4,7 +4,7 @@
 use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::Cbor;
-use fvm_shared::address::Address;
+use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::bigint::{bigint_ser, BigInt};
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
@@ -16,7 +16,8 @@ use fvm_shared::econ::TokenAmount;
 pub struct State {
     /// Channel owner, who has funded the actor.
     pub from: Address,
-    /// Recipient of payouts from channel.
+    /// Recipient of payouts from channel. It is the hierarchical address of
+    /// the recipient if it lives in another subnet.
     pub to: Address,
     /// Amount successfully redeemed through the payment channel, paid out on `Collect`.
     #[serde(with = "bigint_ser")]
@@ -40,6 +41,15 @@ impl State {
             lane_states: empty_arr_cid,
         }
     }
+
+    /// Returns the subnet and the key address of the recipient
+    /// if it lives in another subnet.
+    pub fn cross_to(&self) -> Option<(SubnetID, Address)> {
+        match (self.to.subnet(), self.to.raw_addr()) {
+            (Ok(subnet), Ok(key)) => Some((subnet, key)),
+            _ => None,
+        }
+    }
 }
 
 /// The Lane state tracks the latest (highest) voucher nonce used to merge the lane
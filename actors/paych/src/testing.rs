@@ -25,8 +25,11 @@ pub fn check_state_invariants<BS: Blockstore>(
         format!("from address is not ID address {}", state.from),
     );
     acc.require(
-        state.to.protocol() == Protocol::ID,
-        format!("to address is not ID address {}", state.to),
+        state.to.protocol() == Protocol::ID
+            || state.cross_to().map_or(false, |(_, key)| {
+                matches!(key.protocol(), Protocol::Secp256k1 | Protocol::BLS)
+            }),
+        format!("to address is not ID address or key address in another subnet {}", state.to),
     );
     acc.require(
         state.settling_at >= state.min_settle_height,
@@ -21,8 +21,9 @@ pub const SETTLE_DELAY: ChainEpoch = EPOCHS_IN_HOUR * 12;
 pub const MAX_SECRET_SIZE: usize = 256;
 
 pub const LANE_STATES_AMT_BITWIDTH: u32 = 3;
-/// Constructor parameters for payment channel actor
-#[derive(Serialize_tuple, Deserialize_tuple)]
+/// Constructor parameters for payment channel actor.
+/// `to` may be the hierarchical address of a key address in another subnet.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
     pub from: Address,
     pub to: Address,
@@ -32,7 +33,8 @@ pub struct ConstructorParams {
 /// `to` to redeem payments on-chain in the future
 #[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct SignedVoucher {
-    /// ChannelAddr is the address of the payment channel this signed voucher is valid for
+    /// ChannelAddr is the address of the payment channel this signed voucher is valid for.
+    /// It may be the hierarchical address of the channel if its recipient lives in another subnet
     pub channel_addr: Address,
     /// Min epoch before which the voucher cannot be redeemed
     pub time_lock_min: ChainEpoch,
@@ -114,7 +116,7 @@ pub struct PaymentVerifyParams {
     pub proof: Vec<u8>,
 }
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct UpdateChannelStateParams {
     pub sv: SignedVoucher,
     #[serde(with = "serde_bytes")]
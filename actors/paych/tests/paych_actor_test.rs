@@ -1120,6 +1120,226 @@ mod actor_collect {
     }
 }
 
+mod cross_subnet {
+    use std::str::FromStr;
+
+    use fil_actor_paych::ext;
+    use fil_actors_runtime::SCA_ACTOR_ADDR;
+    use fvm_shared::address::subnet::ROOTNET_ID;
+    use fvm_shared::address::{SubnetID, BLS_PUB_LEN};
+
+    use super::*;
+
+    fn payee_key() -> Address {
+        Address::new_bls(&[104; BLS_PUB_LEN]).unwrap()
+    }
+
+    fn payee_subnet() -> SubnetID {
+        SubnetID::from_str("/root/f0101").unwrap()
+    }
+
+    fn new_runtime() -> MockRuntime {
+        let payer_addr = Address::new_id(PAYER_ID);
+        let mut actor_code_cids = HashMap::default();
+        actor_code_cids.insert(payer_addr, *ACCOUNT_ACTOR_CODE_ID);
+
+        MockRuntime {
+            receiver: Address::new_id(PAYCH_ID),
+            caller: *INIT_ACTOR_ADDR,
+            caller_type: *INIT_ACTOR_CODE_ID,
+            actor_code_cids,
+            balance: RefCell::new(TokenAmount::from(100_000)),
+            epoch: 2,
+            ..Default::default()
+        }
+    }
+
+    fn construct_runtime() -> MockRuntime {
+        let mut rt = new_runtime();
+        let payee_addr = Address::new_hierarchical(&payee_subnet(), &payee_key()).unwrap();
+        expect_network_name(&mut rt);
+        construct_and_verify(&mut rt, Address::new_id(PAYER_ID), payee_addr);
+        rt
+    }
+
+    fn expect_network_name(rt: &mut MockRuntime) {
+        rt.expect_send(
+            *SCA_ACTOR_ADDR,
+            ext::sca::NETWORK_NAME_METHOD,
+            RawBytes::default(),
+            TokenAmount::from(0),
+            RawBytes::serialize(&*ROOTNET_ID).unwrap(),
+            ExitCode::OK,
+        );
+    }
+
+    fn expect_construct_abort(rt: &mut MockRuntime, to: Address, code: ExitCode) {
+        let params = ConstructorParams { from: Address::new_id(PAYER_ID), to };
+        rt.set_caller(*INIT_ACTOR_CODE_ID, *INIT_ACTOR_ADDR);
+        rt.expect_validate_caller_type(vec![*INIT_ACTOR_CODE_ID]);
+        expect_abort(rt, METHOD_CONSTRUCTOR, &RawBytes::serialize(params).unwrap(), code);
+        rt.verify();
+    }
+
+    fn voucher(channel_addr: Address, nonce: u64, amount: u64) -> SignedVoucher {
+        SignedVoucher {
+            channel_addr,
+            time_lock_min: 0,
+            time_lock_max: 0,
+            secret_pre_image: Default::default(),
+            extra: Default::default(),
+            lane: 0,
+            nonce,
+            amount: BigInt::from(amount),
+            min_settle_height: 0,
+            merges: Default::default(),
+            signature: Some(Signature::new_bls(vec![0, 1, 2, 3, 4, 5, 6, 7])),
+        }
+    }
+
+    #[test]
+    fn create_cross_subnet_channel() {
+        let rt = construct_runtime();
+        let st: PState = rt.get_state();
+        assert_eq!(st.cross_to(), Some((payee_subnet(), payee_key())));
+    }
+
+    #[test]
+    fn recipient_must_be_key_address() {
+        let mut rt = new_runtime();
+        let payee_addr =
+            Address::new_hierarchical(&payee_subnet(), &Address::new_id(PAYEE_ID)).unwrap();
+        expect_network_name(&mut rt);
+        expect_construct_abort(&mut rt, payee_addr, ExitCode::USR_FORBIDDEN);
+    }
+
+    #[test]
+    fn recipient_must_be_in_another_subnet() {
+        // the payout to a recipient in the current subnet couldn't be collected,
+        // as the SCA doesn't send cross-messages to its own subnet.
+        let mut rt = new_runtime();
+        let payee_addr = Address::new_hierarchical(&ROOTNET_ID, &payee_key()).unwrap();
+        expect_network_name(&mut rt);
+        expect_construct_abort(&mut rt, payee_addr, ExitCode::USR_ILLEGAL_ARGUMENT);
+    }
+
+    #[test]
+    fn voucher_for_channel_in_another_subnet() {
+        let mut rt = construct_runtime();
+        let payer_addr = Address::new_id(PAYER_ID);
+
+        // a channel with the same ID in another subnet
+        let other = Address::new_hierarchical(&payee_subnet(), &Address::new_id(PAYCH_ID)).unwrap();
+        let sv = voucher(other, 1, 10);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(PAYEE_ID));
+        rt.expect_validate_caller_any();
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: sv.signature.clone().unwrap(),
+            signer: payer_addr,
+            plaintext: sv.signing_bytes().unwrap(),
+            result: Ok(()),
+        });
+        expect_network_name(&mut rt);
+        expect_abort(
+            &mut rt,
+            Method::UpdateChannelState as u64,
+            &RawBytes::serialize(UpdateChannelStateParams::from(sv)).unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+        let st: PState = rt.get_state();
+        assert_eq!(st.to_send, TokenAmount::from(0));
+    }
+
+    #[test]
+    fn redeem_and_collect_through_cross_msg() {
+        let mut rt = construct_runtime();
+        let payer_addr = Address::new_id(PAYER_ID);
+        // the recipient has no account in this subnet, so anyone can redeem
+        // the vouchers it was given and collect.
+        let relayer = Address::new_id(PAYEE_ID + 100);
+
+        let channel_addr =
+            Address::new_hierarchical(&ROOTNET_ID, &Address::new_id(PAYCH_ID)).unwrap();
+        let sv = voucher(channel_addr, 1, 10);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, relayer);
+        rt.expect_validate_caller_any();
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: sv.signature.clone().unwrap(),
+            signer: payer_addr,
+            plaintext: sv.signing_bytes().unwrap(),
+            result: Ok(()),
+        });
+        expect_network_name(&mut rt);
+        call(
+            &mut rt,
+            Method::UpdateChannelState as u64,
+            &RawBytes::serialize(UpdateChannelStateParams::from(sv)).unwrap(),
+        );
+        rt.verify();
+        let st: PState = rt.get_state();
+        assert_eq!(st.to_send, TokenAmount::from(10));
+
+        // vouchers submitted by `from` are signed by the key of the recipient
+        let sv = voucher(Address::new_id(PAYCH_ID), 2, 20);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, payer_addr);
+        rt.expect_validate_caller_any();
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: sv.signature.clone().unwrap(),
+            signer: payee_key(),
+            plaintext: sv.signing_bytes().unwrap(),
+            result: Ok(()),
+        });
+        call(
+            &mut rt,
+            Method::UpdateChannelState as u64,
+            &RawBytes::serialize(UpdateChannelStateParams::from(sv)).unwrap(),
+        );
+        rt.verify();
+        let st: PState = rt.get_state();
+        assert_eq!(st.to_send, TokenAmount::from(20));
+
+        // Only the payer can settle.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, relayer);
+        rt.expect_validate_caller_addr(vec![payer_addr]);
+        expect_abort(&mut rt, Method::Settle as u64, &RawBytes::default(), ExitCode::USR_FORBIDDEN);
+        rt.verify();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, payer_addr);
+        rt.expect_validate_caller_addr(vec![payer_addr]);
+        call(&mut rt, Method::Settle as u64, &RawBytes::default());
+        rt.verify();
+        let st: PState = rt.get_state();
+        rt.epoch = st.settling_at + 1;
+
+        // Collect sends the payout to the recipient in its subnet.
+        let msg = ext::sca::StorableMsg {
+            from: Address::new_id(PAYCH_ID),
+            to: payee_key(),
+            method: fvm_shared::METHOD_SEND,
+            params: RawBytes::default(),
+            value: st.to_send.clone(),
+            nonce: 0,
+            asset: ext::sca::NATIVE_ASSET,
+        };
+        rt.expect_send(
+            *SCA_ACTOR_ADDR,
+            ext::sca::SEND_CROSS_METHOD,
+            RawBytes::serialize(ext::sca::CrossMsgParams { msg, destination: payee_subnet() })
+                .unwrap(),
+            st.to_send.clone(),
+            RawBytes::default(),
+            ExitCode::OK,
+        );
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, relayer);
+        rt.expect_validate_caller_any();
+        rt.expect_delete_actor(payer_addr);
+        call(&mut rt, Method::Collect as u64, &RawBytes::default());
+        rt.verify();
+        check_state(&rt);
+    }
+}
+
 fn require_create_channel_with_lanes(num_lanes: u64) -> (MockRuntime, SignedVoucher) {
     let paych_addr = Address::new_id(100);
     let payer_addr = Address::new_id(PAYER_ID);
@@ -48,7 +48,7 @@ pub mod detail {
 }
 
 /// Storage power actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     /// Constructor for Storage Power Actor
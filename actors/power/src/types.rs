@@ -23,7 +23,7 @@ pub const CRON_QUEUE_HAMT_BITWIDTH: u32 = 6;
 pub const CRON_QUEUE_AMT_BITWIDTH: u32 = 6;
 pub const PROOF_VALIDATION_BATCH_AMT_BITWIDTH: u32 = 4;
 
-#[derive(Serialize_tuple, Deserialize_tuple, Clone)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
 pub struct CreateMinerParams {
     pub owner: Address,
     pub worker: Address,
@@ -34,7 +34,7 @@ pub struct CreateMinerParams {
 }
 impl Cbor for CreateMinerParams {}
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct CreateMinerReturn {
     /// Canonical ID-based address for the actor.
     pub id_address: Address,
@@ -41,7 +41,7 @@ pub mod ext;
 pub const PENALTY_MULTIPLIER: u64 = 3;
 
 /// Reward actor methods available
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
@@ -30,6 +30,9 @@ const WASM_FEATURES: &[&str] = &["+bulk-memory", "+crt-static"];
 
 const NETWORK_ENV: &str = "BUILD_FIL_NETWORK";
 
+/// Environment variable with the path of a JSON or TOML policy file to bake into the actors.
+const POLICY_ENV: &str = "BUILD_FIL_POLICY";
+
 /// Returns the configured network name, checking both the environment and feature flags.
 fn network_name() -> String {
     let env_network = std::env::var_os(NETWORK_ENV);
@@ -93,6 +96,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Make sure we re-build if the network name changes.
     println!("cargo:rerun-if-env-changed={}", NETWORK_ENV);
 
+    // Determine the policy file, if any. The path is made absolute, as the runtime's build
+    // script baking it into the actors runs from its own directory.
+    let policy_file = std::env::var_os(POLICY_ENV)
+        .map(|path| Path::new(&path).canonicalize().expect("policy file not found"));
+    println!("cargo:rerun-if-env-changed={}", POLICY_ENV);
+    if let Some(path) = &policy_file {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
     // Rerun if the source, dependencies, build options, build script _or_ actors have changed. We
     // need to check if the actors have changed because otherwise, when building in a workspace, we
     // won't re-run the build script and therefore won't re-compile them.
@@ -127,6 +139,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         // our own `RUSTFLAGS` and thus, we need to remove this. Otherwise cargo favors this
         // env variable.
         .env_remove("CARGO_ENCODED_RUSTFLAGS");
+    if let Some(path) = &policy_file {
+        cmd.env(POLICY_ENV, path);
+    }
 
     // Print out the command line we're about to run.
     println!("cargo:warning=cmd={:?}", &cmd);
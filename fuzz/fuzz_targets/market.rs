@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use test_vm::fuzz::{run, Op};
+
+// Calls of the other actors are dropped.
+fuzz_target!(|ops: Vec<Op>| {
+    let ops: Vec<Op> = ops.into_iter().filter(|op| matches!(op, Op::Market(_))).collect();
+    if let Err(e) = run(&ops) {
+        panic!("{}", e);
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use test_vm::fuzz::{run, Op};
+
+fuzz_target!(|ops: Vec<Op>| {
+    if let Err(e) = run(&ops) {
+        panic!("{}", e);
+    }
+});
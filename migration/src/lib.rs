@@ -0,0 +1,41 @@
+//! Migrations of the state of builtin actors between versions of their layout.
+//!
+//! Every actor with migrations has its own module, holding the previous layouts
+//! of its state and the functions that transform them into the current one.
+use anyhow::anyhow;
+use cid::Cid;
+use fil_builtin_actors_state::check::{Actor, Tree};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::address::Address;
+
+pub mod sca;
+
+/// Migrates the state of the actor at `addr` in the state tree with root `root`,
+/// returning the root of the new state tree.
+///
+/// `migrate` is given the state tree and the actor, and returns the head of
+/// the migrated state of the actor.
+pub fn migrate_actor<BS, F>(
+    store: &BS,
+    root: &Cid,
+    addr: &Address,
+    migrate: F,
+) -> anyhow::Result<Cid>
+where
+    BS: Blockstore,
+    F: FnOnce(&Tree<BS>, &Actor) -> anyhow::Result<Cid>,
+{
+    let mut tree = Tree::load(store, root)?;
+    let actor = tree
+        .map
+        .get(&addr.to_bytes())
+        .map_err(|e| anyhow!("failed to get actor {} from state tree: {}", addr, e))?
+        .cloned()
+        .ok_or_else(|| anyhow!("actor {} not found in state tree", addr))?;
+
+    let head = migrate(&tree, &actor)?;
+    tree.map
+        .set(addr.to_bytes().into(), Actor { head, ..actor })
+        .map_err(|e| anyhow!("failed to set actor {} in state tree: {}", addr, e))?;
+    tree.map.flush().map_err(|e| anyhow!("failed to flush state tree: {}", e))
+}
@@ -0,0 +1,238 @@
+//! Migration of the SCA state from its v1 layout.
+//!
+//! Content-addressed identifiers are kept as they were: the keys of the cross-msgs
+//! and atomic execution registries, the `msgs_cid` of cross-msg metas and the
+//! `prev_check` links between checkpoints. They are shared with other subnets, so
+//! updating them here would break the references held by subnets that haven't
+//! been migrated yet.
+//!
+//! Checkpoints gain fields, so a migrated checkpoint has a different CID than the
+//! one it was committed with. The previous checkpoint of every subnet keeps its v1
+//! CID in `prev_checkpoint_cid`, which is what the next checkpoint committed by the
+//! subnet points to.
+use actor_primitives::tcid::TCid;
+use anyhow::anyhow;
+use cid::multihash::Code;
+use cid::Cid;
+use fil_actor_hierarchical_sca::exec::{AtomicExec, AtomicExecParams};
+use fil_actor_hierarchical_sca::{
+    Checkpoint, ConsensusType, CrossMsgMeta, CrossMsgs, State, Status, StorableMsg, Subnet,
+    DEFAULT_ESCAPE_HATCH_PERIODS, NATIVE_ASSET,
+};
+use fil_actors_runtime::SCA_ACTOR_ADDR;
+use fil_builtin_actors_state::check::Tree;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::CborStore;
+use fvm_shared::clock::ChainEpoch;
+
+use crate::migrate_actor;
+
+pub mod v1;
+
+/// Information not available in the v1 state that is needed to migrate it.
+pub struct Config {
+    /// Epoch in which the migration is run. It is used as the epoch of the last
+    /// checkpoint of existing subnets, so they are not considered inactive right
+    /// after the migration.
+    pub epoch: ChainEpoch,
+    /// Consensus run by the subnets registered before the migration.
+    pub consensus: ConsensusType,
+}
+
+/// Migrates the SCA in the state tree with root `root` from its v1 layout,
+/// returning the root of the new state tree.
+pub fn migrate_state_tree<BS: Blockstore>(
+    store: &BS,
+    root: &Cid,
+    cfg: &Config,
+) -> anyhow::Result<Cid> {
+    migrate_actor(store, root, &SCA_ACTOR_ADDR, |tree, actor| {
+        let old: v1::State = store
+            .get_cbor(&actor.head)?
+            .ok_or_else(|| anyhow!("SCA state not found for head {}", actor.head))?;
+        let st = migrate_state(store, tree, old, cfg)?;
+        store.put_cbor(&st, Code::Blake2b256)
+    })
+}
+
+/// Transforms a v1 SCA state, and every HAMT and AMT it owns, into the current layout.
+pub fn migrate_state<BS: Blockstore>(
+    store: &BS,
+    tree: &Tree<BS>,
+    old: v1::State,
+    cfg: &Config,
+) -> anyhow::Result<State> {
+    // subnets are keyed by their ID, so we can't iterate them with typed keys.
+    let mut subnets = Vec::new();
+    old.subnets.load(store)?.for_each(|_, sub| {
+        subnets.push(migrate_subnet(store, tree, sub, cfg)?);
+        Ok(())
+    })?;
+    let mut new_subnets = TCid::new_hamt(store)?;
+    new_subnets.update(store, |map| {
+        for sub in subnets {
+            map.set(sub.id.to_bytes().into(), sub)
+                .map_err(|e| anyhow!("failed to set migrated subnet: {}", e))?;
+        }
+        Ok(())
+    })?;
+
+    let mut checkpoints = Vec::new();
+    old.checkpoints.for_each(store, |epoch, ch| {
+        checkpoints.push((epoch, migrate_checkpoint(ch)?));
+        Ok(())
+    })?;
+    let mut new_checkpoints = TCid::new_hamt(store)?;
+    new_checkpoints.batch_set(store, checkpoints)?;
+
+    let mut registry = Vec::new();
+    old.check_msg_registry.for_each(store, |cid, msgs| {
+        registry.push((TCid::from(cid.cid()), migrate_cross_msgs(msgs)));
+        Ok(())
+    })?;
+    let mut new_registry = TCid::new_hamt(store)?;
+    new_registry.batch_set(store, registry)?;
+
+    let mut metas = Vec::new();
+    old.bottomup_msg_meta.for_each(store, |i, meta| {
+        metas.push((i, migrate_msgmeta(meta)));
+        Ok(())
+    })?;
+    let mut new_metas = TCid::new_amt(store)?;
+    new_metas.batch_set(store, metas)?;
+
+    let mut execs = Vec::new();
+    old.atomic_exec_registry.for_each(store, |cid, exec| {
+        execs.push((cid, migrate_exec(exec)));
+        Ok(())
+    })?;
+    let mut new_execs = TCid::new_hamt(store)?;
+    new_execs.batch_set(store, execs)?;
+
+    Ok(State {
+        network_name: old.network_name,
+        total_subnets: old.total_subnets,
+        min_stake: old.min_stake,
+        subnets: new_subnets,
+        check_period: old.check_period,
+        escape_hatch_periods: DEFAULT_ESCAPE_HATCH_PERIODS,
+        checkpoints: new_checkpoints,
+        check_msg_registry: new_registry,
+        nonce: old.nonce,
+        bottomup_nonce: old.bottomup_nonce,
+        bottomup_msg_meta: new_metas,
+        applied_bottomup_nonce: old.applied_bottomup_nonce,
+        applied_topdown_nonce: old.applied_topdown_nonce,
+        atomic_exec_registry: new_execs,
+        assets: TCid::new_hamt(store)?,
+    })
+}
+
+fn migrate_subnet<BS: Blockstore>(
+    store: &BS,
+    tree: &Tree<BS>,
+    sub: &v1::Subnet,
+    cfg: &Config,
+) -> anyhow::Result<Subnet> {
+    let mut msgs = Vec::new();
+    sub.top_down_msgs.for_each(store, |i, msg| {
+        msgs.push((i, migrate_msg(msg)));
+        Ok(())
+    })?;
+    let mut top_down_msgs = TCid::new_amt(store)?;
+    top_down_msgs.batch_set(store, msgs)?;
+
+    // the code of the subnet actor wasn't recorded in v1, so we get it from the state tree.
+    let code = tree
+        .map
+        .get(&sub.id.subnet_actor().to_bytes())
+        .map_err(|e| anyhow!("failed to get subnet actor for {}: {}", sub.id, e))?
+        .map(|act| act.code)
+        .unwrap_or_default();
+
+    // only the tipset of the previous checkpoint is known, older ones weren't recorded in v1.
+    let mut committed_tipsets = TCid::new_hamt(store)?;
+    if let Some(ch) = &sub.prev_checkpoint {
+        committed_tipsets.set(store, &ch.data.tip_set, ch.data.epoch)?;
+    }
+
+    Ok(Subnet {
+        id: sub.id.clone(),
+        stake: sub.stake.clone(),
+        top_down_msgs,
+        nonce: sub.nonce,
+        circ_supply: sub.circ_supply.clone(),
+        status: sub.status,
+        prev_checkpoint: sub.prev_checkpoint.as_ref().map(migrate_checkpoint).transpose()?,
+        prev_checkpoint_cid: sub
+            .prev_checkpoint
+            .as_ref()
+            .map(|ch| TCid::from(ch.cid()))
+            .unwrap_or_default(),
+        committed_tipsets,
+        grace_period_end: 0,
+        last_checkpoint_epoch: cfg.epoch,
+        // the escape hatch clock of inactive subnets starts paused.
+        inactive_since: if sub.status == Status::Inactive { cfg.epoch } else { 0 },
+        claimed: TCid::new_hamt(store)?,
+        name: sub.id.to_string(),
+        consensus: cfg.consensus,
+        // the registration epoch wasn't recorded in v1.
+        genesis_epoch: 0,
+        code,
+        asset_supply: Vec::new(),
+        validator_sets: TCid::new_amt(store)?,
+    })
+}
+
+fn migrate_checkpoint(ch: &v1::Checkpoint) -> anyhow::Result<Checkpoint> {
+    let mut new = Checkpoint::new(ch.data.source.clone(), ch.data.epoch);
+    new.data.tip_set = ch.data.tip_set.clone();
+    new.data.prev_check = TCid::from(ch.data.prev_check.cid());
+    new.data.children = ch.data.children.clone();
+    new.data.cross_msgs = ch.data.cross_msgs.iter().map(migrate_msgmeta).collect();
+    new.data.update_roots()?;
+    new.set_signature(ch.sig.clone());
+    Ok(new)
+}
+
+fn migrate_msgmeta(meta: &v1::CrossMsgMeta) -> CrossMsgMeta {
+    CrossMsgMeta {
+        from: meta.from.clone(),
+        to: meta.to.clone(),
+        msgs_cid: TCid::from(meta.msgs_cid.cid()),
+        nonce: meta.nonce,
+        value: meta.value.clone(),
+        assets: Vec::new(),
+    }
+}
+
+fn migrate_msg(msg: &v1::StorableMsg) -> StorableMsg {
+    StorableMsg {
+        from: msg.from,
+        to: msg.to,
+        method: msg.method,
+        params: msg.params.clone(),
+        value: msg.value.clone(),
+        nonce: msg.nonce,
+        asset: NATIVE_ASSET,
+    }
+}
+
+fn migrate_cross_msgs(msgs: &v1::CrossMsgs) -> CrossMsgs {
+    CrossMsgs {
+        msgs: msgs.msgs.iter().map(migrate_msg).collect(),
+        metas: msgs.metas.iter().map(migrate_msgmeta).collect(),
+    }
+}
+
+fn migrate_exec(exec: &v1::AtomicExec) -> AtomicExec {
+    let params = AtomicExecParams {
+        msgs: exec.params.msgs.iter().map(migrate_msg).collect(),
+        inputs: exec.params.inputs.clone(),
+    };
+    let mut new = AtomicExec::new(params);
+    new.set_status(exec.status);
+    new.submitted_mut().extend(exec.submitted.clone());
+    new
+}
@@ -0,0 +1,125 @@
+//! Layout of the SCA state in v1 (bundle 9.0.0-alpha.1).
+//!
+//! Only the types whose serialization changed since then are defined here,
+//! the rest are shared with the current version of the actor.
+use std::collections::HashMap;
+
+use actor_primitives::taddress::{TAddressKey, ID};
+use actor_primitives::tcid::{TAmt, TCid, THamt, TLink};
+use cid::multihash::{Code, MultihashDigest};
+use cid::Cid;
+use fil_actor_hierarchical_sca::checkpoint::ChildCheck;
+use fil_actor_hierarchical_sca::exec::{ExecStatus, HierarchicalId, LockedStateInfo};
+use fil_actor_hierarchical_sca::subnet::Status;
+use fil_actor_hierarchical_sca::CROSSMSG_AMT_BITWIDTH;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{serde_bytes, to_vec, Cbor, RawBytes, DAG_CBOR};
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::bigint_ser;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct State {
+    pub network_name: SubnetID,
+    pub total_subnets: u64,
+    #[serde(with = "bigint_ser")]
+    pub min_stake: TokenAmount,
+    pub subnets: TCid<THamt<Cid, Subnet>>,
+    pub check_period: ChainEpoch,
+    pub checkpoints: TCid<THamt<ChainEpoch, Checkpoint>>,
+    pub check_msg_registry: TCid<THamt<TCid<TLink<CrossMsgs>>, CrossMsgs>>,
+    pub nonce: u64,
+    pub bottomup_nonce: u64,
+    pub bottomup_msg_meta: TCid<TAmt<CrossMsgMeta, CROSSMSG_AMT_BITWIDTH>>,
+    pub applied_bottomup_nonce: u64,
+    pub applied_topdown_nonce: u64,
+    pub atomic_exec_registry: TCid<THamt<Cid, AtomicExec>>,
+}
+impl Cbor for State {}
+
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple, PartialEq)]
+pub struct Subnet {
+    pub id: SubnetID,
+    #[serde(with = "bigint_ser")]
+    pub stake: TokenAmount,
+    pub top_down_msgs: TCid<TAmt<StorableMsg, CROSSMSG_AMT_BITWIDTH>>,
+    pub nonce: u64,
+    #[serde(with = "bigint_ser")]
+    pub circ_supply: TokenAmount,
+    pub status: Status,
+    pub prev_checkpoint: Option<Checkpoint>,
+}
+impl Cbor for Subnet {}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct Checkpoint {
+    pub data: CheckData,
+    #[serde(with = "serde_bytes")]
+    pub sig: Vec<u8>,
+}
+impl Cbor for Checkpoint {}
+
+impl Checkpoint {
+    /// CID of the checkpoint, computed over its data as in v1.
+    pub fn cid(&self) -> Cid {
+        Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(&to_vec(&self.data).unwrap()))
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CheckData {
+    pub source: SubnetID,
+    #[serde(with = "serde_bytes")]
+    pub tip_set: Vec<u8>,
+    pub epoch: ChainEpoch,
+    pub prev_check: TCid<TLink<Checkpoint>>,
+    pub children: Vec<ChildCheck>,
+    pub cross_msgs: Vec<CrossMsgMeta>,
+}
+impl Cbor for CheckData {}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct CrossMsgMeta {
+    pub from: SubnetID,
+    pub to: SubnetID,
+    pub msgs_cid: TCid<TLink<CrossMsgs>>,
+    pub nonce: u64,
+    #[serde(with = "bigint_ser")]
+    pub value: TokenAmount,
+}
+impl Cbor for CrossMsgMeta {}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct StorableMsg {
+    pub from: Address,
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    #[serde(with = "bigint_ser")]
+    pub value: TokenAmount,
+    pub nonce: u64,
+}
+impl Cbor for StorableMsg {}
+
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct CrossMsgs {
+    pub msgs: Vec<StorableMsg>,
+    pub metas: Vec<CrossMsgMeta>,
+}
+impl Cbor for CrossMsgs {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AtomicExec {
+    pub params: AtomicExecParams,
+    pub submitted: HashMap<TAddressKey<ID>, Cid>,
+    pub status: ExecStatus,
+}
+impl Cbor for AtomicExec {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AtomicExecParams {
+    pub msgs: Vec<StorableMsg>,
+    pub inputs: HashMap<HierarchicalId, LockedStateInfo>,
+}
@@ -0,0 +1,400 @@
+use actor_primitives::taddress::{TAddress, TAddressKey};
+use actor_primitives::tcid::{MapKey, TAmt, TCid, THamt};
+use cid::multihash::{Code, MultihashDigest};
+use cid::Cid;
+use fil_actor_hierarchical_sca::exec::ExecStatus;
+use fil_actor_hierarchical_sca::subnet::{ConsensusType, Status};
+use fil_actor_hierarchical_sca::{
+    Checkpoint, ConstructorParams, CrossMsgMeta, CrossMsgs, ListSubnetsParams, ListSubnetsReturn,
+    Method, RegisterParams, State, StorableMsg, Subnet, DEFAULT_ESCAPE_HATCH_PERIODS, NATIVE_ASSET,
+};
+use fil_actors_runtime::test_utils::{SCA_ACTOR_CODE_ID, SUBNET_ACTOR_CODE_ID};
+use fil_actors_runtime::SCA_ACTOR_ADDR;
+use fil_builtin_actors_migration::sca::{migrate_state_tree, v1, Config};
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::{Cbor, RawBytes, DAG_CBOR};
+use fvm_shared::address::subnet::ROOTNET_ID;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use test_vm::util::{apply_ok, create_accounts};
+use test_vm::{actor, TEST_FAUCET_ADDR, VM};
+
+const MIGRATION_EPOCH: i64 = 100;
+
+#[test]
+fn migrate_sca_from_v1() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let params = ConstructorParams {
+        network_name: ROOTNET_ID.to_string(),
+        checkpoint_period: 10,
+        escape_hatch_periods: 10,
+    };
+    let st = State::new(&store, params).unwrap();
+    v.set_actor(
+        *SCA_ACTOR_ADDR,
+        actor(*SCA_ACTOR_CODE_ID, v.put_store(&st), 0, TokenAmount::zero()),
+    );
+    let balance = TokenAmount::from(1_000u64) * TokenAmount::from(10u64.pow(18));
+    let caller = create_accounts(&v, 1, balance.clone())[0];
+    let subnet_actor = Address::new_id(10_000);
+    let sibling_actor = Address::new_id(10_001);
+    let shid = SubnetID::new(&ROOTNET_ID, subnet_actor);
+    let sibling = SubnetID::new(&ROOTNET_ID, sibling_actor);
+    let fil = TokenAmount::from(10_u64.pow(18));
+    let half_fil = TokenAmount::from(5 * 10_u64.pow(17));
+
+    // build the state with the actors: two subnets, one of them funded and
+    // committing a checkpoint with messages to the root and to its sibling.
+    for (i, addr) in [subnet_actor, sibling_actor].into_iter().enumerate() {
+        v.set_actor(addr, actor(*SUBNET_ACTOR_CODE_ID, v.put_store(&()), 0, TokenAmount::zero()));
+        apply_ok(&v, TEST_FAUCET_ADDR, addr, balance.clone(), METHOD_SEND, RawBytes::default());
+        let params =
+            RegisterParams { name: format!("subnet-{}", i), consensus: ConsensusType::Dummy };
+        apply_ok(&v, addr, *SCA_ACTOR_ADDR, fil.clone(), Method::Register as u64, params);
+    }
+    let params = SubnetParams(shid.clone());
+    apply_ok(&v, caller, *SCA_ACTOR_ADDR, 2 * &fil, Method::Fund as u64, params);
+    let mut ch = Checkpoint::new(shid.clone(), 10);
+    ch.set_tipset(&[cid_of(b"tipset-10")], cid_of(b"state-10"));
+    for to in [&*ROOTNET_ID, &sibling] {
+        let mut meta = CrossMsgMeta::new(&shid, to);
+        meta.msgs_cid = TCid::from(cid_of(to.to_string().as_bytes()));
+        meta.value = half_fil.clone();
+        ch.append_msgmeta(meta).unwrap();
+    }
+    commit(&v, subnet_actor, &ch, ExitCode::OK);
+    let current: State = v.get_state(*SCA_ACTOR_ADDR).unwrap();
+
+    // roll the SCA back to the v1 layout, as it'd be on chain before the migration
+    let mut old = to_v1(&store, &current);
+    // along with an atomic execution some of its parties already submitted an output to
+    let submitted: HashMap<_, _> = [1001, 1002]
+        .into_iter()
+        .map(|id| {
+            let addr = TAddressKey(TAddress::try_from(Address::new_id(id)).unwrap());
+            (addr, cid_of(format!("output-{}", id).as_bytes()))
+        })
+        .collect();
+    let exec_msg = v1::StorableMsg {
+        from: Address::new_hierarchical(&ROOTNET_ID, &Address::new_id(1001)).unwrap(),
+        to: Address::new_hierarchical(&shid, &Address::new_id(1002)).unwrap(),
+        method: METHOD_SEND,
+        params: RawBytes::default(),
+        value: TokenAmount::zero(),
+        nonce: 0,
+    };
+    let exec = v1::AtomicExec {
+        params: v1::AtomicExecParams { msgs: vec![exec_msg.clone()], inputs: HashMap::new() },
+        submitted: submitted.clone(),
+        status: ExecStatus::Initialized,
+    };
+    let exec_cid = cid_of(b"exec");
+    old.atomic_exec_registry.set(&store, &exec_cid, exec).unwrap();
+    let old_prev = get_v1_subnet(&store, &old, &shid).prev_checkpoint.unwrap();
+    let mut sca = v.get_actor(*SCA_ACTOR_ADDR).unwrap();
+    sca.head = v.put_store(&old);
+    v.set_actor(*SCA_ACTOR_ADDR, sca);
+
+    // migrate the state tree
+    let cfg = Config { epoch: MIGRATION_EPOCH, consensus: ConsensusType::Mir };
+    let root = migrate_state_tree(&store, &v.checkpoint(), &cfg).unwrap();
+    v.rollback(root);
+    let st: State = v.get_state(*SCA_ACTOR_ADDR).unwrap();
+
+    // everything recorded in v1 round-trips
+    assert_eq!(st.network_name, current.network_name);
+    assert_eq!(st.total_subnets, 2);
+    assert_eq!(st.min_stake, current.min_stake);
+    assert_eq!(st.check_period, current.check_period);
+    assert_eq!(st.escape_hatch_periods, DEFAULT_ESCAPE_HATCH_PERIODS);
+    assert_eq!(st.nonce, current.nonce);
+    assert_eq!(st.bottomup_nonce, current.bottomup_nonce);
+    assert_eq!(st.applied_bottomup_nonce, current.applied_bottomup_nonce);
+    assert_eq!(st.applied_topdown_nonce, current.applied_topdown_nonce);
+    assert!(st.get_asset(&store, 1).unwrap().is_none());
+    assert_eq!(
+        amt_entries(&store, &st.bottomup_msg_meta),
+        amt_entries(&store, &current.bottomup_msg_meta)
+    );
+    assert_eq!(
+        hamt_entries(&store, &st.check_msg_registry),
+        hamt_entries(&store, &current.check_msg_registry)
+    );
+    assert!(!amt_entries(&store, &st.bottomup_msg_meta).is_empty());
+    assert!(!hamt_entries(&store, &st.check_msg_registry).is_empty());
+
+    let windows = hamt_entries(&store, &current.checkpoints);
+    assert_eq!(windows.len(), 1);
+    for (epoch, ch) in windows {
+        let migrated = st.checkpoints.get(&store, &epoch).unwrap().unwrap();
+        assert_eq!(migrated.data.children, ch.data.children);
+        assert_eq!(migrated.data.cross_msgs, ch.data.cross_msgs);
+        migrated.data.check_roots().unwrap();
+    }
+
+    for id in [&shid, &sibling] {
+        let before = current.get_subnet(&store, id).unwrap().unwrap();
+        let sub = st.get_subnet(&store, id).unwrap().unwrap();
+        assert_eq!(sub.stake, before.stake);
+        assert_eq!(sub.circ_supply, before.circ_supply);
+        assert_eq!(sub.nonce, before.nonce);
+        assert_eq!(sub.status, Status::Active);
+        assert_eq!(sub.name, id.to_string());
+        assert_eq!(sub.consensus, ConsensusType::Mir);
+        assert_eq!(sub.code, before.code);
+        assert_eq!(sub.last_checkpoint_epoch, MIGRATION_EPOCH);
+        assert_eq!(
+            amt_entries(&store, &sub.top_down_msgs),
+            amt_entries(&store, &before.top_down_msgs)
+        );
+    }
+    let sub = st.get_subnet(&store, &shid).unwrap().unwrap();
+    assert_eq!(sub.circ_supply, half_fil.clone() * 2u8);
+    assert_eq!(amt_entries(&store, &sub.top_down_msgs)[0].1.asset, NATIVE_ASSET);
+    let prev = sub.prev_checkpoint.clone().unwrap();
+    assert_eq!(prev.epoch(), 10);
+    assert_eq!(prev.data.cross_msgs, ch.data.cross_msgs);
+    prev.data.check_roots().unwrap();
+    // the migrated checkpoint has a new CID, but the subnet keeps the v1 one
+    assert_ne!(prev.cid(), old_prev.cid());
+    assert_eq!(sub.prev_checkpoint_cid.cid(), old_prev.cid());
+    // and records its tipset as committed
+    assert_eq!(sub.committed_tipsets.get(&store, &prev.data.tip_set).unwrap(), Some(10));
+
+    // atomic executions keep their messages, status and submitted outputs
+    let exec = st.atomic_exec_registry.get(&store, &exec_cid).unwrap().unwrap();
+    assert_eq!(exec.status(), ExecStatus::Initialized);
+    assert_eq!(exec.submitted(), &submitted);
+    let msg = &exec.params().msgs[0];
+    assert_eq!((msg.from, msg.to, msg.asset), (exec_msg.from, exec_msg.to, NATIVE_ASSET));
+
+    // the next checkpoint of the subnet points to the v1 CID of its previous one
+    let mut next = Checkpoint::new(shid.clone(), 20);
+    next.set_tipset(&[cid_of(b"tipset-20")], cid_of(b"state-20"));
+    next.data.prev_check = TCid::from(prev.cid());
+    commit(&v, subnet_actor, &next, ExitCode::USR_ILLEGAL_ARGUMENT);
+    next.data.prev_check = TCid::from(old_prev.cid());
+    commit(&v, subnet_actor, &next, ExitCode::OK);
+    let st: State = v.get_state(*SCA_ACTOR_ADDR).unwrap();
+    let sub = st.get_subnet(&store, &shid).unwrap().unwrap();
+    assert_eq!(sub.prev_checkpoint, Some(next.clone()));
+    assert_eq!(sub.prev_checkpoint_cid.cid(), next.cid());
+
+    // the migrated state can be queried by users
+    let ret = apply_ok(
+        &v,
+        caller,
+        *SCA_ACTOR_ADDR,
+        TokenAmount::zero(),
+        Method::ListSubnets as u64,
+        ListSubnetsParams { offset: 0, limit: 0 },
+    );
+    let ret: ListSubnetsReturn = ret.deserialize().unwrap();
+    assert_eq!(ret.subnets.len(), 2);
+    assert!(ret.subnets.contains(&sub));
+}
+
+#[derive(Serialize, Deserialize)]
+struct SubnetParams(SubnetID);
+impl Cbor for SubnetParams {}
+
+fn commit(v: &VM, subnet_actor: Address, ch: &Checkpoint, code: ExitCode) {
+    let ret = v
+        .apply_message(
+            subnet_actor,
+            *SCA_ACTOR_ADDR,
+            TokenAmount::zero(),
+            Method::CommitChildCheckpoint as u64,
+            ch.clone(),
+        )
+        .unwrap();
+    assert_eq!(ret.code, code);
+}
+
+/// Converts the SCA state to its v1 layout, dropping what v1 didn't record.
+fn to_v1(store: &MemoryBlockstore, st: &State) -> v1::State {
+    let mut subnets = TCid::new_hamt(store).unwrap();
+    // subnets are keyed by their ID, so we can't iterate them with typed keys.
+    let mut old = Vec::new();
+    st.subnets
+        .load(store)
+        .unwrap()
+        .for_each(|_, sub| {
+            old.push(subnet_to_v1(store, sub));
+            Ok(())
+        })
+        .unwrap();
+    subnets
+        .update(store, |map| {
+            for sub in old {
+                map.set(sub.id.to_bytes().into(), sub).map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    let mut checkpoints = TCid::new_hamt(store).unwrap();
+    checkpoints
+        .batch_set(
+            store,
+            hamt_entries(store, &st.checkpoints)
+                .into_iter()
+                .map(|(epoch, ch)| (epoch, checkpoint_to_v1(&ch))),
+        )
+        .unwrap();
+    let mut registry = TCid::new_hamt(store).unwrap();
+    registry
+        .batch_set(
+            store,
+            hamt_entries(store, &st.check_msg_registry)
+                .into_iter()
+                .map(|(cid, msgs)| (TCid::from(cid.cid()), cross_msgs_to_v1(&msgs))),
+        )
+        .unwrap();
+    let mut bottomup_msg_meta = TCid::new_amt(store).unwrap();
+    bottomup_msg_meta
+        .batch_set(
+            store,
+            amt_entries(store, &st.bottomup_msg_meta)
+                .into_iter()
+                .map(|(i, meta)| (i, msgmeta_to_v1(&meta))),
+        )
+        .unwrap();
+    // the actors don't run atomic executions in the test, which adds one to the v1 state
+    assert!(hamt_entries(store, &st.atomic_exec_registry).is_empty());
+
+    v1::State {
+        network_name: st.network_name.clone(),
+        total_subnets: st.total_subnets,
+        min_stake: st.min_stake.clone(),
+        subnets,
+        check_period: st.check_period,
+        checkpoints,
+        check_msg_registry: registry,
+        nonce: st.nonce,
+        bottomup_nonce: st.bottomup_nonce,
+        bottomup_msg_meta,
+        applied_bottomup_nonce: st.applied_bottomup_nonce,
+        applied_topdown_nonce: st.applied_topdown_nonce,
+        atomic_exec_registry: TCid::new_hamt(store).unwrap(),
+    }
+}
+
+fn subnet_to_v1(store: &MemoryBlockstore, sub: &Subnet) -> v1::Subnet {
+    let mut top_down_msgs = TCid::new_amt(store).unwrap();
+    top_down_msgs
+        .batch_set(
+            store,
+            amt_entries(store, &sub.top_down_msgs).into_iter().map(|(i, msg)| (i, msg_to_v1(&msg))),
+        )
+        .unwrap();
+    v1::Subnet {
+        id: sub.id.clone(),
+        stake: sub.stake.clone(),
+        top_down_msgs,
+        nonce: sub.nonce,
+        circ_supply: sub.circ_supply.clone(),
+        status: sub.status,
+        prev_checkpoint: sub.prev_checkpoint.as_ref().map(checkpoint_to_v1),
+    }
+}
+
+fn checkpoint_to_v1(ch: &Checkpoint) -> v1::Checkpoint {
+    v1::Checkpoint {
+        data: v1::CheckData {
+            source: ch.data.source.clone(),
+            tip_set: ch.data.tip_set.clone(),
+            epoch: ch.data.epoch,
+            prev_check: TCid::from(ch.data.prev_check.cid()),
+            children: ch.data.children.clone(),
+            cross_msgs: ch.data.cross_msgs.iter().map(msgmeta_to_v1).collect(),
+        },
+        sig: ch.signature().clone(),
+    }
+}
+
+fn msgmeta_to_v1(meta: &CrossMsgMeta) -> v1::CrossMsgMeta {
+    assert!(meta.assets.is_empty());
+    v1::CrossMsgMeta {
+        from: meta.from.clone(),
+        to: meta.to.clone(),
+        msgs_cid: TCid::from(meta.msgs_cid.cid()),
+        nonce: meta.nonce,
+        value: meta.value.clone(),
+    }
+}
+
+fn msg_to_v1(msg: &StorableMsg) -> v1::StorableMsg {
+    assert_eq!(msg.asset, NATIVE_ASSET);
+    v1::StorableMsg {
+        from: msg.from,
+        to: msg.to,
+        method: msg.method,
+        params: msg.params.clone(),
+        value: msg.value.clone(),
+        nonce: msg.nonce,
+    }
+}
+
+fn cross_msgs_to_v1(msgs: &CrossMsgs) -> v1::CrossMsgs {
+    v1::CrossMsgs {
+        msgs: msgs.msgs.iter().map(msg_to_v1).collect(),
+        metas: msgs.metas.iter().map(msgmeta_to_v1).collect(),
+    }
+}
+
+fn get_v1_subnet(store: &MemoryBlockstore, st: &v1::State, id: &SubnetID) -> v1::Subnet {
+    let mut found = None;
+    st.subnets
+        .load(store)
+        .unwrap()
+        .for_each(|_, sub| {
+            if &sub.id == id {
+                found = Some(sub.clone());
+            }
+            Ok(())
+        })
+        .unwrap();
+    found.unwrap()
+}
+
+fn hamt_entries<K, V, const W: u32>(
+    store: &MemoryBlockstore,
+    hamt: &TCid<THamt<K, V, W>>,
+) -> Vec<(K, V)>
+where
+    K: MapKey,
+    V: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Clone,
+{
+    let mut entries = Vec::new();
+    hamt.for_each(store, |k, v| {
+        entries.push((k, v.clone()));
+        Ok(())
+    })
+    .unwrap();
+    entries
+}
+
+fn amt_entries<V, const W: u32>(store: &MemoryBlockstore, amt: &TCid<TAmt<V, W>>) -> Vec<(u64, V)>
+where
+    V: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Clone,
+{
+    let mut entries = Vec::new();
+    amt.for_each(store, |i, v| {
+        entries.push((i, v.clone()));
+        Ok(())
+    })
+    .unwrap();
+    entries
+}
+
+fn cid_of(data: &[u8]) -> Cid {
+    Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(data))
+}
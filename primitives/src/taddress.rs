@@ -1,10 +1,16 @@
 use std::{convert::TryFrom, fmt::Display, marker::PhantomData, str::FromStr};
 
+use anyhow::anyhow;
 use serde::de::Error;
 
+use fil_actors_runtime::runtime::Runtime;
+use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::Cbor;
+use fvm_ipld_hamt::BytesKey;
 use fvm_shared::address::{Address, Payload, SubnetID};
 
+use crate::tcid::MapKey;
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct TAddress<T> {
     addr: Address,
@@ -113,6 +119,56 @@ impl<A> TAddress<Hierarchical<A>> {
     }
 }
 
+impl TAddress<AnyRawAddr> {
+    /// Resolve the address to its ID address through the runtime.
+    pub fn resolve_id<BS, RT>(&self, rt: &RT) -> anyhow::Result<TAddress<ID>>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        match rt.resolve_address(&self.addr) {
+            Some(id) => Ok(TAddress::try_from(id)?),
+            None => Err(anyhow!("couldn't resolve id address for {}", self.addr)),
+        }
+    }
+}
+
+impl TAddress<Hierarchical<AnyRawAddr>> {
+    /// Resolve the raw address wrapped by the hierarchical address to its ID
+    /// address through the runtime, keeping the subnet of the address.
+    pub fn resolve_id<BS, RT>(&self, rt: &RT) -> anyhow::Result<TAddress<Hierarchical<ID>>>
+    where
+        BS: Blockstore,
+        RT: Runtime<BS>,
+    {
+        let id = self.raw_addr().resolve_id(rt)?;
+        let addr = Address::new_hierarchical(&self.subnet(), id.addr())?;
+        Ok(TAddress { addr, _phantom: PhantomData })
+    }
+}
+
+/// Used as a key in HAMTs, the same way as the underlying `Address`.
+impl<T> MapKey for TAddress<T>
+where
+    Self: TryFrom<Address>,
+    <Self as TryFrom<Address>>::Error: Display,
+{
+    fn from_bytes(b: &[u8]) -> anyhow::Result<Self> {
+        let raw = Address::from_bytes(b)?;
+        Self::try_from(raw).map_err(|e| anyhow!("wrong address type: {}", e))
+    }
+
+    fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.addr.to_bytes())
+    }
+}
+
+impl<T> From<&TAddress<T>> for BytesKey {
+    fn from(addr: &TAddress<T>) -> Self {
+        BytesKey::from(addr.to_bytes())
+    }
+}
+
 /// Serializes exactly as its underlying `Address`.
 impl<T> serde::Serialize for TAddress<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -189,3 +245,75 @@ where
     <TAddress<T> as TryFrom<Address>>::Error: Display,
 {
 }
+
+impl<T> From<TAddress<T>> for TAddressKey<T> {
+    fn from(addr: TAddress<T>) -> Self {
+        Self(addr)
+    }
+}
+
+/// Used as a key in HAMTs, the same way as the underlying `Address`.
+impl<T> MapKey for TAddressKey<T>
+where
+    TAddress<T>: TryFrom<Address>,
+    <TAddress<T> as TryFrom<Address>>::Error: Display,
+{
+    fn from_bytes(b: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self(TAddress::<T>::from_bytes(b)?))
+    }
+
+    fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self.0.addr.to_bytes())
+    }
+}
+
+impl<T> From<&TAddressKey<T>> for BytesKey {
+    fn from(key: &TAddressKey<T>) -> Self {
+        BytesKey::from(&key.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tcid::{TCid, THamt};
+    use fvm_ipld_blockstore::MemoryBlockstore;
+
+    #[test]
+    fn taddress_as_hamt_key() {
+        let store = MemoryBlockstore::new();
+        let mut map: TCid<THamt<TAddress<ID>, u64>> = TCid::new_hamt(&store).unwrap();
+
+        let addr = TAddress::<ID>::try_from(Address::new_id(100)).unwrap();
+        map.set(&store, &addr, 1).unwrap();
+        assert_eq!(map.get(&store, &addr).unwrap(), Some(1));
+
+        map.for_each(&store, |k, v| {
+            assert_eq!(k, addr);
+            assert_eq!(*v, 1);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn taddress_key_rejects_wrong_type() {
+        let bls = Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+        let bytes = bls.to_bytes();
+        assert!(<TAddress<ID> as MapKey>::from_bytes(&bytes).is_err());
+        let key = <TAddressKey<BLS> as MapKey>::from_bytes(&bytes).unwrap();
+        assert_eq!(key.0.addr(), &bls);
+    }
+
+    #[test]
+    fn hierarchical_key_round_trip() {
+        let sn = SubnetID::from_str("/root/f0101").unwrap();
+        let addr = Address::new_hierarchical(&sn, &Address::new_id(100)).unwrap();
+        let key = TAddressKey(TAddress::<Hierarchical<ID>>::try_from(addr).unwrap());
+
+        let bytes = MapKey::to_bytes(&key).unwrap();
+        let parsed = <TAddressKey<Hierarchical<ID>> as MapKey>::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, key);
+        assert_eq!(parsed.0.subnet(), sn);
+    }
+}
@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 
 use crate::tcid_ops;
 
-use super::{TCid, TCidContent};
+use super::{CodeType, CodedStore, Diff, TCid, TCidContent};
 use anyhow::{anyhow, Result};
 use fil_actors_runtime::fvm_ipld_amt::Amt;
 use fil_actors_runtime::fvm_ipld_amt::Error as AmtError;
@@ -48,29 +48,34 @@ pub struct TAmt<V, const W: u32 = AMT_BIT_WIDTH> {
 
 impl<V, const W: u32> TCidContent for TAmt<V, W> {}
 
-impl<V, const W: u32> TCid<TAmt<V, W>>
+impl<V, const W: u32, C: CodeType> TCid<TAmt<V, W>, C>
 where
     V: Serialize + DeserializeOwned,
 {
     /// Initialize an empty data structure, flush it to the store and capture the `Cid`.
     pub fn new_amt<S: Blockstore>(store: &S) -> Result<Self> {
-        let cid = Amt::<V, _>::new_with_bit_width(store, W)
+        let cid = Amt::<V, _>::new_with_bit_width(CodedStore::<S, C>::new(store), W)
             .flush()
             .map_err(|e| anyhow!("Failed to create empty array: {}", e))?;
-
         Ok(Self::from(cid))
     }
 
     /// Load the data pointing at the store with the underlying `Cid` as its root, if it exists.
-    pub fn maybe_load<'s, S: Blockstore>(&self, store: &'s S) -> Result<Option<Amt<V, &'s S>>> {
-        match Amt::<V, _>::load(&self.cid, store) {
+    pub fn maybe_load<'s, S: Blockstore>(
+        &self,
+        store: &'s S,
+    ) -> Result<Option<Amt<V, CodedStore<'s, S, C>>>> {
+        match Amt::<V, _>::load(&self.cid, CodedStore::<S, C>::new(store)) {
             Ok(content) => Ok(Some(content)),
             Err(AmtError::CidNotFound(_)) => Ok(None),
             Err(other) => Err(anyhow!(other)),
         }
     }
 
-    pub fn flush<'s, S: Blockstore>(&mut self, mut value: Amt<V, &'s S>) -> Result<Amt<V, &'s S>> {
+    pub fn flush<'s, S: Blockstore>(
+        &mut self,
+        mut value: Amt<V, CodedStore<'s, S, C>>,
+    ) -> Result<Amt<V, CodedStore<'s, S, C>>> {
         let cid =
             value.flush().map_err(|e| anyhow!("error flushing {}: {}", type_name::<Self>(), e))?;
         self.cid = cid;
@@ -78,7 +83,100 @@ where
     }
 }
 
-tcid_ops!(TAmt<V : Serialize + DeserializeOwned, W const: u32> => Amt<V, &'s S>);
+tcid_ops!(TAmt<V : Serialize + DeserializeOwned, W const: u32>, C: CodeType => Amt<V, CodedStore<'s, S, C>>);
+
+/// Typed access to the entries of the AMT.
+///
+/// Operations that mutate the AMT flush it and overwrite the `Cid`.
+impl<V, const W: u32, C: CodeType> TCid<TAmt<V, W>, C>
+where
+    V: Serialize + DeserializeOwned + PartialEq + Clone,
+{
+    /// Get the value stored at an index, if any.
+    pub fn get<S: Blockstore>(&self, store: &S, index: u64) -> Result<Option<V>> {
+        let array = self.load(store)?;
+        let value = array
+            .get(index)
+            .map_err(|e| anyhow!("error getting entry from {}: {}", type_name::<Self>(), e))?;
+        Ok(value.cloned())
+    }
+
+    /// Set the value at an index.
+    pub fn set<S: Blockstore>(&mut self, store: &S, index: u64, value: V) -> Result<()> {
+        self.update(store, |array| {
+            array
+                .set(index, value)
+                .map_err(|e| anyhow!("error setting entry in {}: {}", type_name::<Self>(), e))
+        })
+    }
+
+    /// Delete the value at an index, returning the deleted value if there was one.
+    pub fn delete<S: Blockstore>(&mut self, store: &S, index: u64) -> Result<Option<V>> {
+        self.modify(store, |array| {
+            array
+                .delete(index)
+                .map_err(|e| anyhow!("error deleting entry from {}: {}", type_name::<Self>(), e))
+        })
+    }
+
+    /// Set several entries at once, flushing only once at the end.
+    pub fn batch_set<S: Blockstore>(
+        &mut self,
+        store: &S,
+        entries: impl IntoIterator<Item = (u64, V)>,
+    ) -> Result<()> {
+        self.update(store, |array| {
+            for (i, v) in entries {
+                array.set(i, v).map_err(|e| {
+                    anyhow!("error setting entry in {}: {}", type_name::<Self>(), e)
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Iterate over all the entries of the AMT with their indexes.
+    pub fn for_each<S: Blockstore>(
+        &self,
+        store: &S,
+        f: impl FnMut(u64, &V) -> Result<()>,
+    ) -> Result<()> {
+        let array = self.load(store)?;
+        array.for_each(f).map_err(|e| anyhow!("error iterating {}: {}", type_name::<Self>(), e))
+    }
+
+    /// Compute the entries added, removed and changed from `old` to `new`.
+    pub fn diff<S: Blockstore>(old: &Self, new: &Self, store: &S) -> Result<Diff<u64, V>> {
+        let mut diff = Diff::default();
+        if old.cid == new.cid {
+            return Ok(diff);
+        }
+        let old_array = old.load(store)?;
+        let new_array = new.load(store)?;
+
+        old_array
+            .for_each(|i, v| {
+                match new_array.get(i)? {
+                    Some(nv) if nv != v => diff.changed.push((i, v.clone(), nv.clone())),
+                    Some(_) => {}
+                    None => diff.removed.push((i, v.clone())),
+                }
+                Ok(())
+            })
+            .map_err(|e| anyhow!("error diffing {}: {}", type_name::<Self>(), e))?;
+
+        new_array
+            .for_each(|i, v| {
+                if old_array.get(i)?.is_none() {
+                    diff.added.push((i, v.clone()));
+                }
+                Ok(())
+            })
+            .map_err(|e| anyhow!("error diffing {}: {}", type_name::<Self>(), e))?;
+
+        Ok(diff)
+    }
+}
 
 /// This `Default` implementation is unsound in that while it
 /// creates `TAmt` instances with a correct `Cid` value, this value
@@ -87,7 +185,7 @@ tcid_ops!(TAmt<V : Serialize + DeserializeOwned, W const: u32> => Amt<V, &'s S>)
 ///
 /// The main purpose is to allow the `#[derive(Default)]` to be
 /// applied on types that use a `TAmt` field, if that's unavoidable.
-impl<V, const W: u32> Default for TCid<TAmt<V, W>>
+impl<V, const W: u32, C: CodeType> Default for TCid<TAmt<V, W>, C>
 where
     V: Serialize + DeserializeOwned,
 {
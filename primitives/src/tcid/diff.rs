@@ -0,0 +1,26 @@
+/// Typed changes between two versions of the same HAMT or AMT.
+///
+/// Entries are reported by key (or index, for AMTs), together with the values
+/// found in the old and/or the new version of the data structure.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Diff<K, V> {
+    /// Entries present in the new version but not in the old one.
+    pub added: Vec<(K, V)>,
+    /// Entries present in the old version but not in the new one.
+    pub removed: Vec<(K, V)>,
+    /// Entries present in both versions with a different value, as `(key, old, new)`.
+    pub changed: Vec<(K, V, V)>,
+}
+
+impl<K, V> Default for Diff<K, V> {
+    fn default() -> Self {
+        Self { added: Vec::new(), removed: Vec::new(), changed: Vec::new() }
+    }
+}
+
+impl<K, V> Diff<K, V> {
+    /// Check whether there are no changes at all.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
@@ -3,15 +3,98 @@ use std::marker::PhantomData;
 
 use crate::tcid_ops;
 use anyhow::{anyhow, Result};
-use fil_actors_runtime::{make_empty_map, make_map_with_root_and_bitwidth};
+use cid::Cid;
+use fil_actors_runtime::{parse_uint_key, u64_key};
 use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
 use fvm_ipld_hamt::Error as HamtError;
-use fvm_ipld_hamt::Hamt;
+use fvm_ipld_hamt::{BytesKey, Hamt};
+use fvm_shared::address::Address;
 use fvm_shared::HAMT_BIT_WIDTH;
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 
-use super::{TCid, TCidContent};
+use super::{CodeType, CodedStore, Diff, TCid, TCidContent};
+
+/// Conversion between typed keys and the `BytesKey` representation
+/// they have inside a HAMT.
+pub trait MapKey: Sized {
+    fn from_bytes(b: &[u8]) -> Result<Self>;
+    fn to_bytes(&self) -> Result<Vec<u8>>;
+}
+
+impl MapKey for Vec<u8> {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        Ok(b.to_vec())
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.clone())
+    }
+}
+
+impl MapKey for String {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        Ok(String::from_utf8(b.to_vec())?)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.as_bytes().to_vec())
+    }
+}
+
+/// Unsigned integers are encoded as varints, same as `fil_actors_runtime::u64_key`.
+impl MapKey for u64 {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        parse_uint_key(b).map_err(|e| anyhow!("failed to parse uint key: {}", e))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(u64_key(*self).0)
+    }
+}
+
+/// Signed integers (e.g. `ChainEpoch`) are encoded as fixed-size little-endian bytes.
+impl MapKey for i64 {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        let bytes = <[u8; 8]>::try_from(b).map_err(|_| anyhow!("invalid i64 key length"))?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.to_le_bytes().to_vec())
+    }
+}
+
+impl MapKey for Cid {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        Ok(Cid::try_from(b)?)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(Cid::to_bytes(self))
+    }
+}
+
+impl MapKey for Address {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        Ok(Address::from_bytes(b)?)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(Address::to_bytes(self))
+    }
+}
+
+/// Typed CIDs are keyed by their underlying `Cid`.
+impl<T: TCidContent, C> MapKey for TCid<T, C> {
+    fn from_bytes(b: &[u8]) -> Result<Self> {
+        Ok(Self::from(<Cid as MapKey>::from_bytes(b)?))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.cid.to_bytes())
+    }
+}
 
 /// Static typing information for HAMT fields, a.k.a. `Map`.
 ///
@@ -51,22 +134,25 @@ pub struct THamt<K, V, const W: u32 = HAMT_BIT_WIDTH> {
 
 impl<K, V, const W: u32> TCidContent for THamt<K, V, W> {}
 
-impl<K, V, const W: u32> TCid<THamt<K, V, W>>
+impl<K, V, const W: u32, C: CodeType> TCid<THamt<K, V, W>, C>
 where
     V: Serialize + DeserializeOwned,
 {
     /// Initialize an empty data structure, flush it to the store and capture the `Cid`.
     pub fn new_hamt<S: Blockstore>(store: &S) -> Result<Self> {
-        let cid = make_empty_map::<_, V>(store, W)
+        let cid = Hamt::<_, V>::new_with_bit_width(CodedStore::<S, C>::new(store), W)
             .flush()
             .map_err(|e| anyhow!("Failed to create empty map: {}", e))?;
-
         Ok(Self::from(cid))
     }
 
     /// Load the data pointing at the store with the underlying `Cid` as its root, if it exists.
-    pub fn maybe_load<'s, S: Blockstore>(&self, store: &'s S) -> Result<Option<Hamt<&'s S, V>>> {
-        match make_map_with_root_and_bitwidth::<S, V>(&self.cid, store, W) {
+    pub fn maybe_load<'s, S: Blockstore>(
+        &self,
+        store: &'s S,
+    ) -> Result<Option<Hamt<CodedStore<'s, S, C>, V>>> {
+        let store = CodedStore::<S, C>::new(store);
+        match Hamt::<_, V>::load_with_bit_width(&self.cid, store, W) {
             Ok(content) => Ok(Some(content)),
             Err(HamtError::CidNotFound(_)) => Ok(None),
             Err(other) => Err(anyhow!(other)),
@@ -76,8 +162,8 @@ where
     /// Flush the data to the store and overwrite the `Cid`.
     pub fn flush<'s, S: Blockstore>(
         &mut self,
-        mut value: Hamt<&'s S, V>,
-    ) -> Result<Hamt<&'s S, V>> {
+        mut value: Hamt<CodedStore<'s, S, C>, V>,
+    ) -> Result<Hamt<CodedStore<'s, S, C>, V>> {
         let cid =
             value.flush().map_err(|e| anyhow!("error flushing {}: {}", type_name::<Self>(), e))?;
         self.cid = cid;
@@ -85,7 +171,105 @@ where
     }
 }
 
-tcid_ops!(THamt<K, V : Serialize + DeserializeOwned, W const: u32> => Hamt<&'s S, V>);
+tcid_ops!(THamt<K, V : Serialize + DeserializeOwned, W const: u32>, C: CodeType => Hamt<CodedStore<'s, S, C>, V>);
+
+/// Typed access to the entries of the HAMT.
+///
+/// Operations that mutate the HAMT flush it and overwrite the `Cid`.
+impl<K, V, const W: u32, C: CodeType> TCid<THamt<K, V, W>, C>
+where
+    K: MapKey,
+    V: Serialize + DeserializeOwned + PartialEq + Clone,
+{
+    /// Get the value stored under a key, if any.
+    pub fn get<S: Blockstore>(&self, store: &S, key: &K) -> Result<Option<V>> {
+        let map = self.load(store)?;
+        let value = map
+            .get(&BytesKey::from(key.to_bytes()?))
+            .map_err(|e| anyhow!("error getting entry from {}: {}", type_name::<Self>(), e))?;
+        Ok(value.cloned())
+    }
+
+    /// Set the value for a key, returning the previous value if there was one.
+    pub fn set<S: Blockstore>(&mut self, store: &S, key: &K, value: V) -> Result<Option<V>> {
+        let key = BytesKey::from(key.to_bytes()?);
+        self.modify(store, |map| {
+            map.set(key, value)
+                .map_err(|e| anyhow!("error setting entry in {}: {}", type_name::<Self>(), e))
+        })
+    }
+
+    /// Delete the entry for a key, returning the deleted value if there was one.
+    pub fn delete<S: Blockstore>(&mut self, store: &S, key: &K) -> Result<Option<V>> {
+        let key = BytesKey::from(key.to_bytes()?);
+        self.modify(store, |map| {
+            map.delete(&key)
+                .map(|deleted| deleted.map(|(_, v)| v))
+                .map_err(|e| anyhow!("error deleting entry from {}: {}", type_name::<Self>(), e))
+        })
+    }
+
+    /// Set several entries at once, flushing only once at the end.
+    pub fn batch_set<S: Blockstore>(
+        &mut self,
+        store: &S,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<()> {
+        self.update(store, |map| {
+            for (k, v) in entries {
+                map.set(BytesKey::from(k.to_bytes()?), v).map_err(|e| {
+                    anyhow!("error setting entry in {}: {}", type_name::<Self>(), e)
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Iterate over all the entries of the HAMT with their typed keys.
+    pub fn for_each<S: Blockstore>(
+        &self,
+        store: &S,
+        mut f: impl FnMut(K, &V) -> Result<()>,
+    ) -> Result<()> {
+        let map = self.load(store)?;
+        map.for_each(|k, v| f(K::from_bytes(k)?, v))
+            .map_err(|e| anyhow!("error iterating {}: {}", type_name::<Self>(), e))
+    }
+
+    /// Compute the entries added, removed and changed from `old` to `new`.
+    pub fn diff<S: Blockstore>(old: &Self, new: &Self, store: &S) -> Result<Diff<K, V>> {
+        let mut diff = Diff::default();
+        if old.cid == new.cid {
+            return Ok(diff);
+        }
+        let old_map = old.load(store)?;
+        let new_map = new.load(store)?;
+
+        old_map
+            .for_each(|k, v| {
+                match new_map.get(k)? {
+                    Some(nv) if nv != v => {
+                        diff.changed.push((K::from_bytes(k)?, v.clone(), nv.clone()))
+                    }
+                    Some(_) => {}
+                    None => diff.removed.push((K::from_bytes(k)?, v.clone())),
+                }
+                Ok(())
+            })
+            .map_err(|e| anyhow!("error diffing {}: {}", type_name::<Self>(), e))?;
+
+        new_map
+            .for_each(|k, v| {
+                if old_map.get(k)?.is_none() {
+                    diff.added.push((K::from_bytes(k)?, v.clone()));
+                }
+                Ok(())
+            })
+            .map_err(|e| anyhow!("error diffing {}: {}", type_name::<Self>(), e))?;
+
+        Ok(diff)
+    }
+}
 
 /// This `Default` implementation is unsound in that while it
 /// creates `TCid` instances with a correct `Cid` value, this value
@@ -94,7 +278,7 @@ tcid_ops!(THamt<K, V : Serialize + DeserializeOwned, W const: u32> => Hamt<&'s S
 ///
 /// The main purpose is to allow the `#[derive(Default)]` to be
 /// applied on types that use a `TCid` field, if that's unavoidable.
-impl<K, V, const W: u32> Default for TCid<THamt<K, V, W>>
+impl<K, V, const W: u32, C: CodeType> Default for TCid<THamt<K, V, W>, C>
 where
     V: Serialize + DeserializeOwned,
 {
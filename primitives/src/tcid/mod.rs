@@ -1,12 +1,15 @@
 use std::{fmt::Display, marker::PhantomData};
 
 use cid::{multihash::Code, Cid};
+use fvm_ipld_blockstore::{Block, Blockstore};
 
 mod amt;
+mod diff;
 mod hamt;
 mod link;
 pub use amt::TAmt;
-pub use hamt::THamt;
+pub use diff::Diff;
+pub use hamt::{MapKey, THamt};
 pub use link::TLink;
 
 /// Helper type to be able to define `Code` as a generic parameter.
@@ -34,6 +37,43 @@ impl<T: TCidContent, C: CodeType> TCid<T, C> {
     }
 }
 
+/// Blockstore storing every block it is given under the hash code `C`.
+///
+/// HAMTs and AMTs always flush their nodes using Blake2b256, so they are
+/// loaded over this store for all of their nodes to be hashed with the
+/// code of their `TCid`, not just the root.
+pub struct CodedStore<'s, S, C> {
+    store: &'s S,
+    _phantom_c: PhantomData<C>,
+}
+
+impl<'s, S: Blockstore, C: CodeType> CodedStore<'s, S, C> {
+    pub fn new(store: &'s S) -> Self {
+        CodedStore { store, _phantom_c: PhantomData }
+    }
+}
+
+impl<'s, S: Blockstore, C: CodeType> Blockstore for CodedStore<'s, S, C> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        self.store.get(k)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.store.put_keyed(k, block)
+    }
+
+    fn has(&self, k: &Cid) -> anyhow::Result<bool> {
+        self.store.has(k)
+    }
+
+    fn put<D>(&self, _code: Code, block: &Block<D>) -> anyhow::Result<Cid>
+    where
+        D: AsRef<[u8]>,
+    {
+        self.store.put(C::code(), block)
+    }
+}
+
 impl<T: TCidContent, C> From<Cid> for TCid<T, C> {
     fn from(cid: Cid) -> Self {
         TCid { cid, _phantom_t: PhantomData, _phantom_c: PhantomData }
@@ -155,13 +195,12 @@ pub mod codes {
         };
     }
 
-    // XXX: For some reason none of the other code types work,
-    // not even on their own as a variable:
-    // let c = multihash::Code::Keccak256;
-    // ERROR: no variant or associated item named `Keccak256` found for enum `Code`
-    //        in the current scope variant or associated item not found in `Code`
+    // The variants of `Code` are feature gated, see the `multihash`
+    // dependency in `Cargo.toml` if you need to add any other code.
     code_types! {
-      Blake2b256 => Blake2b256
+      Blake2b256 => Blake2b256,
+      Sha2_256 => Sha2_256,
+      Keccak256 => Keccak256
     }
 }
 
@@ -173,7 +212,7 @@ mod test {
     use fvm_ipld_encoding::tuple::*;
     use fvm_ipld_hamt::BytesKey;
 
-    #[derive(Default, Serialize_tuple, Deserialize_tuple, PartialEq)]
+    #[derive(Default, Serialize_tuple, Deserialize_tuple, PartialEq, Clone, Debug)]
     struct TestRecord {
         foo: u64,
         bar: Vec<u8>,
@@ -247,4 +286,180 @@ mod test {
         let foo = map.get(&BytesKey::from("spam")).unwrap().map(|x| x.foo);
         assert_eq!(foo, Some(1))
     }
+
+    #[test]
+    fn hamt_typed_ops() {
+        let store = MemoryBlockstore::new();
+        let mut map: TCid<THamt<u64, TestRecord>> = TCid::new_hamt(&store).unwrap();
+
+        assert_eq!(map.get(&store, &1).unwrap(), None);
+        map.set(&store, &1, TestRecord { foo: 1, bar: Vec::new() }).unwrap();
+        map.batch_set(
+            &store,
+            vec![
+                (2, TestRecord { foo: 2, bar: Vec::new() }),
+                (3, TestRecord { foo: 3, bar: Vec::new() }),
+            ],
+        )
+        .unwrap();
+        assert_eq!(map.get(&store, &2).unwrap().map(|x| x.foo), Some(2));
+
+        let deleted = map.delete(&store, &3).unwrap();
+        assert_eq!(deleted.map(|x| x.foo), Some(3));
+        assert_eq!(map.delete(&store, &3).unwrap(), None);
+
+        let mut keys = Vec::new();
+        map.for_each(&store, |k, v| {
+            assert_eq!(k, v.foo);
+            keys.push(k);
+            Ok(())
+        })
+        .unwrap();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![1, 2]);
+    }
+
+    #[test]
+    fn amt_typed_ops() {
+        let store = MemoryBlockstore::new();
+        let mut arr: TCid<TAmt<String>> = TCid::new_amt(&store).unwrap();
+
+        arr.set(&store, 0, "foo".into()).unwrap();
+        arr.batch_set(&store, vec![(1, "bar".into()), (5, "baz".into())]).unwrap();
+        assert_eq!(arr.get(&store, 5).unwrap(), Some("baz".into()));
+        assert_eq!(arr.delete(&store, 1).unwrap(), Some("bar".into()));
+
+        let mut entries = Vec::new();
+        arr.for_each(&store, |i, v| {
+            entries.push((i, v.clone()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(entries, vec![(0, "foo".into()), (5, "baz".into())]);
+    }
+
+    #[test]
+    fn hamt_diff() {
+        let store = MemoryBlockstore::new();
+        let mut old: TCid<THamt<String, u64>> = TCid::new_hamt(&store).unwrap();
+        old.batch_set(&store, vec![("a".into(), 1), ("b".into(), 2), ("c".into(), 3)]).unwrap();
+
+        let mut new = old.clone();
+        assert!(TCid::<THamt<String, u64>>::diff(&old, &new, &store).unwrap().is_empty());
+
+        new.delete(&store, &"a".into()).unwrap();
+        new.set(&store, &"b".into(), 20).unwrap();
+        new.set(&store, &"d".into(), 4).unwrap();
+
+        let diff = TCid::<THamt<String, u64>>::diff(&old, &new, &store).unwrap();
+        assert_eq!(diff.added, vec![("d".into(), 4)]);
+        assert_eq!(diff.removed, vec![("a".into(), 1)]);
+        assert_eq!(diff.changed, vec![("b".into(), 2, 20)]);
+    }
+
+    #[test]
+    fn amt_diff() {
+        let store = MemoryBlockstore::new();
+        let mut old: TCid<TAmt<u64>> = TCid::new_amt(&store).unwrap();
+        old.batch_set(&store, vec![(0, 1), (1, 2)]).unwrap();
+
+        let mut new = old.clone();
+        new.set(&store, 1, 3).unwrap();
+        new.set(&store, 2, 4).unwrap();
+        new.delete(&store, 0).unwrap();
+
+        let diff = TCid::<TAmt<u64>>::diff(&old, &new, &store).unwrap();
+        assert_eq!(diff.added, vec![(2, 4)]);
+        assert_eq!(diff.removed, vec![(0, 1)]);
+        assert_eq!(diff.changed, vec![(1, 2, 3)]);
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    struct MixedCodeRecord {
+        pub link: TCid<TLink<TestRecord>, codes::Sha2_256>,
+        pub map: TCid<THamt<String, u64>, codes::Keccak256>,
+        pub array: TCid<TAmt<u64>, codes::Sha2_256>,
+        pub default: TCid<THamt<String, u64>>,
+    }
+
+    #[test]
+    fn mixed_codes_round_trip() {
+        let store = MemoryBlockstore::new();
+        let mut rec = MixedCodeRecord {
+            link: TCid::new_link(&store, &TestRecord::default()).unwrap(),
+            map: TCid::new_hamt(&store).unwrap(),
+            array: TCid::new_amt(&store).unwrap(),
+            default: TCid::new_hamt(&store).unwrap(),
+        };
+
+        rec.link
+            .update(&store, |r| {
+                r.foo = 1;
+                Ok(())
+            })
+            .unwrap();
+        rec.map.set(&store, &"spam".into(), 1).unwrap();
+        rec.array.set(&store, 0, 2).unwrap();
+        rec.default.set(&store, &"eggs".into(), 3).unwrap();
+
+        let code = |c: Code| u64::from(c);
+        assert_eq!(rec.link.cid().hash().code(), code(Code::Sha2_256));
+        assert_eq!(rec.map.cid().hash().code(), code(Code::Keccak256));
+        assert_eq!(rec.array.cid().hash().code(), code(Code::Sha2_256));
+        assert_eq!(rec.default.cid().hash().code(), code(Code::Blake2b256));
+
+        let bytes = fvm_ipld_encoding::to_vec(&rec).unwrap();
+        let rec: MixedCodeRecord = fvm_ipld_encoding::from_slice(&bytes).unwrap();
+
+        assert_eq!(rec.link.load(&store).unwrap().foo, 1);
+        assert_eq!(rec.map.get(&store, &"spam".into()).unwrap(), Some(1));
+        assert_eq!(rec.array.get(&store, 0).unwrap(), Some(2));
+        assert_eq!(rec.default.get(&store, &"eggs".into()).unwrap(), Some(3));
+        assert_eq!(rec.map.cid().hash().code(), code(Code::Keccak256));
+    }
+
+    /// Store recording the `Cid` of every block put into it.
+    #[derive(Default)]
+    struct RecordingStore {
+        store: MemoryBlockstore,
+        puts: std::cell::RefCell<Vec<Cid>>,
+    }
+
+    impl Blockstore for RecordingStore {
+        fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+            self.store.get(k)
+        }
+
+        fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+            self.puts.borrow_mut().push(*k);
+            self.store.put_keyed(k, block)
+        }
+    }
+
+    #[test]
+    fn multi_level_codes() {
+        let store = RecordingStore::default();
+        let mut map: TCid<THamt<u64, u64>, codes::Sha2_256> = TCid::new_hamt(&store).unwrap();
+        let mut array: TCid<TAmt<u64>, codes::Keccak256> = TCid::new_amt(&store).unwrap();
+        map.batch_set(&store, (0..1000).map(|i| (i, i))).unwrap();
+        array.batch_set(&store, (0..1000).map(|i| (i, i))).unwrap();
+        map.set(&store, &1000, 1000).unwrap();
+        array.delete(&store, 0).unwrap();
+
+        // every node of the trees is hashed with the code of its `TCid`,
+        // without Blake2b256 copies of the nodes or the root.
+        let code = |c: Code| u64::from(c);
+        let puts = store.puts.borrow();
+        let sha = puts.iter().filter(|c| c.hash().code() == code(Code::Sha2_256)).count();
+        let keccak = puts.iter().filter(|c| c.hash().code() == code(Code::Keccak256)).count();
+        assert_eq!(sha + keccak, puts.len());
+        assert!(sha > 3 && keccak > 3);
+        assert_eq!(map.cid().hash().code(), code(Code::Sha2_256));
+        assert_eq!(array.cid().hash().code(), code(Code::Keccak256));
+
+        assert_eq!(map.get(&store, &999).unwrap(), Some(999));
+        assert_eq!(map.get(&store, &1000).unwrap(), Some(1000));
+        assert_eq!(array.get(&store, 0).unwrap(), None);
+        assert_eq!(array.get(&store, 999).unwrap(), Some(999));
+    }
 }
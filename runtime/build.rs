@@ -1,58 +1,68 @@
-static NETWORKS: &[(&str, &[&str])] = &[
-    ("mainnet", &["sector-32g", "sector-64g"]),
-    (
-        "caterpillarnet",
-        &[
-            "sector-512m",
-            "sector-32g",
-            "sector-64g",
-            "small-deals",
-            "short-precommit",
-            "min-power-2k",
-        ],
-    ),
-    ("butterflynet", &["sector-512m", "sector-32g", "sector-64g", "min-power-2g"]),
-    ("calibrationnet", &["sector-32g", "sector-64g", "min-power-32g"]),
-    ("devnet", &["sector-2k", "sector-8m", "small-deals", "short-precommit", "min-power-2k"]),
-    (
-        "testing",
-        &[
-            "sector-2k",
-            "sector-8m",
-            "sector-512m",
-            "sector-32g",
-            "sector-64g",
-            "small-deals",
-            "short-precommit",
-            "min-power-2k",
-            "no-provider-deal-collateral",
-        ],
-    ),
-    (
-        "testing-fake-proofs",
-        &[
-            "sector-2k",
-            "sector-8m",
-            "sector-512m",
-            "sector-32g",
-            "sector-64g",
-            "small-deals",
-            "short-precommit",
-            "min-power-2k",
-            "no-provider-deal-collateral",
-            "fake-proofs",
-        ],
-    ),
-];
+use std::fs;
+use std::path::Path;
+
+// The policy definitions, so that the policy file is checked against them when it is baked,
+// and the runtime features of each network.
+#[allow(dead_code)]
+mod builtin {
+    include!("src/builtin/network.rs");
+}
+#[allow(dead_code)]
+mod policy {
+    include!("src/runtime/policy.rs");
+}
+
 const NETWORK_ENV: &str = "BUILD_FIL_NETWORK";
+const POLICY_ENV: &str = "BUILD_FIL_POLICY";
+
+/// Bakes the JSON or TOML policy file given through the environment, if any, into the build.
+/// It is re-encoded as CBOR, so that Wasm actors can decode it without JSON or TOML parsers.
+/// The file is checked against the `Policy` definition here, so that a bad file fails the build.
+fn bake_policy() {
+    println!("cargo:rerun-if-env-changed={}", POLICY_ENV);
+
+    let policy = match std::env::var_os(POLICY_ENV) {
+        Some(path) => {
+            let path = Path::new(&path);
+            println!("cargo:rerun-if-changed={}", path.display());
+            let policy = policy::Policy::from_file(path)
+                .unwrap_or_else(|e| panic!("invalid policy file {}: {}", path.display(), e));
+
+            // Only the fields present in the file are baked, the others are left to the
+            // runtime features, which are not enabled for this script.
+            let content = fs::read_to_string(path).expect("failed to read policy file");
+            let encoded = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("json") => fvm_ipld_encoding::to_vec(
+                    &serde_json::from_str::<serde_json::Value>(&content).unwrap(),
+                ),
+                Some("toml") => {
+                    fvm_ipld_encoding::to_vec(&toml::from_str::<toml::Value>(&content).unwrap())
+                }
+                _ => unreachable!("policy format checked when parsing"),
+            }
+            .expect("failed to encode policy");
+
+            let baked: policy::Policy = fvm_ipld_encoding::from_slice(&encoded)
+                .unwrap_or_else(|e| panic!("policy does not decode from CBOR: {}", e));
+            assert_eq!(baked, policy, "policy changed when encoded as CBOR");
+            encoded
+        }
+        None => Vec::new(),
+    };
+
+    let out_dir = std::env::var_os("OUT_DIR").expect("no OUT_DIR env var");
+    fs::write(Path::new(&out_dir).join("policy.cbor"), policy).expect("failed to write policy");
+}
 
 fn main() {
     let network = std::env::var(NETWORK_ENV).ok();
     println!("cargo:rerun-if-env-changed={}", NETWORK_ENV);
 
     let network = network.as_deref().unwrap_or("mainnet");
-    let features = NETWORKS.iter().find(|(k, _)| k == &network).expect("unknown network").1;
+    let features = policy::NETWORKS.iter().find(|(k, _)| k == &network).expect("unknown network").1;
     for feature in features {
         println!("cargo:rustc-cfg=feature=\"{}\"", feature);
     }
+
+    bake_policy();
 }
@@ -3,9 +3,10 @@
 
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
-use fvm_shared::METHOD_SEND;
+use fvm_shared::{MethodNum, METHOD_SEND};
 
 use crate::runtime::Runtime;
+use crate::{actor_error, ActorError, FIRST_EXPORTED_METHOD_NUMBER};
 
 pub const HAMT_BIT_WIDTH: u32 = 5;
 
@@ -33,3 +34,26 @@ where
         )
     })
 }
+
+/// Restricts the methods below `FIRST_EXPORTED_METHOD_NUMBER` to builtin callers.
+/// Other actors call the FRC-0042 numbers the methods are exported under.
+pub fn restrict_internal_api<BS, RT>(rt: &mut RT, method: MethodNum) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+{
+    if method >= FIRST_EXPORTED_METHOD_NUMBER {
+        return Ok(());
+    }
+    let caller = rt.message().caller();
+    // a caller whose code can't be resolved is treated as non-builtin
+    match rt.get_actor_code_cid(&caller) {
+        Some(code) if rt.resolve_builtin_actor_type(&code).is_some() => Ok(()),
+        _ => Err(actor_error!(
+            forbidden,
+            "method {} is reserved to builtin actors, caller {} isn't one",
+            method,
+            caller
+        )),
+    }
+}
@@ -0,0 +1,101 @@
+// Re-exports for the expansion of `actor_dispatch!`, so that actors don't need
+// to import them.
+pub use fvm_ipld_blockstore::Blockstore;
+pub use fvm_ipld_encoding::RawBytes;
+pub use fvm_shared::MethodNum;
+pub use num_traits::FromPrimitive;
+use serde::de::DeserializeOwned;
+
+use crate::ActorError;
+
+/// Params a dispatched method is given, read from the params of the message.
+pub trait MethodParams: Sized {
+    fn read(params: &RawBytes) -> Result<Self, ActorError>;
+}
+
+/// Params are deserialized to the type the method takes.
+impl<T: DeserializeOwned> MethodParams for T {
+    fn read(params: &RawBytes) -> Result<Self, ActorError> {
+        crate::cbor::deserialize_params(params)
+    }
+}
+
+/// The params of the message as they are, for methods that decode them depending
+/// on the caller.
+pub struct RawParams(pub RawBytes);
+
+impl MethodParams for RawParams {
+    fn read(params: &RawBytes) -> Result<Self, ActorError> {
+        Ok(RawParams(params.clone()))
+    }
+}
+
+/// Generates `ActorCode::invoke_method` from a table mapping the variants of a
+/// `Method` enum to the functions of the actor implementing them.
+///
+/// Each function is given the runtime and, if a params type is listed, the
+/// params deserialized to that type, or as they are for `RawParams`. Params of
+/// methods without one are ignored.
+/// The return value is serialized if a return type is listed, and must be `()`
+/// otherwise. Params that fail to deserialize abort with `USR_SERIALIZATION`,
+/// and unknown method numbers with `USR_UNHANDLED_MESSAGE`.
+///
+/// The dispatch is an exhaustive match on the enum, so a variant missing from
+/// the table fails to compile.
+///
+/// Actors exporting FRC-0042 method numbers prefix the enum with `restricted`,
+/// so that the method numbers below `FIRST_EXPORTED_METHOD_NUMBER` can only be
+/// called by builtin actors.
+///
+/// ```ignore
+/// impl ActorCode for Actor {
+///     actor_dispatch! {
+///         restricted Method {
+///             Constructor => constructor(Address),
+///             PubkeyAddress => pubkey_address() -> Address,
+///             PubkeyAddressExported => pubkey_address() -> Address,
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! actor_dispatch {
+    (restricted $method:ident $arms:tt) => {
+        $crate::actor_dispatch!(@impl $method $arms $crate::restrict_internal_api);
+    };
+    ($method:ident $arms:tt) => {
+        $crate::actor_dispatch!(@impl $method $arms);
+    };
+    (@impl $method:ident {
+        $($variant:ident => $func:ident($($ptype:ty)?) $(-> $rtype:ty)?),* $(,)?
+    } $($check:path)?) => {
+        fn invoke_method<BS, RT>(
+            rt: &mut RT,
+            method: $crate::dispatch::MethodNum,
+            #[allow(unused_variables)] params: &$crate::dispatch::RawBytes,
+        ) -> Result<$crate::dispatch::RawBytes, $crate::ActorError>
+        where
+            BS: $crate::dispatch::Blockstore,
+            RT: $crate::runtime::Runtime<BS>,
+        {
+            $($check(rt, method)?;)?
+            match <$method as $crate::dispatch::FromPrimitive>::from_u64(method) {
+                $(Some($method::$variant) => {
+                    let ret = Self::$func(
+                        rt $(, <$ptype as $crate::dispatch::MethodParams>::read(params)?)?
+                    )?;
+                    $crate::actor_dispatch!(@return ret $(, $rtype)?)
+                })*
+                None => Err($crate::actor_error!(unhandled_message; "Invalid method")),
+            }
+        }
+    };
+    (@return $ret:ident) => {{
+        let () = $ret;
+        Ok($crate::dispatch::RawBytes::default())
+    }};
+    (@return $ret:ident, $typ:ty) => {{
+        let ret: $typ = $ret;
+        Ok($crate::dispatch::RawBytes::serialize(ret)?)
+    }};
+}
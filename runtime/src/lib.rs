@@ -27,6 +27,8 @@ use crate::runtime::Runtime;
 
 pub mod actor_error;
 pub mod builtin;
+#[doc(hidden)]
+pub mod dispatch;
 pub mod runtime;
 pub mod util;
 
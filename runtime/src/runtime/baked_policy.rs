@@ -0,0 +1,26 @@
+//! The policy baked into the build. It lives apart from the policy definitions,
+//! which the build script includes to check the policy file against them.
+
+use super::Policy;
+
+/// Policy file given in the `BUILD_FIL_POLICY` environment variable, checked and
+/// encoded as CBOR by the build script. Empty if no file was given.
+const BUILD_POLICY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/policy.cbor"));
+
+lazy_static! {
+    /// The policy baked into the build, or the one selected by the runtime features if none was.
+    /// Decoded once, on first use.
+    pub(crate) static ref BAKED_POLICY: Policy = if BUILD_POLICY.is_empty() {
+        Policy::from_features()
+    } else {
+        // The build script has already decoded these bytes into a `Policy`.
+        fvm_ipld_encoding::from_slice(BUILD_POLICY).expect("invalid policy baked into the build")
+    };
+}
+
+impl Default for Policy {
+    /// A copy of the policy baked into the build.
+    fn default() -> Policy {
+        BAKED_POLICY.clone()
+    }
+}
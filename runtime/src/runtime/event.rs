@@ -0,0 +1,79 @@
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{Cbor, RawBytes};
+use serde::Serialize;
+
+use crate::util::cbor::serialize;
+use crate::ActorError;
+
+/// Key of the entry naming the type of an event.
+pub const EVENT_TYPE_KEY: &str = "$type";
+
+/// A structured event emitted by an actor, for indexers to follow its state
+/// transitions without diffing its state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ActorEvent {
+    pub entries: Vec<EventEntry>,
+}
+
+impl Cbor for ActorEvent {}
+
+impl ActorEvent {
+    /// Value of the entry with the given key, if any.
+    pub fn get(&self, key: &str) -> Option<&RawBytes> {
+        self.entries.iter().find(|e| e.key == key).map(|e| &e.value)
+    }
+}
+
+/// A key/value entry of an event, with the value encoded as CBOR.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct EventEntry {
+    pub key: String,
+    pub value: RawBytes,
+    /// Whether indexers should index the event by this entry.
+    pub indexed: bool,
+}
+
+/// Builds an [`ActorEvent`] from typed values, e.g.
+/// `EventBuilder::new().typ("fund").field_indexed("subnet", &id).build()?`.
+#[derive(Default)]
+pub struct EventBuilder {
+    entries: Vec<EventEntry>,
+    error: Option<ActorError>,
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the type of the event, which is always indexed.
+    pub fn typ(self, name: &str) -> Self {
+        self.push(EVENT_TYPE_KEY, &name, true)
+    }
+
+    pub fn field<T: Serialize + ?Sized>(self, key: &str, value: &T) -> Self {
+        self.push(key, value, false)
+    }
+
+    pub fn field_indexed<T: Serialize + ?Sized>(self, key: &str, value: &T) -> Self {
+        self.push(key, value, true)
+    }
+
+    /// Returns the event, or the error serializing the first value that failed.
+    pub fn build(self) -> Result<ActorEvent, ActorError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(ActorEvent { entries: self.entries }),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(mut self, key: &str, value: &T, indexed: bool) -> Self {
+        if self.error.is_none() {
+            match serialize(value, &format!("event entry {}", key)) {
+                Ok(value) => self.entries.push(EventEntry { key: key.to_owned(), value, indexed }),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self
+    }
+}
@@ -23,9 +23,10 @@ use fvm_shared::{ActorID, MethodNum};
 use sha2::{Digest, Sha256};
 
 use crate::runtime::actor_blockstore::ActorBlockstore;
+use crate::runtime::baked_policy::BAKED_POLICY;
 use crate::runtime::{
-    ActorCode, ConsensusFault, DomainSeparationTag, MessageInfo, Policy, Primitives, RuntimePolicy,
-    Verifier,
+    ActorCode, ActorEvent, ConsensusFault, DomainSeparationTag, MessageInfo, Policy, Primitives,
+    RuntimePolicy, Verifier,
 };
 use crate::{actor_error, ActorError, Runtime};
 
@@ -46,7 +47,7 @@ pub struct FvmRuntime<B = ActorBlockstore> {
     /// Indicates that the caller has been validated.
     caller_validated: bool,
     /// The runtime policy
-    policy: Policy,
+    policy: &'static Policy,
 }
 
 impl Default for FvmRuntime {
@@ -55,7 +56,7 @@ impl Default for FvmRuntime {
             blockstore: ActorBlockstore,
             in_transaction: false,
             caller_validated: false,
-            policy: Policy::default(),
+            policy: &BAKED_POLICY,
         }
     }
 }
@@ -70,11 +71,6 @@ impl<B> FvmRuntime<B> {
         }
         Ok(())
     }
-
-    #[allow(dead_code)]
-    fn policy_mut(&mut self) -> &mut Policy {
-        &mut self.policy
-    }
 }
 
 /// A stub MessageInfo implementation performing FVM syscalls to obtain its fields.
@@ -379,6 +375,20 @@ where
     fn base_fee(&self) -> TokenAmount {
         fvm::network::base_fee()
     }
+
+    fn emit_event(&mut self, event: &ActorEvent) -> Result<(), ActorError> {
+        if self.in_transaction {
+            return Err(
+                actor_error!(assertion_failed; "events are not allowed during transaction"),
+            );
+        }
+        // The FVM doesn't record events yet, so they're dropped unless the
+        // `log-events` feature writes them to the debug log.
+        if cfg!(feature = "log-events") {
+            log::info!("actor {} emitted event {:?}", fvm::message::receiver(), event);
+        }
+        Ok(())
+    }
 }
 
 impl<B> Primitives for FvmRuntime<B>
@@ -531,7 +541,7 @@ where
     B: Blockstore,
 {
     fn policy(&self) -> &Policy {
-        &self.policy
+        self.policy
     }
 }
 
@@ -20,11 +20,14 @@ use fvm_shared::version::NetworkVersion;
 use fvm_shared::{ActorID, MethodNum};
 
 pub use self::actor_code::*;
+pub use self::event::*;
 pub use self::policy::*;
 pub use self::randomness::DomainSeparationTag;
 use crate::ActorError;
 
 mod actor_code;
+mod baked_policy;
+mod event;
 
 #[cfg(feature = "fil-actor")]
 pub mod fvm;
@@ -162,6 +165,10 @@ pub trait Runtime<BS: Blockstore>: Primitives + Verifier + RuntimePolicy {
     fn charge_gas(&mut self, name: &'static str, compute: i64);
 
     fn base_fee(&self) -> TokenAmount;
+
+    /// Emits an event, recorded in the receipt of the message being executed
+    /// for indexers. Events of calls that abort are dropped.
+    fn emit_event(&mut self, event: &ActorEvent) -> Result<(), ActorError>;
 }
 
 /// Message information available to the actor about executing message.
@@ -1,8 +1,116 @@
 use std::collections::HashSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::anyhow;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
 use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+/// Runtime features enabled for each network, when built for it with `BUILD_FIL_NETWORK`.
+pub const NETWORKS: &[(&str, &[&str])] = &[
+    ("mainnet", &["sector-32g", "sector-64g"]),
+    (
+        "caterpillarnet",
+        &[
+            "sector-512m",
+            "sector-32g",
+            "sector-64g",
+            "small-deals",
+            "short-precommit",
+            "min-power-2k",
+        ],
+    ),
+    ("butterflynet", &["sector-512m", "sector-32g", "sector-64g", "min-power-2g"]),
+    ("calibrationnet", &["sector-32g", "sector-64g", "min-power-32g"]),
+    ("devnet", &["sector-2k", "sector-8m", "small-deals", "short-precommit", "min-power-2k"]),
+    (
+        "testing",
+        &[
+            "sector-2k",
+            "sector-8m",
+            "sector-512m",
+            "sector-32g",
+            "sector-64g",
+            "small-deals",
+            "short-precommit",
+            "min-power-2k",
+            "no-provider-deal-collateral",
+        ],
+    ),
+    (
+        "testing-fake-proofs",
+        &[
+            "sector-2k",
+            "sector-8m",
+            "sector-512m",
+            "sector-32g",
+            "sector-64g",
+            "small-deals",
+            "short-precommit",
+            "min-power-2k",
+            "no-provider-deal-collateral",
+            "fake-proofs",
+        ],
+    ),
+];
+
+/// Runtime features selecting policy values this crate is built with.
+const ENABLED_FEATURES: &[&str] = &[
+    #[cfg(feature = "sector-2k")]
+    "sector-2k",
+    #[cfg(feature = "sector-8m")]
+    "sector-8m",
+    #[cfg(feature = "sector-512m")]
+    "sector-512m",
+    #[cfg(feature = "sector-32g")]
+    "sector-32g",
+    #[cfg(feature = "sector-64g")]
+    "sector-64g",
+    #[cfg(feature = "small-deals")]
+    "small-deals",
+    #[cfg(feature = "short-precommit")]
+    "short-precommit",
+    #[cfg(feature = "min-power-2k")]
+    "min-power-2k",
+    #[cfg(feature = "min-power-2g")]
+    "min-power-2g",
+    #[cfg(feature = "min-power-32g")]
+    "min-power-32g",
+    #[cfg(feature = "no-provider-deal-collateral")]
+    "no-provider-deal-collateral",
+];
+
+/// Proof types enabled by each sector size feature.
+const SECTOR_FEATURES: &[(&str, RegisteredPoStProof, RegisteredSealProof)] = &[
+    (
+        "sector-2k",
+        RegisteredPoStProof::StackedDRGWindow2KiBV1,
+        RegisteredSealProof::StackedDRG2KiBV1P1,
+    ),
+    (
+        "sector-8m",
+        RegisteredPoStProof::StackedDRGWindow8MiBV1,
+        RegisteredSealProof::StackedDRG8MiBV1P1,
+    ),
+    (
+        "sector-512m",
+        RegisteredPoStProof::StackedDRGWindow512MiBV1,
+        RegisteredSealProof::StackedDRG512MiBV1P1,
+    ),
+    (
+        "sector-32g",
+        RegisteredPoStProof::StackedDRGWindow32GiBV1,
+        RegisteredSealProof::StackedDRG32GiBV1P1,
+    ),
+    (
+        "sector-64g",
+        RegisteredPoStProof::StackedDRGWindow64GiBV1,
+        RegisteredSealProof::StackedDRG64GiBV1P1,
+    ),
+];
 
 // A trait for runtime policy configuration
 pub trait RuntimePolicy {
@@ -10,6 +118,11 @@ pub trait RuntimePolicy {
 }
 
 // The policy itself
+//
+// Policies can be (de)serialised, so networks can adjust them without recompiling.
+// Fields missing when deserialising keep the values selected by the runtime features.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default = "Policy::from_features", deny_unknown_fields)]
 pub struct Policy {
     /// Maximum amount of sectors that can be aggregated.
     pub max_aggregated_sectors: u64,
@@ -134,6 +247,7 @@ pub struct Policy {
 
     // --- verifreg policy
     /// Minimum verified deal size
+    #[serde(with = "storage_power_ser")]
     pub minimum_verified_deal_size: StoragePower,
 
     //  --- market policy ---
@@ -150,11 +264,35 @@ pub struct Policy {
 
     // --- power ---
     /// Minimum miner consensus power
+    #[serde(with = "storage_power_ser")]
     pub minimum_consensus_power: StoragePower,
 }
 
-impl Default for Policy {
-    fn default() -> Policy {
+impl Policy {
+    /// The policy selected by the runtime features.
+    pub fn from_features() -> Policy {
+        Policy::for_features(ENABLED_FEATURES)
+    }
+
+    /// The policy of a network, as selected by the runtime features it is built with.
+    pub fn for_network(network: &str) -> Option<Policy> {
+        NETWORKS.iter().find(|(name, _)| *name == network).map(|(_, f)| Policy::for_features(f))
+    }
+
+    /// The policy selected by runtime features, whether or not this crate is built with them.
+    pub fn for_features(features: &[&str]) -> Policy {
+        let enabled = |feature: &str| features.contains(&feature);
+        let sectors = || SECTOR_FEATURES.iter().filter(|(feature, _, _)| enabled(*feature));
+        let minimum_consensus_power = if enabled("min-power-2k") {
+            policy_constants::MIN_POWER_2K_MINIMUM_CONSENSUS_POWER
+        } else if enabled("min-power-2g") {
+            policy_constants::MIN_POWER_2G_MINIMUM_CONSENSUS_POWER
+        } else if enabled("min-power-32g") {
+            policy_constants::MIN_POWER_32G_MINIMUM_CONSENSUS_POWER
+        } else {
+            policy_constants::DEFAULT_MINIMUM_CONSENSUS_POWER
+        };
+
         Policy {
             max_aggregated_sectors: policy_constants::MAX_AGGREGATED_SECTORS,
             min_aggregated_sectors: policy_constants::MIN_AGGREGATED_SECTORS,
@@ -178,7 +316,11 @@ impl Default for Policy {
             addressed_sectors_max: policy_constants::ADDRESSED_SECTORS_MAX,
             max_pre_commit_randomness_lookback:
                 policy_constants::MAX_PRE_COMMIT_RANDOMNESS_LOOKBACK,
-            pre_commit_challenge_delay: policy_constants::PRE_COMMIT_CHALLENGE_DELAY,
+            pre_commit_challenge_delay: if enabled("short-precommit") {
+                policy_constants::SHORT_PRE_COMMIT_CHALLENGE_DELAY
+            } else {
+                policy_constants::DEFAULT_PRE_COMMIT_CHALLENGE_DELAY
+            },
             wpost_challenge_lookback: policy_constants::WPOST_CHALLENGE_LOOKBACK,
             fault_declaration_cutoff: policy_constants::FAULT_DECLARATION_CUTOFF,
             fault_max_age: policy_constants::FAULT_MAX_AGE,
@@ -191,47 +333,102 @@ impl Default for Policy {
             new_sectors_per_period_max: policy_constants::NEW_SECTORS_PER_PERIOD_MAX,
             chain_finality: policy_constants::CHAIN_FINALITY,
 
-            valid_post_proof_type: HashSet::<RegisteredPoStProof>::from([
-                #[cfg(feature = "sector-2k")]
-                RegisteredPoStProof::StackedDRGWindow2KiBV1,
-                #[cfg(feature = "sector-8m")]
-                RegisteredPoStProof::StackedDRGWindow8MiBV1,
-                #[cfg(feature = "sector-512m")]
-                RegisteredPoStProof::StackedDRGWindow512MiBV1,
-                #[cfg(feature = "sector-32g")]
-                RegisteredPoStProof::StackedDRGWindow32GiBV1,
-                #[cfg(feature = "sector-64g")]
-                RegisteredPoStProof::StackedDRGWindow64GiBV1,
-            ]),
-            valid_pre_commit_proof_type: HashSet::<RegisteredSealProof>::from([
-                #[cfg(feature = "sector-2k")]
-                RegisteredSealProof::StackedDRG2KiBV1P1,
-                #[cfg(feature = "sector-8m")]
-                RegisteredSealProof::StackedDRG8MiBV1P1,
-                #[cfg(feature = "sector-512m")]
-                RegisteredSealProof::StackedDRG512MiBV1P1,
-                #[cfg(feature = "sector-32g")]
-                RegisteredSealProof::StackedDRG32GiBV1P1,
-                #[cfg(feature = "sector-64g")]
-                RegisteredSealProof::StackedDRG64GiBV1P1,
-            ]),
-
-            minimum_verified_deal_size: StoragePower::from_i32(
-                policy_constants::MINIMUM_VERIFIED_DEAL_SIZE,
-            )
+            valid_post_proof_type: sectors().map(|(_, post, _)| *post).collect(),
+            valid_pre_commit_proof_type: sectors().map(|(_, _, seal)| *seal).collect(),
+
+            minimum_verified_deal_size: StoragePower::from_i32(if enabled("small-deals") {
+                policy_constants::SMALL_DEALS_MINIMUM_VERIFIED_DEAL_SIZE
+            } else {
+                policy_constants::DEFAULT_MINIMUM_VERIFIED_DEAL_SIZE
+            })
             .unwrap(),
 
             deal_updates_interval: policy_constants::DEAL_UPDATES_INTERVAL,
-            prov_collateral_percent_supply_num:
-                policy_constants::PROV_COLLATERAL_PERCENT_SUPPLY_NUM,
+            prov_collateral_percent_supply_num: if enabled("no-provider-deal-collateral") {
+                0
+            } else {
+                policy_constants::DEFAULT_PROV_COLLATERAL_PERCENT_SUPPLY_NUM
+            },
             prov_collateral_percent_supply_denom:
                 policy_constants::PROV_COLLATERAL_PERCENT_SUPPLY_DENOM,
 
-            minimum_consensus_power: StoragePower::from(policy_constants::MINIMUM_CONSENSUS_POWER),
+            minimum_consensus_power: StoragePower::from(minimum_consensus_power),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Policy {
+    /// Parses a policy from JSON.
+    pub fn from_json(s: &str) -> anyhow::Result<Policy> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Parses a policy from TOML.
+    pub fn from_toml(s: &str) -> anyhow::Result<Policy> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Loads a policy from a `.json` or `.toml` file.
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Policy> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Policy::from_json(&content),
+            Some("toml") => Policy::from_toml(&content),
+            _ => {
+                Err(anyhow!("unknown policy format of {}, expected .json or .toml", path.display()))
+            }
         }
     }
 }
 
+/// Serialises storage power as a decimal string, so that large values fit in
+/// JSON and TOML numbers. Plain integers are accepted too when deserialising.
+mod storage_power_ser {
+    use std::fmt;
+    use std::str::FromStr;
+
+    use fvm_shared::sector::StoragePower;
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(power: &StoragePower, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&power.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<StoragePower, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PowerVisitor;
+
+        impl<'de> de::Visitor<'de> for PowerVisitor {
+            type Value = StoragePower;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an integer or a decimal string")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<StoragePower, E> {
+                Ok(StoragePower::from(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<StoragePower, E> {
+                Ok(StoragePower::from(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<StoragePower, E> {
+                StoragePower::from_str(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(PowerVisitor)
+    }
+}
+
 pub mod policy_constants {
     use crate::builtin::*;
     use fvm_shared::clock::ChainEpoch;
@@ -308,9 +505,12 @@ pub mod policy_constants {
     /// Number of epochs between publishing the precommit and when the challenge for interactive PoRep is drawn
     /// used to ensure it is not predictable by miner.
     #[cfg(not(feature = "short-precommit"))]
-    pub const PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = 150;
+    pub const PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = DEFAULT_PRE_COMMIT_CHALLENGE_DELAY;
     #[cfg(feature = "short-precommit")]
-    pub const PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = 10;
+    pub const PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = SHORT_PRE_COMMIT_CHALLENGE_DELAY;
+    pub const DEFAULT_PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = 150;
+    /// Pre-commit challenge delay with the `short-precommit` feature.
+    pub const SHORT_PRE_COMMIT_CHALLENGE_DELAY: ChainEpoch = 10;
 
     /// Lookback from the deadline's challenge window opening from which to sample chain randomness for the challenge seed.
 
@@ -358,9 +558,12 @@ pub mod policy_constants {
     pub const CHAIN_FINALITY: ChainEpoch = 900;
 
     #[cfg(not(feature = "small-deals"))]
-    pub const MINIMUM_VERIFIED_DEAL_SIZE: i32 = 1 << 20;
+    pub const MINIMUM_VERIFIED_DEAL_SIZE: i32 = DEFAULT_MINIMUM_VERIFIED_DEAL_SIZE;
     #[cfg(feature = "small-deals")]
-    pub const MINIMUM_VERIFIED_DEAL_SIZE: i32 = 256;
+    pub const MINIMUM_VERIFIED_DEAL_SIZE: i32 = SMALL_DEALS_MINIMUM_VERIFIED_DEAL_SIZE;
+    pub const DEFAULT_MINIMUM_VERIFIED_DEAL_SIZE: i32 = 1 << 20;
+    /// Minimum verified deal size with the `small-deals` feature.
+    pub const SMALL_DEALS_MINIMUM_VERIFIED_DEAL_SIZE: i32 = 256;
 
     /// DealUpdatesInterval is the number of blocks between payouts for deals
     pub const DEAL_UPDATES_INTERVAL: i64 = EPOCHS_IN_DAY;
@@ -368,24 +571,32 @@ pub mod policy_constants {
     /// Numerator of the percentage of normalized cirulating
     /// supply that must be covered by provider collateral
     #[cfg(not(feature = "no-provider-deal-collateral"))]
-    pub const PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = 1;
+    pub const PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = DEFAULT_PROV_COLLATERAL_PERCENT_SUPPLY_NUM;
     #[cfg(feature = "no-provider-deal-collateral")]
     pub const PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = 0;
+    pub const DEFAULT_PROV_COLLATERAL_PERCENT_SUPPLY_NUM: i64 = 1;
 
     /// Denominator of the percentage of normalized cirulating
     /// supply that must be covered by provider collateral
     pub const PROV_COLLATERAL_PERCENT_SUPPLY_DENOM: i64 = 100;
 
     #[cfg(feature = "min-power-2k")]
-    pub const MINIMUM_CONSENSUS_POWER: i64 = 2 << 10;
+    pub const MINIMUM_CONSENSUS_POWER: i64 = MIN_POWER_2K_MINIMUM_CONSENSUS_POWER;
     #[cfg(feature = "min-power-2g")]
-    pub const MINIMUM_CONSENSUS_POWER: i64 = 2 << 30;
+    pub const MINIMUM_CONSENSUS_POWER: i64 = MIN_POWER_2G_MINIMUM_CONSENSUS_POWER;
     #[cfg(feature = "min-power-32g")]
-    pub const MINIMUM_CONSENSUS_POWER: i64 = 32 << 30;
+    pub const MINIMUM_CONSENSUS_POWER: i64 = MIN_POWER_32G_MINIMUM_CONSENSUS_POWER;
     #[cfg(not(any(
         feature = "min-power-2k",
         feature = "min-power-2g",
         feature = "min-power-32g"
     )))]
-    pub const MINIMUM_CONSENSUS_POWER: i64 = 10 << 40;
+    pub const MINIMUM_CONSENSUS_POWER: i64 = DEFAULT_MINIMUM_CONSENSUS_POWER;
+    pub const DEFAULT_MINIMUM_CONSENSUS_POWER: i64 = 10 << 40;
+    /// Minimum consensus power with the `min-power-2k` feature.
+    pub const MIN_POWER_2K_MINIMUM_CONSENSUS_POWER: i64 = 2 << 10;
+    /// Minimum consensus power with the `min-power-2g` feature.
+    pub const MIN_POWER_2G_MINIMUM_CONSENSUS_POWER: i64 = 2 << 30;
+    /// Minimum consensus power with the `min-power-32g` feature.
+    pub const MIN_POWER_32G_MINIMUM_CONSENSUS_POWER: i64 = 32 << 30;
 }
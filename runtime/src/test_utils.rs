@@ -35,8 +35,8 @@ use multihash::MultihashDigest;
 use rand::prelude::*;
 
 use crate::runtime::{
-    ActorCode, DomainSeparationTag, MessageInfo, Policy, Primitives, Runtime, RuntimePolicy,
-    Verifier,
+    ActorCode, ActorEvent, DomainSeparationTag, MessageInfo, Policy, Primitives, Runtime,
+    RuntimePolicy, Verifier,
 };
 use crate::{actor_error, ActorError};
 
@@ -157,6 +157,7 @@ pub struct Expectations {
     pub expect_aggregate_verify_seals: Option<ExpectAggregateVerifySeals>,
     pub expect_replica_verify: Option<ExpectReplicaVerify>,
     pub expect_gas_charge: VecDeque<i64>,
+    pub expect_emitted_events: VecDeque<ActorEvent>,
 }
 
 impl Expectations {
@@ -246,6 +247,11 @@ impl Expectations {
             "expect_gas_charge {:?}, not received",
             self.expect_gas_charge
         );
+        assert!(
+            self.expect_emitted_events.is_empty(),
+            "expect_emitted_events {:?}, not received",
+            self.expect_emitted_events
+        );
     }
 }
 
@@ -654,6 +660,11 @@ impl MockRuntime {
         self.expectations.borrow_mut().expect_gas_charge.push_back(value);
     }
 
+    #[allow(dead_code)]
+    pub fn expect_emitted_event(&mut self, event: ActorEvent) {
+        self.expectations.borrow_mut().expect_emitted_events.push_back(event);
+    }
+
     ///// Private helpers /////
 
     fn require_in_call(&self) {
@@ -1019,6 +1030,18 @@ impl Runtime<MemoryBlockstore> for MockRuntime {
     fn base_fee(&self) -> TokenAmount {
         self.base_fee.clone()
     }
+
+    fn emit_event(&mut self, event: &ActorEvent) -> Result<(), ActorError> {
+        self.require_in_call();
+        if self.in_transaction {
+            return Err(actor_error!(assertion_failed; "side-effect within transaction"));
+        }
+        let mut exs = self.expectations.borrow_mut();
+        assert!(!exs.expect_emitted_events.is_empty(), "unexpected event {:?}", event);
+        let expected = exs.expect_emitted_events.pop_front().unwrap();
+        assert_eq!(&expected, event, "expected event {:?}, actual {:?}", expected, event);
+        Ok(())
+    }
 }
 
 impl Primitives for MockRuntime {
@@ -0,0 +1,186 @@
+//! FRC-0042 method numbers, derived from the name of the method so that actors
+//! exposing the same interface share their numbers.
+//!
+//! The number is the first 4-byte chunk, read as big-endian, of the Blake2b-512
+//! digest of `1|<name>` that isn't below [`FIRST_EXPORTED_METHOD_NUMBER`]. The
+//! hash is computed by a `const fn`, so numbers can be used in `Method` enums.
+
+use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
+
+/// Method numbers below this one are reserved to builtin actors.
+pub const FIRST_EXPORTED_METHOD_NUMBER: MethodNum = 1 << 24;
+
+/// Computes the FRC-0042 method number of a method name at compile time, e.g.
+/// `AddBalanceExported = method_hash!("AddBalance")`.
+#[macro_export]
+macro_rules! method_hash {
+    ($name:literal) => {
+        $crate::util::method_hash::method_number($name)
+    };
+}
+
+/// FRC-0042 method number of `name`, which must start with an uppercase letter
+/// followed by letters, digits and underscores. `Constructor` is method 1.
+pub const fn method_number(name: &str) -> MethodNum {
+    let name = name.as_bytes();
+    if name.is_empty() || !name[0].is_ascii_uppercase() {
+        panic!("method names must start with an uppercase letter");
+    }
+    let mut i = 1;
+    while i < name.len() {
+        if !name[i].is_ascii_alphanumeric() && name[i] != b'_' {
+            panic!("method names may only contain letters, digits and underscores");
+        }
+        i += 1;
+    }
+    if bytes_eq(name, b"Constructor") {
+        return METHOD_CONSTRUCTOR;
+    }
+
+    let digest = blake2b_512(b"1|", name);
+    let mut i = 0;
+    while i < digest.len() {
+        let num = (digest[i] as u64) << 24
+            | (digest[i + 1] as u64) << 16
+            | (digest[i + 2] as u64) << 8
+            | digest[i + 3] as u64;
+        if num >= FIRST_EXPORTED_METHOD_NUMBER {
+            return num;
+        }
+        i += 4;
+    }
+    panic!("no method number for name");
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLOCK_LEN: usize = 128;
+
+/// Unkeyed Blake2b-512 digest of `prefix` followed by `data`.
+const fn blake2b_512(prefix: &[u8], data: &[u8]) -> [u8; 64] {
+    let len = prefix.len() + data.len();
+    let mut h = IV;
+    // parameter block: 64 bytes digest, no key, fanout and depth of 1
+    h[0] ^= 0x01010040;
+    let mut offset = 0;
+    while len - offset > BLOCK_LEN {
+        h = compress(h, prefix, data, offset, (offset + BLOCK_LEN) as u128, false);
+        offset += BLOCK_LEN;
+    }
+    h = compress(h, prefix, data, offset, len as u128, true);
+
+    let mut digest = [0; 64];
+    let mut i = 0;
+    while i < digest.len() {
+        digest[i] = (h[i / 8] >> (8 * (i % 8))) as u8;
+        i += 1;
+    }
+    digest
+}
+
+/// Compresses the block at `offset`, zero-padded, with `count` bytes hashed so far.
+const fn compress(
+    mut h: [u64; 8],
+    prefix: &[u8],
+    data: &[u8],
+    offset: usize,
+    count: u128,
+    last: bool,
+) -> [u64; 8] {
+    let mut m = [0u64; 16];
+    let mut i = 0;
+    while i < BLOCK_LEN {
+        let pos = offset + i;
+        let byte = if pos < prefix.len() {
+            prefix[pos]
+        } else if pos - prefix.len() < data.len() {
+            data[pos - prefix.len()]
+        } else {
+            0
+        };
+        m[i / 8] |= (byte as u64) << (8 * (i % 8));
+        i += 1;
+    }
+
+    let mut v = [0u64; 16];
+    let mut i = 0;
+    while i < 8 {
+        v[i] = h[i];
+        v[i + 8] = IV[i];
+        i += 1;
+    }
+    v[12] ^= count as u64;
+    v[13] ^= (count >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    let mut round = 0;
+    while round < 12 {
+        let s = SIGMA[round % 10];
+        v = mix(v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        v = mix(v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        v = mix(v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        v = mix(v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        v = mix(v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        v = mix(v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        v = mix(v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        v = mix(v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+        round += 1;
+    }
+
+    let mut i = 0;
+    while i < 8 {
+        h[i] ^= v[i] ^ v[i + 8];
+        i += 1;
+    }
+    h
+}
+
+const fn mix(mut v: [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) -> [u64; 16] {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+    v
+}
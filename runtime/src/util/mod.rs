@@ -3,6 +3,7 @@
 
 pub use self::downcast::*;
 pub use self::message_accumulator::MessageAccumulator;
+pub use self::method_hash::{method_number, FIRST_EXPORTED_METHOD_NUMBER};
 pub use self::multimap::*;
 pub use self::set::Set;
 pub use self::set_multimap::SetMultimap;
@@ -11,6 +12,7 @@ pub mod cbor;
 pub mod chaos;
 mod downcast;
 mod message_accumulator;
+pub mod method_hash;
 mod multimap;
 mod set;
 mod set_multimap;
@@ -0,0 +1,22 @@
+use fil_actors_runtime::{method_hash, FIRST_EXPORTED_METHOD_NUMBER};
+use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
+
+#[test]
+fn frc42_method_numbers() {
+    // numbers from the FRC-0042 reference implementation
+    assert_eq!(method_hash!("AddBalance"), 822473126);
+    assert_eq!(method_hash!("PublishStorageDeals"), 2236929350);
+    assert_eq!(method_hash!("AuthenticateMessage"), 2643134072);
+    assert_eq!(method_hash!("Propose"), 1696838335);
+    assert_eq!(method_hash!("Constructor"), METHOD_CONSTRUCTOR);
+}
+
+#[test]
+fn long_names() {
+    // names spanning several blocks of the hash
+    const NUM: MethodNum = method_hash!(
+        "A_very_long_method_name_that_doesnt_fit_in_a_single_128_byte_block_of_the_blake2b_hash_function_used_by_frc42_method_numbers_at_all"
+    );
+    assert_eq!(NUM, 57587197);
+    assert!(NUM >= FIRST_EXPORTED_METHOD_NUMBER);
+}
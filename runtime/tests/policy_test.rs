@@ -0,0 +1,49 @@
+use fil_actors_runtime::runtime::Policy;
+use fvm_shared::sector::{RegisteredPoStProof, StoragePower};
+
+#[test]
+fn missing_fields_keep_defaults() {
+    let policy = Policy::from_json(r#"{ "pre_commit_challenge_delay": 10 }"#).unwrap();
+    assert_eq!(policy.pre_commit_challenge_delay, 10);
+    assert_eq!(policy, Policy { pre_commit_challenge_delay: 10, ..Policy::from_features() });
+
+    let policy = Policy::from_toml("").unwrap();
+    assert_eq!(policy, Policy::from_features());
+}
+
+#[test]
+fn json_and_toml() {
+    let json = r#"{
+        "minimum_consensus_power": "2048",
+        "minimum_verified_deal_size": 256,
+        "valid_post_proof_type": [5]
+    }"#;
+    let toml = r#"
+        minimum_consensus_power = 2048
+        minimum_verified_deal_size = "256"
+        valid_post_proof_type = [5]
+    "#;
+    for policy in [Policy::from_json(json).unwrap(), Policy::from_toml(toml).unwrap()] {
+        assert_eq!(policy.minimum_consensus_power, StoragePower::from(2048));
+        assert_eq!(policy.minimum_verified_deal_size, StoragePower::from(256));
+        assert_eq!(
+            policy.valid_post_proof_type.into_iter().collect::<Vec<_>>(),
+            vec![RegisteredPoStProof::StackedDRGWindow2KiBV1]
+        );
+    }
+}
+
+#[test]
+fn round_trip() {
+    let policy = Policy { chain_finality: 20, ..Policy::from_features() };
+    let json = serde_json::to_string(&policy).unwrap();
+    assert_eq!(Policy::from_json(&json).unwrap(), policy);
+    let toml = toml::to_string(&policy).unwrap();
+    assert_eq!(Policy::from_toml(&toml).unwrap(), policy);
+}
+
+#[test]
+fn unknown_fields() {
+    assert!(Policy::from_json(r#"{ "pre_commit_challenge_dely": 10 }"#).is_err());
+    assert!(Policy::from_file("policy.yaml").is_err());
+}
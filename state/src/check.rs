@@ -5,6 +5,7 @@ use anyhow::bail;
 use cid::Cid;
 use fil_actor_account::State as AccountState;
 use fil_actor_cron::State as CronState;
+use fil_actor_hierarchical_sca::State as SCAState;
 use fil_actor_init::State as InitState;
 use fil_actor_market::State as MarketState;
 use fil_actor_miner::CronEventPayload;
@@ -40,6 +41,7 @@ use fvm_shared::bigint::bigint_ser;
 
 use fil_actor_account::testing as account;
 use fil_actor_cron::testing as cron;
+use fil_actor_hierarchical_sca::testing as sca;
 use fil_actor_init::testing as init;
 use fil_actor_market::testing as market;
 use fil_actor_miner::testing as miner;
@@ -202,8 +204,13 @@ pub fn check_state_invariants<'a, BS: Blockstore + Debug>(
                 acc.with_prefix("verifreg: ").add_all(&msgs);
                 verifreg_summary = Some(summary);
             }
-            Some(Type::SCA) => todo!(),
-            Some(Type::Subnet) => todo!(),
+            Some(Type::SCA) => {
+                let state = get_state!(tree, actor, SCAState);
+                let (_, msgs) = sca::check_state_invariants(&state, tree.store, &actor.balance);
+                acc.with_prefix("sca: ").add_all(&msgs);
+            }
+            // subnet actors are user-defined, so their state can't be checked here.
+            Some(Type::Subnet) => (),
             None => {
                 bail!("unexpected actor code CID {} for address {}", actor.code, key);
             }
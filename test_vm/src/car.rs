@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Write};
+
+use anyhow::anyhow;
+use cid::multihash::Code;
+use cid::Cid;
+use fil_actor_system::State as SystemState;
+use fil_actors_runtime::test_utils::{ACTOR_CODES, ACTOR_TYPES};
+use fil_actors_runtime::HAMT_BIT_WIDTH;
+use futures::executor::block_on;
+use futures::io::AllowStdIo;
+use futures::stream;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+use fvm_ipld_car::{load_car, CarHeader};
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{CborStore, DAG_CBOR};
+use fvm_ipld_hamt::{BytesKey, Hamt, Sha256};
+use fvm_shared::actor::builtin::Type;
+use fvm_shared::address::Address;
+
+use crate::{Actor, SYSTEM_ACTOR_ADDR, VM};
+
+const IPLD_RAW: u64 = 0x55;
+
+/// Names of the builtin actors, as found in the registry of the system
+/// actor and in the legacy code CIDs of actors before v8.
+pub(crate) const BUILTIN_ACTORS: &[(&str, Type)] = &[
+    ("system", Type::System),
+    ("init", Type::Init),
+    ("cron", Type::Cron),
+    ("account", Type::Account),
+    ("multisig", Type::Multisig),
+    ("storagepower", Type::Power),
+    ("storageminer", Type::Miner),
+    ("storagemarket", Type::Market),
+    ("paymentchannel", Type::PaymentChannel),
+    ("reward", Type::Reward),
+    ("verifiedregistry", Type::VerifiedRegistry),
+    ("hierarchical_sca", Type::SCA),
+];
+
+/// Versioned root of a state tree, as found in chain snapshots.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+struct StateRoot {
+    version: u64,
+    actors: Cid,
+    info: Cid,
+}
+
+impl<'bs> VM<'bs> {
+    /// Loads a CAR into `store` and creates a VM on the state tree at its root,
+    /// e.g. the parent state of a tipset of a devnet or subnet.
+    ///
+    /// The root can be either a versioned state root or the actors HAMT, and is
+    /// exported the same way. The code CIDs of the builtin actors in the snapshot
+    /// are swapped for the test ones so the VM can run them, and are restored when
+    /// exporting the state tree.
+    pub fn load_car(
+        store: &'bs MemoryBlockstore,
+        reader: impl Read + Send + Unpin,
+    ) -> anyhow::Result<VM<'bs>> {
+        let roots = block_on(load_car(store, AllowStdIo::new(reader)))?;
+        let root = match roots.as_slice() {
+            [root] => *root,
+            _ => return Err(anyhow!("expected a single root in CAR, found {}", roots.len())),
+        };
+        let (actors_root, snapshot_root) = match store.get_cbor::<StateRoot>(&root) {
+            Ok(Some(state_root)) => {
+                (state_root.actors, Some((state_root.version, state_root.info)))
+            }
+            _ => (root, None),
+        };
+
+        // snapshots of the FVM use a different bit width than the actors HAMT of the
+        // VM, so the tree is copied into a VM one.
+        let snapshot = Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::load_with_bit_width(
+            &actors_root,
+            store,
+            HAMT_BIT_WIDTH,
+        )?;
+        let registry = builtin_actors(store, &snapshot)?;
+        let mut actors = Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::new(store);
+        let mut codes = HashMap::new();
+        snapshot.for_each(|key, actor| {
+            if ACTOR_TYPES.contains_key(&actor.code) {
+                actors.set(key.clone(), actor.clone())?;
+                return Ok(());
+            }
+            let code = registry
+                .get(&actor.code)
+                .cloned()
+                .or_else(|| legacy_name(&actor.code))
+                .and_then(|name| BUILTIN_ACTORS.iter().find(|(n, _)| *n == name))
+                .and_then(|(_, typ)| ACTOR_CODES.get(typ))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown code {} of actor {:?}",
+                        actor.code,
+                        Address::from_bytes(&key.0)
+                    )
+                })?;
+            codes.insert(*code, actor.code);
+            actors.set(key.clone(), Actor { code: *code, ..actor.clone() })?;
+            Ok(())
+        })?;
+
+        let v = VM::new(store);
+        v.rollback(actors.flush()?);
+        Ok(VM { snapshot_codes: codes, snapshot_root, ..v })
+    }
+
+    /// Exports the state tree of the VM as a CAR, with the blocks reachable from
+    /// its root only. Returns the root.
+    ///
+    /// The actors HAMT has the bit width of the FVM, and the code CIDs of the
+    /// snapshot the VM was loaded from, so the CAR can be loaded by nodes. It is
+    /// wrapped in a state root with the version and info of the snapshot, if it
+    /// was loaded from a versioned one.
+    pub fn export_car(&self, writer: impl Write + Send + Unpin) -> anyhow::Result<Cid> {
+        let actors = self.snapshot_tree(self.checkpoint())?;
+        let root = match self.snapshot_root {
+            Some((version, info)) => {
+                self.store.put_cbor(&StateRoot { version, actors, info }, Code::Blake2b256)?
+            }
+            None => actors,
+        };
+        let blocks = reachable_blocks(self.store, root)?;
+        let header = CarHeader::new(vec![root], 1);
+        block_on(
+            header.write_stream_async(&mut AllowStdIo::new(writer), &mut stream::iter(blocks)),
+        )?;
+        Ok(root)
+    }
+
+    /// Copies the actors HAMT at `root` into one with the bit width of the FVM,
+    /// and the code CIDs of the snapshot the VM was loaded from.
+    fn snapshot_tree(&self, root: Cid) -> anyhow::Result<Cid> {
+        let actors =
+            Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::load(&root, self.store)?;
+        let mut snapshot =
+            Hamt::<&'bs MemoryBlockstore, Actor, BytesKey, Sha256>::new_with_bit_width(
+                self.store,
+                HAMT_BIT_WIDTH,
+            );
+        actors.for_each(|key, actor| {
+            let code = self.snapshot_codes.get(&actor.code).unwrap_or(&actor.code);
+            snapshot.set(key.clone(), Actor { code: *code, ..actor.clone() })?;
+            Ok(())
+        })?;
+        Ok(snapshot.flush()?)
+    }
+}
+
+/// Names of the builtin actors by code CID, from the registry of the system actor.
+/// The registry is empty, or missing in snapshots before v8.
+fn builtin_actors(
+    store: &MemoryBlockstore,
+    actors: &Hamt<&MemoryBlockstore, Actor, BytesKey, Sha256>,
+) -> anyhow::Result<HashMap<Cid, String>> {
+    let system = match actors.get(&SYSTEM_ACTOR_ADDR.to_bytes())? {
+        Some(system) => system,
+        None => return Ok(HashMap::new()),
+    };
+    let registry = match store.get_cbor::<SystemState>(&system.head) {
+        Ok(Some(st)) => st.get_builtin_actors(store).map_err(|e| anyhow!(e))?,
+        _ => Vec::new(),
+    };
+    Ok(registry.into_iter().map(|(name, code)| (code, name)).collect())
+}
+
+/// Name of the actor of a legacy code CID, i.e. an identity hash of `fil/<version>/<name>`.
+fn legacy_name(code: &Cid) -> Option<String> {
+    if code.hash().code() != 0 {
+        return None;
+    }
+    let path = std::str::from_utf8(code.hash().digest()).ok()?;
+    path.strip_prefix("fil/")?.split('/').nth(1).map(String::from)
+}
+
+/// Blocks reachable from `root`, following the links of DAG-CBOR blocks.
+///
+/// Raw blocks missing from the store are skipped, as the code of actors
+/// is referenced by the state tree but isn't part of it.
+fn reachable_blocks(store: &MemoryBlockstore, root: Cid) -> anyhow::Result<Vec<(Cid, Vec<u8>)>> {
+    let mut seen = HashSet::from([root]);
+    let mut queue = VecDeque::from([root]);
+    let mut blocks = Vec::new();
+    while let Some(cid) = queue.pop_front() {
+        let block = match store.get(&cid)? {
+            Some(block) => block,
+            None if cid.codec() == IPLD_RAW => continue,
+            None => return Err(anyhow!("missing block {}", cid)),
+        };
+        if cid.codec() == DAG_CBOR {
+            for link in scan_links(&block)? {
+                if seen.insert(link) {
+                    queue.push_back(link);
+                }
+            }
+        }
+        blocks.push((cid, block));
+    }
+    Ok(blocks)
+}
+
+/// Links of a DAG-CBOR block, i.e. the CIDs under tag 42.
+fn scan_links(mut data: &[u8]) -> anyhow::Result<Vec<Cid>> {
+    let mut links = Vec::new();
+    let mut items = 1u64;
+    while items > 0 {
+        items -= 1;
+        let (major, arg) = read_header(&mut data)?;
+        match major {
+            // integers, simple values and floats
+            0 | 1 | 7 => {}
+            // byte and text strings
+            2 | 3 => {
+                take(&mut data, arg)?;
+            }
+            4 => items = items.saturating_add(arg),
+            5 => items = items.saturating_add(arg.saturating_mul(2)),
+            6 if arg == 42 => links.push(read_link(&mut data)?),
+            6 => items += 1,
+            _ => unreachable!(),
+        }
+    }
+    Ok(links)
+}
+
+/// Reads the CID of a link, the byte string following tag 42.
+pub(crate) fn read_link(data: &mut &[u8]) -> anyhow::Result<Cid> {
+    let (major, len) = read_header(data)?;
+    let bytes = take(data, len)?;
+    // CIDs are prefixed by the identity multibase
+    if major != 2 || bytes.first() != Some(&0) {
+        return Err(anyhow!("invalid link"));
+    }
+    Cid::try_from(&bytes[1..]).map_err(|e| anyhow!("invalid link: {}", e))
+}
+
+/// Reads the major type and argument of the header of a CBOR item.
+pub(crate) fn read_header(data: &mut &[u8]) -> anyhow::Result<(u8, u64)> {
+    let first = take(data, 1)?[0];
+    let info = first & 0x1f;
+    let arg = match info {
+        0..=23 => info as u64,
+        24..=27 => take(data, 1 << (info - 24))?.iter().fold(0, |acc, b| acc << 8 | *b as u64),
+        // indefinite lengths aren't allowed in DAG-CBOR
+        _ => return Err(anyhow!("invalid CBOR item {:#x}", first)),
+    };
+    Ok((first >> 5, arg))
+}
+
+pub(crate) fn take<'a>(data: &mut &'a [u8], len: u64) -> anyhow::Result<&'a [u8]> {
+    if (data.len() as u64) < len {
+        return Err(anyhow!("truncated block"));
+    }
+    let (head, rest) = data.split_at(len as usize);
+    *data = rest;
+    Ok(head)
+}
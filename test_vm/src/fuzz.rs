@@ -0,0 +1,385 @@
+//! Random sequences of actor method calls, for property-based and fuzz testing.
+//!
+//! Sequences are generated either by the proptest strategies of this module, which
+//! shrink failing sequences to minimal ones, or from raw bytes with `arbitrary`, as
+//! done by the cargo-fuzz targets in `fuzz/`. Actors and amounts are picked by index
+//! into a small fixed set so that most calls get past argument validation, and the
+//! state invariants of all actors are checked after every call.
+//!
+//! Only built with the `fuzz` feature, e.g. `cargo test -p test_vm --features fuzz`.
+
+use arbitrary::Arbitrary;
+use fil_actor_account::State as AccountState;
+use fil_actor_hierarchical_sca::{
+    merkle, Checkpoint, ClaimFundsParams, ConsensusType, ConstructorParams as SCAConstructorParams,
+    CrossMsgParams, FundParams, Method as SCAMethod, RegisterParams, SnapshotBalance,
+    State as SCAState, StorableMsg,
+};
+use fil_actor_init::ExecReturn;
+use fil_actor_market::{Method as MarketMethod, WithdrawBalanceParams};
+use fil_actor_multisig::{Method as MsigMethod, ProposeParams, TxnID, TxnIDParams};
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::test_utils::{
+    MULTISIG_ACTOR_CODE_ID, SCA_ACTOR_CODE_ID, SUBNET_ACTOR_CODE_ID,
+};
+use fil_actors_runtime::{INIT_ACTOR_ADDR, SCA_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR};
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::{Cbor, RawBytes};
+use fvm_shared::address::subnet::ROOTNET_ID;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::{MethodNum, METHOD_SEND};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::util::{apply_ok, create_accounts};
+use crate::{actor, TEST_FAUCET_ADDR, VM};
+
+const ACCOUNTS: u64 = 4;
+const SUBNETS: u64 = 3;
+/// ID of the first subnet actor, far from the ones assigned by the init actor.
+const FIRST_SUBNET_ADDR: u64 = 10_000;
+/// The first signers of the multisig, out of the accounts. The others aren't signers.
+const SIGNERS: usize = 3;
+/// ID of the subnet actor of the network the SCA runs in, a child of the root so
+/// that funds can be released to its parent.
+const NETWORK_ADDR: u64 = 1_000;
+/// Balance of every account in the snapshots committed by subnets, in tenths of FIL.
+const SNAPSHOT_BALANCE: u8 = 1;
+
+#[derive(Clone, Debug, Arbitrary)]
+pub enum Op {
+    Sca(ScaOp),
+    Market(MarketOp),
+    Msig(MsigOp),
+    /// Moves the VM forward by some epochs, e.g. past the grace period of a subnet.
+    Advance(u8),
+}
+
+/// Calls to the SCA. Amounts are in tenths of FIL, so the minimum stake is 10.
+/// Checkpoints are committed at the current epoch on top of the last one of the
+/// subnet, and funds are claimed back with the snapshot of its last checkpoint.
+#[derive(Clone, Debug, Arbitrary)]
+pub enum ScaOp {
+    Register { subnet: u8, stake: u8 },
+    AddStake { subnet: u8, amount: u8 },
+    ReleaseStake { subnet: u8, amount: u8 },
+    Kill { subnet: u8 },
+    Fund { from: u8, subnet: u8, amount: u8 },
+    CommitChildCheckpoint { subnet: u8 },
+    SendCross { from: u8, subnet: u8, to: u8, amount: u8 },
+    Release { from: u8, amount: u8 },
+    ClaimFunds { from: u8, subnet: u8 },
+}
+
+/// Calls to the market actor. Amounts are in tenths of FIL.
+#[derive(Clone, Debug, Arbitrary)]
+pub enum MarketOp {
+    AddBalance { from: u8, amount: u8 },
+    WithdrawBalance { from: u8, amount: u8 },
+}
+
+/// Calls to a 2-of-3 multisig. Amounts are in tenths of FIL.
+#[derive(Clone, Debug, Arbitrary)]
+pub enum MsigOp {
+    Propose { signer: u8, to: u8, amount: u8 },
+    Approve { signer: u8, txn: u8 },
+    Cancel { signer: u8, txn: u8 },
+}
+
+/// Actors the operations are applied to.
+pub struct Env {
+    pub network: SubnetID,
+    pub accounts: Vec<Address>,
+    pub subnets: Vec<Address>,
+    pub msig: Address,
+}
+
+impl Env {
+    fn account(&self, i: u8) -> Address {
+        self.accounts[i as usize % self.accounts.len()]
+    }
+
+    fn subnet(&self, i: u8) -> Address {
+        self.subnets[i as usize % self.subnets.len()]
+    }
+
+    fn subnet_id(&self, i: u8) -> SubnetID {
+        SubnetID::new(&self.network, self.subnet(i))
+    }
+}
+
+/// Creates a VM with the SCA, the accounts, the subnet actors and the multisig.
+pub fn setup(store: &MemoryBlockstore) -> (VM, Env) {
+    let v = VM::new_with_singletons(store);
+    let network = SubnetID::new(&ROOTNET_ID, Address::new_id(NETWORK_ADDR));
+    let params = SCAConstructorParams {
+        network_name: network.to_string(),
+        checkpoint_period: 10,
+        escape_hatch_periods: 10,
+    };
+    let st = SCAState::new(store, params).unwrap();
+    v.set_actor(
+        *SCA_ACTOR_ADDR,
+        actor(*SCA_ACTOR_CODE_ID, v.put_store(&st), 0, TokenAmount::zero()),
+    );
+
+    let balance = TokenAmount::from(1_000u64) * TokenAmount::from(10u64.pow(18));
+    let accounts = create_accounts(&v, ACCOUNTS, balance.clone());
+    let subnets: Vec<Address> =
+        (0..SUBNETS).map(|i| Address::new_id(FIRST_SUBNET_ADDR + i)).collect();
+    for addr in subnets.iter() {
+        let head = v.put_store(&());
+        v.set_actor(*addr, actor(*SUBNET_ACTOR_CODE_ID, head, 0, TokenAmount::zero()));
+        apply_ok(&v, TEST_FAUCET_ADDR, *addr, balance.clone(), METHOD_SEND, RawBytes::default());
+    }
+
+    let params = serialize(
+        &fil_actor_multisig::ConstructorParams {
+            signers: accounts[..SIGNERS].to_vec(),
+            num_approvals_threshold: 2,
+            unlock_duration: 0,
+            start_epoch: 0,
+        },
+        "multisig ctor params",
+    )
+    .unwrap();
+    let ret: ExecReturn = apply_ok(
+        &v,
+        accounts[0],
+        *INIT_ACTOR_ADDR,
+        balance,
+        fil_actor_init::Method::Exec as u64,
+        fil_actor_init::ExecParams {
+            code_cid: *MULTISIG_ACTOR_CODE_ID,
+            constructor_params: params,
+        },
+    )
+    .deserialize()
+    .unwrap();
+
+    (v, Env { network, accounts, subnets, msig: ret.id_address })
+}
+
+/// Applies an operation. Calls are allowed to fail: only the VM itself failing is an error.
+pub fn apply<'bs>(v: VM<'bs>, env: &Env, op: &Op) -> Result<VM<'bs>, String> {
+    let sca = *SCA_ACTOR_ADDR;
+    let market = *STORAGE_MARKET_ACTOR_ADDR;
+    match op {
+        Op::Sca(op) => match *op {
+            ScaOp::Register { subnet, stake } => {
+                let params = RegisterParams {
+                    name: format!("subnet-{}", subnet),
+                    consensus: ConsensusType::Dummy,
+                };
+                call(&v, env.subnet(subnet), sca, stake, SCAMethod::Register as u64, params)
+            }
+            ScaOp::AddStake { subnet, amount } => call(
+                &v,
+                env.subnet(subnet),
+                sca,
+                amount,
+                SCAMethod::AddStake as u64,
+                RawBytes::default(),
+            ),
+            ScaOp::ReleaseStake { subnet, amount } => {
+                let params = FundParams { value: fil_tenths(amount) };
+                call(&v, env.subnet(subnet), sca, 0, SCAMethod::ReleaseStake as u64, params)
+            }
+            ScaOp::Kill { subnet } => {
+                call(&v, env.subnet(subnet), sca, 0, SCAMethod::Kill as u64, RawBytes::default())
+            }
+            ScaOp::Fund { from, subnet, amount } => {
+                let params = SubnetParams(env.subnet_id(subnet));
+                call(&v, env.account(from), sca, amount, SCAMethod::Fund as u64, params)
+            }
+            ScaOp::CommitChildCheckpoint { subnet } => {
+                let id = env.subnet_id(subnet);
+                let st: SCAState = v.get_state(sca).ok_or("missing SCA state")?;
+                let prev = st.get_subnet(v.store, &id).map_err(|e| e.to_string())?;
+                let mut ch = Checkpoint::new(id, v.get_epoch());
+                ch.data.prev_check = prev.map(|sub| sub.prev_checkpoint_cid).unwrap_or_default();
+                // a tipset of its own for every subnet and epoch
+                let block = ch.cid();
+                ch.set_tipset(&[block], block);
+                ch.set_balances(&snapshot(&v, env)?).map_err(|e| e.to_string())?;
+                let method = SCAMethod::CommitChildCheckpoint as u64;
+                call(&v, env.subnet(subnet), sca, 0, method, ch)
+            }
+            ScaOp::SendCross { from, subnet, to, amount } => {
+                let msg = StorableMsg {
+                    to: env.account(to),
+                    method: METHOD_SEND,
+                    value: fil_tenths(amount),
+                    ..Default::default()
+                };
+                let params = CrossMsgParams { msg, destination: env.subnet_id(subnet) };
+                call(&v, env.account(from), sca, amount, SCAMethod::SendCross as u64, params)
+            }
+            ScaOp::Release { from, amount } => call(
+                &v,
+                env.account(from),
+                sca,
+                amount,
+                SCAMethod::Release as u64,
+                RawBytes::default(),
+            ),
+            ScaOp::ClaimFunds { from, subnet } => {
+                let balances = snapshot(&v, env)?;
+                let balance = &balances[from as usize % balances.len()];
+                let params = ClaimFundsParams {
+                    subnet: env.subnet_id(subnet),
+                    value: balance.value.clone(),
+                    proof: merkle::prove(&balances, balance).map_err(|e| e.to_string())?,
+                };
+                call(&v, env.account(from), sca, 0, SCAMethod::ClaimFunds as u64, params)
+            }
+        },
+        Op::Market(op) => match *op {
+            MarketOp::AddBalance { from, amount } => {
+                let params = env.account(from);
+                call(&v, params, market, amount, MarketMethod::AddBalance as u64, params)
+            }
+            MarketOp::WithdrawBalance { from, amount } => {
+                let params = WithdrawBalanceParams {
+                    provider_or_client: env.account(from),
+                    amount: fil_tenths(amount),
+                };
+                call(&v, env.account(from), market, 0, MarketMethod::WithdrawBalance as u64, params)
+            }
+        },
+        Op::Msig(op) => match *op {
+            MsigOp::Propose { signer, to, amount } => {
+                let params = ProposeParams {
+                    to: env.account(to),
+                    value: fil_tenths(amount),
+                    method: METHOD_SEND,
+                    params: RawBytes::default(),
+                };
+                call(&v, env.account(signer), env.msig, 0, MsigMethod::Propose as u64, params)
+            }
+            // an empty proposal hash skips its verification
+            MsigOp::Approve { signer, txn } => {
+                let params = TxnIDParams { id: TxnID(txn as i64), proposal_hash: Vec::new() };
+                call(&v, env.account(signer), env.msig, 0, MsigMethod::Approve as u64, params)
+            }
+            MsigOp::Cancel { signer, txn } => {
+                let params = TxnIDParams { id: TxnID(txn as i64), proposal_hash: Vec::new() };
+                call(&v, env.account(signer), env.msig, 0, MsigMethod::Cancel as u64, params)
+            }
+        },
+        Op::Advance(epochs) => {
+            let epoch = v.get_epoch() + *epochs as ChainEpoch;
+            return Ok(v.with_epoch(epoch));
+        }
+    }?;
+    Ok(v)
+}
+
+/// Applies the operations in order on a fresh VM, checking the state invariants
+/// after each of them. The error describes the first operation breaking them.
+pub fn run(ops: &[Op]) -> Result<(), String> {
+    let store = MemoryBlockstore::new();
+    let (mut v, env) = setup(&store);
+    for (i, op) in ops.iter().enumerate() {
+        v = apply(v, &env, op).map_err(|e| format!("op {} {:?} failed: {}", i, op, e))?;
+        let acc = v.check_state_invariants().map_err(|e| format!("op {} {:?}: {}", i, op, e))?;
+        if !acc.is_empty() {
+            return Err(format!(
+                "op {} {:?} broke invariants:\n{}",
+                i,
+                op,
+                acc.messages().join("\n")
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn sca_op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(subnet, stake)| Op::Sca(ScaOp::Register { subnet, stake })),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(subnet, amount)| Op::Sca(ScaOp::AddStake { subnet, amount })),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(subnet, amount)| Op::Sca(ScaOp::ReleaseStake { subnet, amount })),
+        any::<u8>().prop_map(|subnet| Op::Sca(ScaOp::Kill { subnet })),
+        (any::<u8>(), any::<u8>(), any::<u8>())
+            .prop_map(|(from, subnet, amount)| Op::Sca(ScaOp::Fund { from, subnet, amount })),
+        any::<u8>().prop_map(|subnet| Op::Sca(ScaOp::CommitChildCheckpoint { subnet })),
+        (any::<u8>(), any::<u8>(), any::<u8>(), any::<u8>()).prop_map(
+            |(from, subnet, to, amount)| Op::Sca(ScaOp::SendCross { from, subnet, to, amount })
+        ),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(from, amount)| Op::Sca(ScaOp::Release { from, amount })),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(from, subnet)| Op::Sca(ScaOp::ClaimFunds { from, subnet })),
+        any::<u8>().prop_map(Op::Advance),
+    ]
+}
+
+pub fn market_op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(from, amount)| Op::Market(MarketOp::AddBalance { from, amount })),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(from, amount)| Op::Market(MarketOp::WithdrawBalance { from, amount })),
+    ]
+}
+
+pub fn msig_op() -> impl Strategy<Value = Op> {
+    // transaction IDs are assigned sequentially, so only the first ones exist
+    prop_oneof![
+        (any::<u8>(), any::<u8>(), any::<u8>())
+            .prop_map(|(signer, to, amount)| Op::Msig(MsigOp::Propose { signer, to, amount })),
+        (any::<u8>(), 0..8u8).prop_map(|(signer, txn)| Op::Msig(MsigOp::Approve { signer, txn })),
+        (any::<u8>(), 0..8u8).prop_map(|(signer, txn)| Op::Msig(MsigOp::Cancel { signer, txn })),
+    ]
+}
+
+pub fn any_op() -> impl Strategy<Value = Op> {
+    prop_oneof![sca_op(), market_op(), msig_op()]
+}
+
+/// Sequences of up to `max_len` operations.
+pub fn ops<S: Strategy<Value = Op>>(op: S, max_len: usize) -> impl Strategy<Value = Vec<Op>> {
+    vec(op, 1..=max_len)
+}
+
+/// Balances committed in the checkpoints of subnets, in the order of the accounts.
+fn snapshot(v: &VM, env: &Env) -> Result<Vec<SnapshotBalance>, String> {
+    env.accounts
+        .iter()
+        .map(|addr| {
+            let st: AccountState = v.get_state(*addr).ok_or("missing account state")?;
+            Ok(SnapshotBalance { addr: st.address, value: fil_tenths(SNAPSHOT_BALANCE) })
+        })
+        .collect()
+}
+
+/// Params of `Fund`, which are the bare ID of the subnet.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct SubnetParams(SubnetID);
+impl Cbor for SubnetParams {}
+
+fn call<C: Cbor>(
+    v: &VM,
+    from: Address,
+    to: Address,
+    value: u8,
+    method: MethodNum,
+    params: C,
+) -> Result<(), String> {
+    v.apply_message(from, to, fil_tenths(value), method, params)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn fil_tenths(amount: u8) -> TokenAmount {
+    TokenAmount::from(amount) * TokenAmount::from(10u64.pow(17))
+}
@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+
+use cid::Cid;
+use fil_actors_runtime::ActorError;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+use fvm_shared::crypto::signature::SignatureType;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_SEND};
+use num_traits::Zero;
+
+/// Gas prices of the operations metered by the test VM.
+///
+/// The defaults follow the FVM price list for network version 16, only keeping
+/// the compute cost of each operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceList {
+    pub on_chain_message_compute_base: i64,
+    pub on_chain_message_per_byte: i64,
+    pub send_base: i64,
+    pub send_transfer_funds: i64,
+    pub send_invoke_method: i64,
+    pub create_actor: i64,
+    pub ipld_get_base: i64,
+    pub ipld_get_per_byte: i64,
+    pub ipld_put_base: i64,
+    pub ipld_put_per_byte: i64,
+    pub secp256k1_sig_cost: i64,
+    pub bls_sig_cost: i64,
+    pub hashing_base: i64,
+    pub compute_unsealed_sector_cid_base: i64,
+    pub verify_seal_base: i64,
+    pub verify_aggregate_seal_per_proof: i64,
+    pub verify_post_base: i64,
+    pub verify_consensus_fault: i64,
+    pub verify_replica_update: i64,
+}
+
+impl Default for PriceList {
+    fn default() -> Self {
+        Self {
+            on_chain_message_compute_base: 38863,
+            on_chain_message_per_byte: 1300,
+            send_base: 29233,
+            send_transfer_funds: 27500,
+            send_invoke_method: -5377,
+            create_actor: 1108454,
+            ipld_get_base: 114617,
+            ipld_get_per_byte: 0,
+            ipld_put_base: 353640,
+            ipld_put_per_byte: 1300,
+            secp256k1_sig_cost: 1637292,
+            bls_sig_cost: 16598605,
+            hashing_base: 31355,
+            compute_unsealed_sector_cid_base: 98647,
+            verify_seal_base: 2000,
+            verify_aggregate_seal_per_proof: 449900,
+            verify_post_base: 123861062,
+            verify_consensus_fault: 495422,
+            verify_replica_update: 36316136,
+        }
+    }
+}
+
+impl PriceList {
+    pub fn on_chain_message(&self, msg_size: usize) -> i64 {
+        self.on_chain_message_compute_base + self.on_chain_message_per_byte * msg_size as i64
+    }
+
+    pub fn on_method_invocation(&self, value: &TokenAmount, method: MethodNum) -> i64 {
+        let mut gas = self.send_base;
+        if !value.is_zero() {
+            gas += self.send_transfer_funds;
+        }
+        if method != METHOD_SEND {
+            gas += self.send_invoke_method;
+        }
+        gas
+    }
+
+    pub fn on_ipld_get(&self, size: usize) -> i64 {
+        self.ipld_get_base + self.ipld_get_per_byte * size as i64
+    }
+
+    pub fn on_ipld_put(&self, size: usize) -> i64 {
+        self.ipld_put_base + self.ipld_put_per_byte * size as i64
+    }
+
+    pub fn on_verify_signature(&self, sig_type: SignatureType) -> i64 {
+        match sig_type {
+            SignatureType::BLS => self.bls_sig_cost,
+            SignatureType::Secp256k1 => self.secp256k1_sig_cost,
+        }
+    }
+}
+
+/// Gas used by the message being applied, and its limit.
+#[derive(Clone, Debug, Default)]
+pub struct GasTracker {
+    limit: Option<i64>,
+    used: i64,
+}
+
+impl GasTracker {
+    pub fn new(limit: Option<i64>) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    /// Charges `gas` to the message, failing with `SYS_OUT_OF_GAS` if it goes over its limit.
+    ///
+    /// Gas is still accounted when the limit is exceeded, so charges that can't
+    /// fail, like explicit `charge_gas` calls, abort the message once it returns.
+    pub fn charge(&mut self, name: &str, gas: i64) -> Result<(), ActorError> {
+        self.used = self.used.saturating_add(gas);
+        if self.out_of_gas() {
+            return Err(ActorError::unchecked(
+                ExitCode::SYS_OUT_OF_GAS,
+                format!(
+                    "not enough gas charging {}: used {} of {}",
+                    name,
+                    self.used,
+                    self.limit.unwrap()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn out_of_gas(&self) -> bool {
+        matches!(self.limit, Some(limit) if self.used > limit)
+    }
+
+    /// Gas used by the message, capped to its limit.
+    pub fn gas_used(&self) -> i64 {
+        match self.limit {
+            Some(limit) => self.used.min(limit),
+            None => self.used,
+        }
+    }
+}
+
+/// Blockstore charging gas for the IPLD reads and writes of actors.
+#[derive(Clone, Copy)]
+pub struct GasBlockstore<'a> {
+    pub(crate) store: &'a MemoryBlockstore,
+    pub(crate) price_list: &'a PriceList,
+    pub(crate) tracker: &'a RefCell<GasTracker>,
+}
+
+impl Blockstore for GasBlockstore<'_> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let block = self.store.get(k)?;
+        let size = block.as_ref().map(|b| b.len()).unwrap_or_default();
+        self.tracker.borrow_mut().charge("ipld_get", self.price_list.on_ipld_get(size))?;
+        Ok(block)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.tracker.borrow_mut().charge("ipld_put", self.price_list.on_ipld_put(block.len()))?;
+        self.store.put_keyed(k, block)
+    }
+}
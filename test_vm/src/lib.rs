@@ -15,11 +15,11 @@ use fil_actor_system::{Actor as SystemActor, State as SystemState};
 use fil_actor_verifreg::{Actor as VerifregActor, State as VerifRegState};
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::{
-    ActorCode, DomainSeparationTag, MessageInfo, Policy, Primitives, Runtime, RuntimePolicy,
-    Verifier,
+    ActorCode, ActorEvent, DomainSeparationTag, MessageInfo, Policy, Primitives, Runtime,
+    RuntimePolicy, Verifier,
 };
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::MessageAccumulator;
+use fil_actors_runtime::{ActorDowncast, MessageAccumulator};
 use fil_actors_runtime::{
     ActorError, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, FIRST_NON_SINGLETON_ADDR, INIT_ACTOR_ADDR,
     REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
@@ -58,8 +58,15 @@ use std::error::Error;
 use std::fmt;
 use std::ops::Add;
 
+pub mod car;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod gas;
+pub mod trace;
 pub mod util;
 
+use gas::{GasBlockstore, GasTracker, PriceList};
+
 pub struct VM<'bs> {
     pub store: &'bs MemoryBlockstore,
     pub state_root: RefCell<Cid>,
@@ -68,8 +75,16 @@ pub struct VM<'bs> {
     actors_cache: RefCell<HashMap<Address, Actor>>,
     empty_obj_cid: Cid,
     network_version: NetworkVersion,
+    policy: Policy,
     curr_epoch: ChainEpoch,
     invocations: RefCell<Vec<InvocationTrace>>,
+    price_list: PriceList,
+    gas_tracker: RefCell<GasTracker>,
+    /// Code CIDs of the snapshot the VM was loaded from, by test code CID.
+    snapshot_codes: HashMap<Cid, Cid>,
+    /// Version and info of the state root of the snapshot the VM was loaded from,
+    /// if the snapshot is rooted at a versioned state root.
+    snapshot_root: Option<(u64, Cid)>,
 }
 
 pub struct MinerBalances {
@@ -120,8 +135,13 @@ impl<'bs> VM<'bs> {
             actors_cache: RefCell::new(HashMap::new()),
             empty_obj_cid: empty,
             network_version: NetworkVersion::V16,
+            policy: Policy::default(),
             curr_epoch: ChainEpoch::zero(),
             invocations: RefCell::new(vec![]),
+            price_list: PriceList::default(),
+            gas_tracker: RefCell::new(GasTracker::default()),
+            snapshot_codes: HashMap::new(),
+            snapshot_root: None,
         }
     }
 
@@ -129,6 +149,20 @@ impl<'bs> VM<'bs> {
         Self { total_fil, ..self }
     }
 
+    pub fn with_price_list(self, price_list: PriceList) -> Self {
+        Self { price_list, ..self }
+    }
+
+    /// Sets the policy all actors are invoked with, instead of the one
+    /// selected by the features the runtime is built with.
+    pub fn with_policy(self, policy: Policy) -> Self {
+        Self { policy, ..self }
+    }
+
+    pub fn with_network_version(self, network_version: NetworkVersion) -> Self {
+        Self { network_version, ..self }
+    }
+
     pub fn new_with_singletons(store: &'bs MemoryBlockstore) -> VM<'bs> {
         // funding
         let fil = TokenAmount::from(1_000_000_000i32)
@@ -268,8 +302,13 @@ impl<'bs> VM<'bs> {
             actors_cache: RefCell::new(HashMap::new()),
             empty_obj_cid: self.empty_obj_cid,
             network_version: self.network_version,
+            policy: self.policy,
             curr_epoch: epoch,
             invocations: RefCell::new(vec![]),
+            price_list: self.price_list,
+            gas_tracker: RefCell::new(GasTracker::default()),
+            snapshot_codes: self.snapshot_codes,
+            snapshot_root: self.snapshot_root,
         }
     }
 
@@ -377,6 +416,14 @@ impl<'bs> VM<'bs> {
         self.curr_epoch
     }
 
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    pub fn network_version(&self) -> NetworkVersion {
+        self.network_version
+    }
+
     pub fn apply_message<C: Cbor>(
         &self,
         from: Address,
@@ -384,6 +431,50 @@ impl<'bs> VM<'bs> {
         value: TokenAmount,
         method: MethodNum,
         params: C,
+    ) -> Result<MessageResult, TestVMError> {
+        self.apply_message_with_limit(from, to, value, method, params, None)
+    }
+
+    /// Applies a message that aborts with `SYS_OUT_OF_GAS` if it uses more than `gas_limit`.
+    pub fn apply_message_with_gas_limit<C: Cbor>(
+        &self,
+        from: Address,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: C,
+        gas_limit: i64,
+    ) -> Result<MessageResult, TestVMError> {
+        self.apply_message_with_limit(from, to, value, method, params, Some(gas_limit))
+    }
+
+    /// Applies an implicit message, as the ones sent by the system actor at the
+    /// end of every epoch. They don't bump the nonce of the sender nor are they
+    /// charged for their gas.
+    pub fn apply_implicit_message<C: Cbor>(
+        &self,
+        from: Address,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: C,
+    ) -> Result<MessageResult, TestVMError> {
+        // implicit messages aren't signed, their sender may not exist in the state tree
+        let from_id = self.normalize_address(&from).unwrap_or(from);
+        let call_seq = self.get_actor(from_id).map_or(0, |a| a.call_seq_num);
+        let params = serialize(&params, "params for apply message").unwrap();
+        let res = self.execute(from, from_id, call_seq, to, value, method, params, None);
+        Ok(MessageResult { gas_used: 0, ..res })
+    }
+
+    fn apply_message_with_limit<C: Cbor>(
+        &self,
+        from: Address,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: C,
+        gas_limit: Option<i64>,
     ) -> Result<MessageResult, TestVMError> {
         let from_id = self.normalize_address(&from).unwrap();
         let mut a = self.get_actor(from_id).unwrap();
@@ -391,50 +482,71 @@ impl<'bs> VM<'bs> {
         a.call_seq_num = call_seq + 1;
         self.set_actor(from_id, a);
 
+        let params = serialize(&params, "params for apply message").unwrap();
+        Ok(self.execute(from, from_id, call_seq, to, value, method, params, gas_limit))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute(
+        &self,
+        from: Address,
+        from_id: Address,
+        call_seq: u64,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: RawBytes,
+        gas_limit: Option<i64>,
+    ) -> MessageResult {
         let prior_root = self.checkpoint();
 
-        // big.Mul(big.NewInt(1e9), big.NewInt(1e18))
         // make top level context with internal context
-        let top = TopCtx {
-            originator_stable_addr: from,
-            _originator_call_seq: call_seq,
-            new_actor_addr_count: RefCell::new(0),
-            circ_supply: TokenAmount::from(1e9 as u128 * 1e18 as u128),
-        };
-        let msg = InternalMessage {
-            from: from_id,
-            to,
-            value,
-            method,
-            params: serialize(&params, "params for apply message").unwrap(),
-        };
-        let mut new_ctx = InvocationCtx {
-            v: self,
-            top,
-            msg,
-            allow_side_effects: true,
-            caller_validated: false,
-            policy: &Policy::default(),
-            subinvocations: RefCell::new(vec![]),
-        };
-        let res = new_ctx.invoke();
-        let invoc = new_ctx.gather_trace(res.clone());
-        RefMut::map(self.invocations.borrow_mut(), |invocs| {
-            invocs.push(invoc);
-            invocs
+        let top = TopCtx::new(from, call_seq);
+        let msg = InternalMessage { from: from_id, to, value, method, params };
+        self.gas_tracker.replace(GasTracker::new(gas_limit));
+        let on_chain_gas = self.price_list.on_chain_message(msg.params.len());
+        let charged = self.gas_tracker.borrow_mut().charge("on_chain_message", on_chain_gas);
+        let res = charged.and_then(|_| {
+            let mut new_ctx = InvocationCtx::new(self, top, msg, &self.policy);
+            let res = new_ctx.invoke();
+            let invoc = new_ctx.gather_trace(res.clone());
+            RefMut::map(self.invocations.borrow_mut(), |invocs| {
+                invocs.push(invoc);
+                invocs
+            });
+            res.map(|ret| (ret, new_ctx.events.take()))
         });
+        let gas_used = self.gas_tracker.borrow().gas_used();
         match res {
             Err(ae) => {
                 self.rollback(prior_root);
-                Ok(MessageResult { code: ae.exit_code(), ret: RawBytes::default() })
+                MessageResult {
+                    code: ae.exit_code(),
+                    ret: RawBytes::default(),
+                    gas_used,
+                    events: vec![],
+                }
             }
-            Ok(ret) => {
+            Ok((ret, events)) => {
                 self.checkpoint();
-                Ok(MessageResult { code: ExitCode::OK, ret })
+                MessageResult { code: ExitCode::OK, ret, gas_used, events }
             }
         }
     }
 
+    /// The runtime an actor at `to` is invoked with by a message from `from`, to check
+    /// what actors see of the VM.
+    pub fn runtime(&self, from: Address, to: Address) -> InvocationCtx<'_, 'bs> {
+        let msg = InternalMessage {
+            from,
+            to,
+            value: TokenAmount::zero(),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+        };
+        InvocationCtx::new(self, TopCtx::new(from, 0), msg, &self.policy)
+    }
+
     pub fn take_invocations(&self) -> Vec<InvocationTrace> {
         self.invocations.take()
     }
@@ -456,11 +568,10 @@ impl<'bs> VM<'bs> {
             })
             .unwrap();
 
-        let policy = Policy::default();
         let state_tree = Tree::load(&self.store, &self.state_root.borrow()).unwrap();
         check_state_invariants(
             &manifest,
-            &policy,
+            &self.policy,
             state_tree,
             &self.total_fil,
             self.get_epoch() - 1,
@@ -501,6 +612,18 @@ pub struct TopCtx {
     circ_supply: BigInt,
 }
 
+impl TopCtx {
+    fn new(originator: Address, call_seq: u64) -> Self {
+        TopCtx {
+            originator_stable_addr: originator,
+            _originator_call_seq: call_seq,
+            new_actor_addr_count: RefCell::new(0),
+            // big.Mul(big.NewInt(1e9), big.NewInt(1e18))
+            circ_supply: TokenAmount::from(1e9 as u128 * 1e18 as u128),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct InternalMessage {
     from: Address,
@@ -533,15 +656,43 @@ pub const TEST_VM_INVALID: &str = "i_am_invalid";
 
 pub struct InvocationCtx<'invocation, 'bs> {
     v: &'invocation VM<'bs>,
+    store: GasBlockstore<'invocation>,
     top: TopCtx,
     msg: InternalMessage,
     allow_side_effects: bool,
     caller_validated: bool,
     policy: &'invocation Policy,
     subinvocations: RefCell<Vec<InvocationTrace>>,
+    /// Events emitted by this call and the calls it made that didn't abort.
+    events: RefCell<Vec<EmittedEvent>>,
 }
 
 impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
+    fn new(
+        v: &'invocation VM<'bs>,
+        top: TopCtx,
+        msg: InternalMessage,
+        policy: &'invocation Policy,
+    ) -> Self {
+        let store =
+            GasBlockstore { store: v.store, price_list: &v.price_list, tracker: &v.gas_tracker };
+        InvocationCtx {
+            v,
+            store,
+            top,
+            msg,
+            allow_side_effects: true,
+            caller_validated: false,
+            policy,
+            subinvocations: RefCell::new(vec![]),
+            events: RefCell::new(vec![]),
+        }
+    }
+
+    fn charge(&self, name: &str, gas: i64) -> Result<(), ActorError> {
+        self.v.gas_tracker.borrow_mut().charge(name, gas)
+    }
+
     fn resolve_target(&'invocation self, target: &Address) -> Result<(Actor, Address), ActorError> {
         if let Some(a) = self.v.normalize_address(target) {
             if let Some(act) = self.v.get_actor(a) {
@@ -574,15 +725,8 @@ impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
             params: serialize::<Address>(target, "address").unwrap(),
         };
         {
-            let mut new_ctx = InvocationCtx {
-                v: self.v,
-                top: self.top.clone(),
-                msg: new_actor_msg,
-                allow_side_effects: true,
-                caller_validated: false,
-                policy: self.policy,
-                subinvocations: RefCell::new(vec![]),
-            };
+            let mut new_ctx =
+                InvocationCtx::new(self.v, self.top.clone(), new_actor_msg, self.policy);
             new_ctx.create_actor(*ACCOUNT_ACTOR_CODE_ID, target_id).unwrap();
             let res = new_ctx.invoke();
             let invoc = new_ctx.gather_trace(res);
@@ -601,11 +745,15 @@ impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
             Err(ae) => (None, Some(ae.exit_code())),
         };
         let mut msg = self.msg.clone();
+        let mut actor_type = None;
         msg.to = match self.resolve_target(&self.msg.to) {
-            Ok((_, addr)) => addr, // use normalized address in trace
+            Ok((act, addr)) => {
+                actor_type = ACTOR_TYPES.get(&act.code).cloned();
+                addr // use normalized address in trace
+            }
             _ => self.msg.to, // if target resolution fails don't fail whole invoke, just use non normalized
         };
-        InvocationTrace { msg, code, ret, subinvocations: self.subinvocations.take() }
+        InvocationTrace { msg, actor_type, code, ret, subinvocations: self.subinvocations.take() }
     }
 
     fn to(&'_ self) -> Address {
@@ -613,6 +761,10 @@ impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
     }
 
     fn invoke(&mut self) -> Result<RawBytes, ActorError> {
+        self.charge(
+            "on_method_invocation",
+            self.v.price_list.on_method_invocation(&self.msg.value, self.msg.method),
+        )?;
         let prior_root = self.v.checkpoint();
 
         // Transfer funds
@@ -663,6 +815,17 @@ impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
             Type::SCA => SCAActor::invoke_method(self, self.msg.method, &params),
             Type::Subnet => panic!("TODO: subnet actor not implemented yet"),
         };
+        // charges that can't fail leave the message out of gas, so it's aborted here.
+        let res = match res {
+            Ok(_) if self.v.gas_tracker.borrow().out_of_gas() => Err(ActorError::unchecked(
+                ExitCode::SYS_OUT_OF_GAS,
+                "message ran out of gas".to_string(),
+            )),
+            Err(e) if self.v.gas_tracker.borrow().out_of_gas() => {
+                Err(ActorError::unchecked(ExitCode::SYS_OUT_OF_GAS, e.msg().to_string()))
+            }
+            res => res,
+        };
         if res.is_err() {
             self.v.rollback(prior_root)
         };
@@ -670,8 +833,9 @@ impl<'invocation, 'bs> InvocationCtx<'invocation, 'bs> {
     }
 }
 
-impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocation, 'bs> {
+impl<'invocation, 'bs> Runtime<GasBlockstore<'invocation>> for InvocationCtx<'invocation, 'bs> {
     fn create_actor(&mut self, code_id: Cid, actor_id: ActorID) -> Result<(), ActorError> {
+        self.charge("create_actor", self.v.price_list.create_actor)?;
         match NON_SINGLETON_CODES.get(&code_id) {
             Some(_) => (),
             None => {
@@ -693,8 +857,8 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
         Ok(())
     }
 
-    fn store(&self) -> &&'bs MemoryBlockstore {
-        &self.v.store
+    fn store(&self) -> &GasBlockstore<'invocation> {
+        &self.store
     }
 
     fn network_version(&self) -> NetworkVersion {
@@ -793,15 +957,7 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
         }
 
         let new_actor_msg = InternalMessage { from: self.to(), to, value, method, params };
-        let mut new_ctx = InvocationCtx {
-            v: self.v,
-            top: self.top.clone(),
-            msg: new_actor_msg,
-            allow_side_effects: true,
-            caller_validated: false,
-            policy: self.policy,
-            subinvocations: RefCell::new(vec![]),
-        };
+        let mut new_ctx = InvocationCtx::new(self.v, self.top.clone(), new_actor_msg, self.policy);
         let res = new_ctx.invoke();
 
         let invoc = new_ctx.gather_trace(res.clone());
@@ -809,6 +965,9 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
             subinvocs.push(invoc);
             subinvocs
         });
+        if res.is_ok() {
+            self.events.borrow_mut().append(&mut new_ctx.events.borrow_mut());
+        }
         res
     }
 
@@ -844,7 +1003,9 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
                         "failed to construct state: already initialized".to_string(),
                     ))
                 } else {
-                    act.head = self.v.store.put_cbor(obj, Code::Blake2b256).unwrap();
+                    act.head = self.store.put_cbor(obj, Code::Blake2b256).map_err(|e| {
+                        e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to store state")
+                    })?;
                     self.v.set_actor(self.to(), act);
                     Ok(())
                 }
@@ -853,7 +1014,12 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
     }
 
     fn state<C: Cbor>(&self) -> Result<C, ActorError> {
-        Ok(self.v.get_state::<C>(self.to()).unwrap())
+        let act = self.v.get_actor(self.to()).unwrap();
+        let st = self
+            .store
+            .get_cbor::<C>(&act.head)
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load state"))?;
+        Ok(st.unwrap())
     }
 
     fn transaction<C, RT, F>(&mut self, f: F) -> Result<RT, ActorError>
@@ -861,13 +1027,15 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
         C: Cbor,
         F: FnOnce(&mut C, &mut Self) -> Result<RT, ActorError>,
     {
-        let mut st = self.state::<C>().unwrap();
+        let mut st = self.state::<C>()?;
         self.allow_side_effects = false;
         let result = f(&mut st, self);
         self.allow_side_effects = true;
         let ret = result?;
         let mut act = self.v.get_actor(self.to()).unwrap();
-        act.head = self.v.store.put_cbor(&st, Code::Blake2b256).unwrap();
+        act.head = self.store.put_cbor(&st, Code::Blake2b256).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to store state")
+        })?;
         self.v.set_actor(self.to(), act);
         Ok(ret)
     }
@@ -900,11 +1068,26 @@ impl<'invocation, 'bs> Runtime<&'bs MemoryBlockstore> for InvocationCtx<'invocat
         self.top.circ_supply.clone()
     }
 
-    fn charge_gas(&mut self, _name: &'static str, _compute: i64) {}
+    fn charge_gas(&mut self, name: &'static str, compute: i64) {
+        // running out of gas aborts the message once the actor returns.
+        let _ = self.charge(name, compute);
+    }
 
     fn base_fee(&self) -> TokenAmount {
         TokenAmount::zero()
     }
+
+    fn emit_event(&mut self, event: &ActorEvent) -> Result<(), ActorError> {
+        if !self.allow_side_effects {
+            return Err(ActorError::unchecked(
+                ExitCode::SYS_ASSERTION_FAILED,
+                "Emitting events is not allowed during side-effect lock".to_string(),
+            ));
+        }
+        let emitter = self.to();
+        self.events.borrow_mut().push(EmittedEvent { emitter, event: event.clone() });
+        Ok(())
+    }
 }
 
 impl Primitives for VM<'_> {
@@ -949,10 +1132,13 @@ impl Primitives for InvocationCtx<'_, '_> {
         signer: &Address,
         plaintext: &[u8],
     ) -> Result<(), anyhow::Error> {
+        let gas = self.v.price_list.on_verify_signature(signature.sig_type);
+        self.charge("verify_signature", gas)?;
         self.v.verify_signature(signature, signer, plaintext)
     }
 
     fn hash_blake2b(&self, data: &[u8]) -> [u8; 32] {
+        let _ = self.charge("hash_blake2b", self.v.price_list.hashing_base);
         self.v.hash_blake2b(data)
     }
 
@@ -961,16 +1147,20 @@ impl Primitives for InvocationCtx<'_, '_> {
         proof_type: RegisteredSealProof,
         pieces: &[PieceInfo],
     ) -> Result<Cid, anyhow::Error> {
+        let gas = self.v.price_list.compute_unsealed_sector_cid_base;
+        self.charge("compute_unsealed_sector_cid", gas)?;
         self.v.compute_unsealed_sector_cid(proof_type, pieces)
     }
 }
 
 impl Verifier for InvocationCtx<'_, '_> {
     fn verify_seal(&self, _vi: &SealVerifyInfo) -> Result<(), anyhow::Error> {
+        self.charge("verify_seal", self.v.price_list.verify_seal_base)?;
         Ok(())
     }
 
     fn verify_post(&self, verify_info: &WindowPoStVerifyInfo) -> Result<(), anyhow::Error> {
+        self.charge("verify_post", self.v.price_list.verify_post_base)?;
         for proof in &verify_info.proofs {
             if proof.proof_bytes.eq(&TEST_VM_INVALID.as_bytes().to_vec()) {
                 return Err(anyhow!("invalid proof"));
@@ -986,21 +1176,27 @@ impl Verifier for InvocationCtx<'_, '_> {
         _h2: &[u8],
         _extra: &[u8],
     ) -> Result<Option<ConsensusFault>, anyhow::Error> {
+        self.charge("verify_consensus_fault", self.v.price_list.verify_consensus_fault)?;
         Ok(None)
     }
 
     fn batch_verify_seals(&self, batch: &[SealVerifyInfo]) -> anyhow::Result<Vec<bool>> {
+        let gas = self.v.price_list.verify_seal_base * batch.len() as i64;
+        self.charge("batch_verify_seals", gas)?;
         Ok(vec![true; batch.len()]) // everyone wins
     }
 
     fn verify_aggregate_seals(
         &self,
-        _aggregate: &AggregateSealVerifyProofAndInfos,
+        aggregate: &AggregateSealVerifyProofAndInfos,
     ) -> Result<(), anyhow::Error> {
+        let gas = self.v.price_list.verify_aggregate_seal_per_proof * aggregate.infos.len() as i64;
+        self.charge("verify_aggregate_seals", gas)?;
         Ok(())
     }
 
     fn verify_replica_update(&self, _replica: &ReplicaUpdateInfo) -> Result<(), anyhow::Error> {
+        self.charge("verify_replica_update", self.v.price_list.verify_replica_update)?;
         Ok(())
     }
 }
@@ -1015,6 +1211,17 @@ impl RuntimePolicy for InvocationCtx<'_, '_> {
 pub struct MessageResult {
     pub code: ExitCode,
     pub ret: RawBytes,
+    /// Gas used by the message, capped to its gas limit.
+    pub gas_used: i64,
+    /// Events emitted while executing the message, empty if it aborted.
+    pub events: Vec<EmittedEvent>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct EmittedEvent {
+    /// ID address of the actor that emitted the event.
+    pub emitter: Address,
+    pub event: ActorEvent,
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple, Clone, PartialEq, Debug)]
@@ -1033,6 +1240,8 @@ pub fn actor(code: Cid, head: Cid, seq: u64, bal: TokenAmount) -> Actor {
 #[derive(Clone)]
 pub struct InvocationTrace {
     pub msg: InternalMessage,
+    /// Type of the receiving actor, if it exists.
+    pub actor_type: Option<Type>,
     pub code: Option<ExitCode>,
     pub ret: Option<RawBytes>,
     pub subinvocations: Vec<InvocationTrace>,
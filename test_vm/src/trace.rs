@@ -0,0 +1,525 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::actor::builtin::Type;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::bigint_ser::BigIntDe;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_SEND};
+use num_traits::{FromPrimitive, Zero};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::car::{read_header, read_link, take, BUILTIN_ACTORS};
+use crate::{ExpectInvocation, InvocationTrace};
+
+/// JSON form of an invocation trace, with its params and return value decoded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceJson {
+    pub from: String,
+    pub to: String,
+    /// Name of the receiving actor, if it's a builtin actor.
+    pub actor: Option<String>,
+    pub method: MethodNum,
+    /// Name of the method, if it's a method of a builtin actor.
+    pub method_name: Option<String>,
+    /// Value transferred, in attoFIL.
+    pub value: String,
+    pub params: Value,
+    /// Exit code, missing if the invocation succeeded.
+    pub code: Option<u32>,
+    pub ret: Option<Value>,
+    pub subinvocations: Vec<TraceJson>,
+}
+
+impl InvocationTrace {
+    pub fn to_json(&self) -> TraceJson {
+        TraceJson {
+            from: self.msg.from.to_string(),
+            to: self.msg.to.to_string(),
+            actor: self.actor_type.as_ref().map(|typ| actor_name(typ).to_owned()),
+            method: self.msg.method,
+            method_name: self.method_name(),
+            value: self.msg.value.to_string(),
+            params: self.decode(&self.msg.params, false),
+            code: self.code.map(|code| code.value()),
+            ret: self.ret.as_ref().map(|ret| self.decode(ret, true)),
+            subinvocations: self.subinvocations.iter().map(|i| i.to_json()).collect(),
+        }
+    }
+
+    fn method_name(&self) -> Option<String> {
+        if self.msg.method == METHOD_SEND {
+            return Some("Send".to_owned());
+        }
+        let method = self.msg.method;
+        match self.actor_type.as_ref()? {
+            Type::System => name::<fil_actor_system::Method>(method),
+            Type::Init => name::<fil_actor_init::Method>(method),
+            Type::Cron => name::<fil_actor_cron::Method>(method),
+            Type::Account => name::<fil_actor_account::Method>(method),
+            Type::Power => name::<fil_actor_power::Method>(method),
+            Type::Miner => name::<fil_actor_miner::Method>(method),
+            Type::Market => name::<fil_actor_market::Method>(method),
+            Type::PaymentChannel => name::<fil_actor_paych::Method>(method),
+            Type::Multisig => name::<fil_actor_multisig::Method>(method),
+            Type::Reward => name::<fil_actor_reward::Method>(method),
+            Type::VerifiedRegistry => name::<fil_actor_verifreg::Method>(method),
+            Type::SCA => name::<fil_actor_hierarchical_sca::Method>(method),
+            Type::Subnet => None,
+        }
+    }
+
+    /// Decodes the params, or the return value if `ret`, of the invocation with the types of
+    /// its method. Unknown methods, and bytes that don't decode to the types of the method,
+    /// are decoded without a schema.
+    fn decode(&self, bytes: &RawBytes, ret: bool) -> Value {
+        let typed = self.decoder(ret).and_then(|decoder| decoder(bytes));
+        typed.unwrap_or_else(|| decode(bytes))
+    }
+
+    /// Decoder of the params, or the return value if `ret`, of the method invoked.
+    fn decoder(&self, ret: bool) -> Option<fn(&RawBytes) -> Option<Value>> {
+        use fil_actor_account::Method as Account;
+        use fil_actor_hierarchical_sca::Method as Sca;
+        use fil_actor_init::Method as Init;
+        use fil_actor_market::Method as Market;
+        use fil_actor_miner::Method as Miner;
+        use fil_actor_multisig::Method as Multisig;
+        use fil_actor_paych::Method as Paych;
+        use fil_actor_power::Method as Power;
+        use fil_actor_verifreg::Method as Verifreg;
+        use fvm_shared::address::SubnetID;
+
+        let method = self.msg.method;
+        let decoder: fn(&RawBytes) -> Option<Value> = match self.actor_type.as_ref()? {
+            Type::Account => match (Account::from_u64(method)?, ret) {
+                (Account::Constructor, false)
+                | (Account::PubkeyAddress | Account::PubkeyAddressExported, true) => {
+                    typed::<Address>
+                }
+                _ => return None,
+            },
+            Type::Init => match (Init::from_u64(method)?, ret) {
+                (Init::Constructor, false) => typed::<fil_actor_init::ConstructorParams>,
+                (Init::Exec, false) => typed::<fil_actor_init::ExecParams>,
+                (Init::Exec, true) => typed::<fil_actor_init::ExecReturn>,
+                _ => return None,
+            },
+            Type::Market => match (Market::from_u64(method)?, ret) {
+                (Market::AddBalance | Market::AddBalanceExported, false) => typed::<Address>,
+                (Market::WithdrawBalance | Market::WithdrawBalanceExported, false) => {
+                    typed::<fil_actor_market::WithdrawBalanceParams>
+                }
+                (Market::WithdrawBalance | Market::WithdrawBalanceExported, true) => {
+                    typed::<fil_actor_market::WithdrawBalanceReturn>
+                }
+                (Market::PublishStorageDeals | Market::PublishStorageDealsExported, false) => {
+                    typed::<fil_actor_market::PublishStorageDealsParams>
+                }
+                (Market::PublishStorageDeals | Market::PublishStorageDealsExported, true) => {
+                    typed::<fil_actor_market::PublishStorageDealsReturn>
+                }
+                (Market::ActivateDeals, false) => typed::<fil_actor_market::ActivateDealsParams>,
+                _ => return None,
+            },
+            Type::Miner => match (Miner::from_u64(method)?, ret) {
+                (Miner::ControlAddresses, true) => {
+                    typed::<fil_actor_miner::GetControlAddressesReturn>
+                }
+                (Miner::ChangeWorkerAddress, false) => {
+                    typed::<fil_actor_miner::ChangeWorkerAddressParams>
+                }
+                (Miner::ChangePeerID, false) => typed::<fil_actor_miner::ChangePeerIDParams>,
+                (Miner::ChangeOwnerAddress, false) => typed::<Address>,
+                (Miner::ApplyRewards, false) => typed::<fil_actor_miner::ApplyRewardParams>,
+                (Miner::WithdrawBalance, false) => typed::<fil_actor_miner::WithdrawBalanceParams>,
+                (Miner::WithdrawBalance, true) => typed::<fil_actor_miner::WithdrawBalanceReturn>,
+                _ => return None,
+            },
+            Type::Multisig => match (Multisig::from_u64(method)?, ret) {
+                (Multisig::Constructor, false) => typed::<fil_actor_multisig::ConstructorParams>,
+                (Multisig::Propose | Multisig::ProposeExported, false) => {
+                    typed::<fil_actor_multisig::ProposeParams>
+                }
+                (Multisig::Propose | Multisig::ProposeExported, true) => {
+                    typed::<fil_actor_multisig::ProposeReturn>
+                }
+                (
+                    Multisig::Approve
+                    | Multisig::ApproveExported
+                    | Multisig::Cancel
+                    | Multisig::CancelExported,
+                    false,
+                ) => typed::<fil_actor_multisig::TxnIDParams>,
+                (Multisig::Approve | Multisig::ApproveExported, true) => {
+                    typed::<fil_actor_multisig::ApproveReturn>
+                }
+                (Multisig::AddSigner | Multisig::AddSignerExported, false) => {
+                    typed::<fil_actor_multisig::AddSignerParams>
+                }
+                (Multisig::RemoveSigner | Multisig::RemoveSignerExported, false) => {
+                    typed::<fil_actor_multisig::RemoveSignerParams>
+                }
+                (Multisig::SwapSigner | Multisig::SwapSignerExported, false) => {
+                    typed::<fil_actor_multisig::SwapSignerParams>
+                }
+                (
+                    Multisig::ChangeNumApprovalsThreshold
+                    | Multisig::ChangeNumApprovalsThresholdExported,
+                    false,
+                ) => typed::<fil_actor_multisig::ChangeNumApprovalsThresholdParams>,
+                (Multisig::LockBalance | Multisig::LockBalanceExported, false) => {
+                    typed::<fil_actor_multisig::LockBalanceParams>
+                }
+                _ => return None,
+            },
+            Type::PaymentChannel => match (Paych::from_u64(method)?, ret) {
+                (Paych::Constructor, false) => typed::<fil_actor_paych::ConstructorParams>,
+                (Paych::UpdateChannelState, false) => {
+                    typed::<fil_actor_paych::UpdateChannelStateParams>
+                }
+                _ => return None,
+            },
+            Type::Power => match (Power::from_u64(method)?, ret) {
+                (Power::CreateMiner, false) => typed::<fil_actor_power::CreateMinerParams>,
+                (Power::CreateMiner, true) => typed::<fil_actor_power::CreateMinerReturn>,
+                _ => return None,
+            },
+            Type::SCA => match (Sca::from_u64(method)?, ret) {
+                (Sca::Constructor, false) => typed::<fil_actor_hierarchical_sca::ConstructorParams>,
+                (Sca::Register | Sca::RegisterExported, false) => {
+                    typed::<fil_actor_hierarchical_sca::RegisterParams>
+                }
+                (
+                    Sca::Register
+                    | Sca::RegisterExported
+                    | Sca::NetworkName
+                    | Sca::NetworkNameExported,
+                    true,
+                )
+                | (
+                    Sca::Fund | Sca::FundExported | Sca::GetSubnet | Sca::GetSubnetExported,
+                    false,
+                ) => typed::<SubnetID>,
+                (Sca::ReleaseStake | Sca::ReleaseStakeExported, false) => {
+                    typed::<fil_actor_hierarchical_sca::FundParams>
+                }
+                (Sca::CommitChildCheckpoint | Sca::CommitChildCheckpointExported, false) => {
+                    typed::<fil_actor_hierarchical_sca::Checkpoint>
+                }
+                (Sca::SendCross | Sca::SendCrossExported, false) => {
+                    typed::<fil_actor_hierarchical_sca::CrossMsgParams>
+                }
+                (Sca::ApplyMessage, false) => typed::<fil_actor_hierarchical_sca::ApplyMsgParams>,
+                (Sca::ClaimFunds | Sca::ClaimFundsExported, false) => {
+                    typed::<fil_actor_hierarchical_sca::ClaimFundsParams>
+                }
+                (Sca::GetSubnet | Sca::GetSubnetExported, true) => {
+                    typed::<fil_actor_hierarchical_sca::Subnet>
+                }
+                _ => return None,
+            },
+            Type::VerifiedRegistry => match (Verifreg::from_u64(method)?, ret) {
+                (Verifreg::Constructor | Verifreg::RemoveVerifier, false) => typed::<Address>,
+                (Verifreg::AddVerifier | Verifreg::AddVerifiedClient, false) => {
+                    typed::<fil_actor_verifreg::VerifierParams>
+                }
+                (Verifreg::UseBytes | Verifreg::RestoreBytes, false) => {
+                    typed::<fil_actor_verifreg::BytesParams>
+                }
+                (Verifreg::RemoveVerifiedClientDataCap, false) => {
+                    typed::<fil_actor_verifreg::RemoveDataCapParams>
+                }
+                (Verifreg::RemoveVerifiedClientDataCap, true) => {
+                    typed::<fil_actor_verifreg::RemoveDataCapReturn>
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(decoder)
+    }
+
+    /// Writes the trace as an indented tree, one invocation per line.
+    fn fmt_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        let method = match self.method_name() {
+            Some(name) => format!("{}({})", name, self.msg.method),
+            None => self.msg.method.to_string(),
+        };
+        let actor = self
+            .actor_type
+            .as_ref()
+            .map(|typ| format!(" [{}]", actor_name(typ)))
+            .unwrap_or_default();
+        write!(
+            f,
+            "{:indent$}{} -> {}{} {}",
+            "",
+            self.msg.from,
+            self.msg.to,
+            actor,
+            method,
+            indent = depth * 2
+        )?;
+        if !self.msg.value.is_zero() {
+            write!(f, " value {}", self.msg.value)?;
+        }
+        match self.code {
+            Some(code) => writeln!(f, " => exit {}", code)?,
+            None => writeln!(f, " => ok")?,
+        }
+        for invoc in self.subinvocations.iter() {
+            invoc.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for InvocationTrace {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl fmt::Display for InvocationTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+impl ExpectInvocation {
+    /// Differences between the expected invocation and the trace, one per line, with the
+    /// path of the invocation in the tree, e.g. `0.1` for the second subinvocation of the
+    /// first subinvocation. Empty if the trace matches.
+    pub fn diff(&self, invoc: &InvocationTrace) -> Vec<String> {
+        let mut diffs = Vec::new();
+        self.diff_at("root", invoc, &mut diffs);
+        diffs
+    }
+
+    fn diff_at(&self, path: &str, invoc: &InvocationTrace, diffs: &mut Vec<String>) {
+        let mut check = |field: &str, expected: String, actual: String| {
+            if expected != actual {
+                diffs.push(format!("{}: {} expected {} was {}", path, field, expected, actual));
+            }
+        };
+        check("to", self.to.to_string(), invoc.msg.to.to_string());
+        check("method", self.method.to_string(), invoc.msg.method.to_string());
+        if let Some(code) = self.code {
+            check("code", code.to_string(), invoc.code.unwrap_or(ExitCode::OK).to_string());
+        }
+        if let Some(from) = self.from {
+            check("from", from.to_string(), invoc.msg.from.to_string());
+        }
+        if let Some(value) = &self.value {
+            check("value", value.to_string(), invoc.msg.value.to_string());
+        }
+        if let Some(params) = &self.params {
+            check(
+                "params",
+                invoc.decode(params, false).to_string(),
+                invoc.decode(&invoc.msg.params, false).to_string(),
+            );
+        }
+        if let Some(ret) = &self.ret {
+            let actual = invoc.ret.as_ref().map(|r| invoc.decode(r, true).to_string());
+            check(
+                "ret",
+                invoc.decode(ret, true).to_string(),
+                actual.unwrap_or_else(|| "none".to_owned()),
+            );
+        }
+        if let Some(expected) = &self.subinvocs {
+            let actual = &invoc.subinvocations;
+            if expected.len() != actual.len() {
+                diffs.push(format!(
+                    "{}: subinvocations expected {} was {}",
+                    path,
+                    expected.len(),
+                    actual.len()
+                ));
+            }
+            for (i, (e, a)) in expected.iter().zip(actual.iter()).enumerate() {
+                let path = if path == "root" { i.to_string() } else { format!("{}.{}", path, i) };
+                e.diff_at(&path, a, diffs);
+            }
+        }
+    }
+}
+
+fn name<M: FromPrimitive + fmt::Debug>(method: MethodNum) -> Option<String> {
+    M::from_u64(method).map(|m| format!("{:?}", m))
+}
+
+fn actor_name(typ: &Type) -> &'static str {
+    match BUILTIN_ACTORS.iter().find(|(_, t)| t == typ) {
+        Some((name, _)) => name,
+        None => "subnet",
+    }
+}
+
+/// Decodes params or return values of type `T`, naming the fields of tuple-encoded structs
+/// after their `Debug` form. `None` if the bytes don't decode to `T`.
+fn typed<T: DeserializeOwned + fmt::Debug>(bytes: &RawBytes) -> Option<Value> {
+    let value: T = bytes.deserialize().ok()?;
+    let debug = DebugValue::parse(&format!("{:#?}", value))?;
+    let mut data = bytes.bytes();
+    let rendered = render(&mut data, &debug).ok()?;
+    data.is_empty().then(|| rendered)
+}
+
+/// A value in the pretty `Debug` form of a type, which has its fields or elements on the
+/// following lines with one more level of indentation.
+struct DebugValue {
+    /// The value if it's on a single line, or its text up to the opening bracket.
+    head: String,
+    /// The fields of a struct with their names, or the elements of a tuple or list.
+    fields: Vec<(Option<String>, DebugValue)>,
+}
+
+impl DebugValue {
+    fn parse(debug: &str) -> Option<Self> {
+        let mut lines = debug.lines();
+        let value = Self::parse_lines(lines.next()?, 0, &mut lines)?;
+        lines.next().is_none().then(|| value)
+    }
+
+    /// Parses the value starting with `head`, at `indent`.
+    fn parse_lines<'a>(
+        head: &str,
+        indent: usize,
+        lines: &mut impl Iterator<Item = &'a str>,
+    ) -> Option<Self> {
+        let close = match head.chars().last() {
+            Some('{') => "}",
+            Some('(') => ")",
+            Some('[') => "]",
+            _ => return Some(DebugValue { head: head.to_owned(), fields: Vec::new() }),
+        };
+        let mut fields = Vec::new();
+        loop {
+            let line = lines.next()?.get(indent..)?;
+            if line.strip_suffix(',').unwrap_or(line) == close {
+                break;
+            }
+            let line = line.strip_prefix("    ")?;
+            let line = line.strip_suffix(',').unwrap_or(line);
+            let (name, value) = match line.split_once(": ") {
+                Some((name, value)) if close == "}" && is_ident(name) => (Some(name), value),
+                _ => (None, line),
+            };
+            let value = Self::parse_lines(value, indent + 4, lines)?;
+            fields.push((name.map(String::from), value));
+        }
+        Some(DebugValue { head: head.to_owned(), fields })
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Renders a DAG-CBOR item with the `Debug` form of the value it encodes. Structs encoded as
+/// tuples are rendered as objects with the names of their fields, and addresses and big
+/// integers, e.g. token amounts, in their text form. Other items are decoded without a schema.
+fn render(data: &mut &[u8], value: &DebugValue) -> anyhow::Result<Value> {
+    let (major, len) = read_header(&mut &data[..])?;
+    let fields = &value.fields;
+    let rendered = if major == 2 && value.head.starts_with("Address") {
+        let addr: Address = fvm_ipld_encoding::from_slice(take_item(data)?)?;
+        Value::String(addr.to_string())
+    } else if major == 2 && is_integer(&value.head) {
+        let int: BigIntDe = fvm_ipld_encoding::from_slice(take_item(data)?)?;
+        Value::String(int.0.to_string())
+    } else if value.head.ends_with('(') && fields.len() == 1 {
+        // options and newtypes are encoded as the value they hold
+        render(data, &fields[0].1)?
+    } else if major == 4 && !fields.is_empty() && len == fields.len() as u64 {
+        read_header(data)?;
+        if fields.iter().all(|(name, _)| name.is_some()) {
+            let mut map = Map::new();
+            for (name, field) in fields {
+                map.insert(name.clone().unwrap_or_default(), render(data, field)?);
+            }
+            Value::Object(map)
+        } else {
+            let items = fields.iter().map(|(_, field)| render(data, field));
+            Value::Array(items.collect::<anyhow::Result<_>>()?)
+        }
+    } else {
+        decode_item(data)?
+    };
+    Ok(rendered)
+}
+
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Takes the bytes of a CBOR byte string item, header included.
+fn take_item<'a>(data: &mut &'a [u8]) -> anyhow::Result<&'a [u8]> {
+    let start = *data;
+    let (major, len) = read_header(data)?;
+    if major != 2 {
+        return Err(anyhow!("expected a byte string"));
+    }
+    take(data, len)?;
+    Ok(&start[..start.len() - data.len()])
+}
+
+/// Decodes DAG-CBOR params or return values into JSON. Byte strings are rendered
+/// as hex, CIDs as `{"/": cid}`, and blobs that aren't DAG-CBOR as hex strings.
+fn decode(bytes: &RawBytes) -> Value {
+    if bytes.bytes().is_empty() {
+        return Value::Null;
+    }
+    let mut data = bytes.bytes();
+    match decode_item(&mut data) {
+        Ok(value) if data.is_empty() => value,
+        _ => Value::String(hex(bytes.bytes())),
+    }
+}
+
+fn decode_item(data: &mut &[u8]) -> anyhow::Result<Value> {
+    let info = data.first().map(|b| b & 0x1f).unwrap_or_default();
+    let (major, arg) = read_header(data)?;
+    let value = match major {
+        0 => json!(arg),
+        1 if arg < i64::MAX as u64 => json!(-1 - arg as i64),
+        1 => Value::String(format!("-{}", arg as u128 + 1)),
+        2 => Value::String(hex(take(data, arg)?)),
+        3 => Value::String(std::str::from_utf8(take(data, arg)?)?.to_owned()),
+        4 => Value::Array((0..arg).map(|_| decode_item(data)).collect::<anyhow::Result<_>>()?),
+        5 => {
+            let mut map = Map::new();
+            for _ in 0..arg {
+                let key = match decode_item(data)? {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                map.insert(key, decode_item(data)?);
+            }
+            Value::Object(map)
+        }
+        6 if arg == 42 => json!({ "/": read_link(data)?.to_string() }),
+        6 => decode_item(data)?,
+        _ => match (info, arg) {
+            (20, _) => Value::Bool(false),
+            (21, _) => Value::Bool(true),
+            (22, _) | (23, _) => Value::Null,
+            (26, bits) => json!(f32::from_bits(bits as u32)),
+            (27, bits) => json!(f64::from_bits(bits)),
+            _ => return Err(anyhow!("unsupported CBOR simple value {}", arg)),
+        },
+    };
+    Ok(value)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,5 +1,8 @@
 use crate::*;
 use fil_actor_cron::Method as CronMethod;
+use fil_actor_hierarchical_sca::{
+    ApplyMsgParams, Method as SCAMethod, State as SCAState, StorableMsg, MAX_NONCE,
+};
 use fil_actor_market::{
     ClientDealProposal, DealProposal, Label, Method as MarketMethod, PublishStorageDealsParams,
     PublishStorageDealsReturn,
@@ -16,8 +19,9 @@ use fil_actor_multisig::ProposeParams;
 use fil_actor_power::{
     CreateMinerParams, CreateMinerReturn, Method as PowerMethod, UpdateClaimedPowerParams,
 };
-use fil_actor_reward::Method as RewardMethod;
+use fil_actor_reward::{AwardBlockRewardParams, Method as RewardMethod};
 use fil_actor_verifreg::{Method as VerifregMethod, VerifierParams};
+use fil_actors_runtime::SCA_ACTOR_ADDR;
 use fvm_ipld_bitfield::{BitField, UnvalidatedBitField};
 use fvm_ipld_encoding::{BytesDe, Cbor, RawBytes};
 use fvm_shared::address::{Address, BLS_PUB_LEN};
@@ -166,8 +170,8 @@ pub fn precommit_sectors(
     let expiration = match exp {
         None => {
             v.get_epoch()
-                + Policy::default().min_sector_expiration
-                + max_prove_commit_duration(&Policy::default(), seal_proof).unwrap()
+                + v.policy().min_sector_expiration
+                + max_prove_commit_duration(v.policy(), seal_proof).unwrap()
         }
         Some(e) => e,
     };
@@ -330,12 +334,8 @@ pub fn advance_by_deadline_to_epoch_while_proving(
             return v.with_epoch(e);
         }
         submit_windowed_post(&v, worker, maddr, dline_info, p_idx, None);
-        v = advance_by_deadline_to_index(
-            v,
-            maddr,
-            d + 1 % &Policy::default().wpost_period_deadlines,
-        )
-        .0
+        let next_d = d + 1 % v.policy().wpost_period_deadlines;
+        v = advance_by_deadline_to_index(v, maddr, next_d).0
     }
 }
 
@@ -361,45 +361,88 @@ where
         }
         v = v.with_epoch(dline_info.last());
 
-        let res = v
-            .apply_message(
-                *SYSTEM_ACTOR_ADDR,
-                *CRON_ACTOR_ADDR,
-                TokenAmount::zero(),
-                CronMethod::EpochTick as u64,
-                RawBytes::default(),
-            )
-            .unwrap();
-        assert_eq!(ExitCode::OK, res.code);
+        apply_implicit_ok(&v, *CRON_ACTOR_ADDR, CronMethod::EpochTick as u64, RawBytes::default());
         let next = v.get_epoch() + 1;
         v = v.with_epoch(next);
     }
 }
 
+pub fn apply_implicit_ok<C: Cbor>(v: &VM, to: Address, method: MethodNum, params: C) -> RawBytes {
+    let res = v
+        .apply_implicit_message(*SYSTEM_ACTOR_ADDR, to, TokenAmount::zero(), method, params)
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    res.ret
+}
+
+/// Runs the implicit messages applied at the end of the current epoch and moves to the next one.
+///
+/// Each of `block_miners` is rewarded for a block, the pending bottom-up cross-messages
+/// are applied by the SCA, and cron is ticked.
+pub fn advance_epoch(v: VM, block_miners: &[Address]) -> VM {
+    for miner in block_miners {
+        let params = AwardBlockRewardParams {
+            miner: *miner,
+            penalty: TokenAmount::zero(),
+            gas_reward: TokenAmount::zero(),
+            win_count: 1,
+        };
+        apply_implicit_ok(&v, *REWARD_ACTOR_ADDR, RewardMethod::AwardBlockReward as u64, params);
+    }
+    for msg in pending_cross_msgs(&v) {
+        apply_implicit_ok(&v, *SCA_ACTOR_ADDR, SCAMethod::ApplyMessage as u64, msg);
+    }
+    apply_implicit_ok(&v, *CRON_ACTOR_ADDR, CronMethod::EpochTick as u64, RawBytes::default());
+    let next = v.get_epoch() + 1;
+    v.with_epoch(next)
+}
+
+/// Returns the bottom-up cross-messages committed to the SCA by child checkpoints that
+/// haven't been applied yet, in the order they need to be applied, along with the subnet
+/// they were committed from. Top-down messages are left to the VM of the subnet they are
+/// directed to.
+pub fn pending_cross_msgs(v: &VM) -> Vec<ApplyMsgParams> {
+    let st = match v.get_state::<SCAState>(*SCA_ACTOR_ADDR) {
+        Some(st) => st,
+        None => return Vec::new(),
+    };
+    let next = match st.applied_bottomup_nonce {
+        MAX_NONCE => 0,
+        nonce => nonce + 1,
+    };
+    let mut msgs = Vec::new();
+    for nonce in next..st.bottomup_nonce {
+        let meta = st.bottomup_msg_meta.get(v.store, nonce).unwrap().unwrap();
+        let cross_msgs = st.check_msg_registry.get(v.store, &meta.msgs_cid).unwrap().unwrap();
+        // bottom-up messages are applied with the nonce of their meta.
+        msgs.extend(cross_msgs.msgs.into_iter().map(|msg| ApplyMsgParams {
+            msg: StorableMsg { nonce, ..msg },
+            meta_from: Some(meta.from.clone()),
+        }));
+    }
+    msgs
+}
+
 pub fn miner_dline_info(v: &VM, m: Address) -> DeadlineInfo {
     let st = v.get_state::<MinerState>(m).unwrap();
-    new_deadline_info_from_offset_and_epoch(
-        &Policy::default(),
-        st.proving_period_start,
-        v.get_epoch(),
-    )
+    new_deadline_info_from_offset_and_epoch(v.policy(), st.proving_period_start, v.get_epoch())
 }
 
 fn sector_deadline(v: &VM, m: Address, s: SectorNumber) -> (u64, u64) {
     let st = v.get_state::<MinerState>(m).unwrap();
-    st.find_sector(&Policy::default(), v.store, s).unwrap()
+    st.find_sector(v.policy(), v.store, s).unwrap()
 }
 
 pub fn check_sector_active(v: &VM, m: Address, s: SectorNumber) -> bool {
     let (d_idx, p_idx) = sector_deadline(v, m, s);
     let st = v.get_state::<MinerState>(m).unwrap();
-    st.check_sector_active(&Policy::default(), v.store, d_idx, p_idx, s, true).unwrap()
+    st.check_sector_active(v.policy(), v.store, d_idx, p_idx, s, true).unwrap()
 }
 
 pub fn check_sector_faulty(v: &VM, m: Address, d_idx: u64, p_idx: u64, s: SectorNumber) -> bool {
     let st = v.get_state::<MinerState>(m).unwrap();
     let deadlines = st.load_deadlines(v.store).unwrap();
-    let deadline = deadlines.load_deadline(&Policy::default(), v.store, d_idx).unwrap();
+    let deadline = deadlines.load_deadline(v.policy(), v.store, d_idx).unwrap();
     let partition = deadline.load_partition(v.store, p_idx).unwrap();
     partition.faults.get(s)
 }
@@ -407,7 +450,7 @@ pub fn check_sector_faulty(v: &VM, m: Address, d_idx: u64, p_idx: u64, s: Sector
 pub fn deadline_state(v: &VM, m: Address, d_idx: u64) -> Deadline {
     let st = v.get_state::<MinerState>(m).unwrap();
     let deadlines = st.load_deadlines(v.store).unwrap();
-    deadlines.load_deadline(&Policy::default(), v.store, d_idx).unwrap()
+    deadlines.load_deadline(v.policy(), v.store, d_idx).unwrap()
 }
 
 pub fn sector_info(v: &VM, m: Address, s: SectorNumber) -> SectorOnChainInfo {
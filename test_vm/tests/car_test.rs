@@ -0,0 +1,131 @@
+use cid::Cid;
+use fil_actor_account::State as AccountState;
+use fil_actors_runtime::test_utils::{make_builtin, ACCOUNT_ACTOR_CODE_ID};
+use fil_actors_runtime::HAMT_BIT_WIDTH;
+use futures::executor::block_on;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_car::load_car;
+use fvm_ipld_encoding::{CborStore, RawBytes};
+use fvm_ipld_hamt::{BytesKey, Hamt, Sha256};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::METHOD_SEND;
+use test_vm::util::{apply_ok, create_accounts};
+use test_vm::{actor, Actor, VM};
+
+#[test]
+fn car_round_trip() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let accounts = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+
+    let mut car = Vec::new();
+    let root = v.export_car(&mut car).unwrap();
+
+    // the snapshot holds the whole state tree in a fresh store
+    let snapshot_store = MemoryBlockstore::new();
+    let snapshot = VM::load_car(&snapshot_store, car.as_slice()).unwrap();
+    assert_eq!(snapshot.checkpoint(), v.checkpoint());
+    assert_eq!(snapshot.export_car(Vec::new()).unwrap(), root);
+    let st: AccountState = snapshot.get_state(accounts[0]).unwrap();
+    assert_eq!(st.address, v.get_state::<AccountState>(accounts[0]).unwrap().address);
+
+    // messages can be run against the snapshot
+    apply_ok(
+        &snapshot,
+        accounts[0],
+        accounts[1],
+        TokenAmount::from(100u8),
+        METHOD_SEND,
+        RawBytes::default(),
+    );
+    assert_eq!(snapshot.get_actor(accounts[1]).unwrap().balance, TokenAmount::from(10_100u32));
+    assert_eq!(v.get_actor(accounts[1]).unwrap().balance, TokenAmount::from(10_000u32));
+}
+
+#[test]
+fn snapshot_codes() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let accounts = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+    let (addr, to) = (accounts[0], accounts[1]);
+
+    // an account with the legacy code CID of a real network
+    let legacy_code = make_builtin(b"fil/7/account");
+    let account = v.get_actor(addr).unwrap();
+    v.set_actor(addr, actor(legacy_code, account.head, account.call_seq_num, account.balance));
+    let mut car = Vec::new();
+    v.export_car(&mut car).unwrap();
+
+    // the test code is used to run the actor, and the legacy one is restored on export
+    let snapshot_store = MemoryBlockstore::new();
+    let snapshot = VM::load_car(&snapshot_store, car.as_slice()).unwrap();
+    assert_eq!(snapshot.get_actor(addr).unwrap().code, *ACCOUNT_ACTOR_CODE_ID);
+    apply_ok(&snapshot, addr, to, TokenAmount::from(1u8), METHOD_SEND, RawBytes::default());
+
+    let root = snapshot.export_car(Vec::new()).unwrap();
+    assert_eq!(get_exported(&snapshot_store, root, addr).code, legacy_code);
+    assert_eq!(get_exported(&snapshot_store, root, to).balance, TokenAmount::from(10_001u32));
+}
+
+#[test]
+fn fvm_snapshot() {
+    // a state tree as written by the FVM: a versioned state root, an actors HAMT
+    // with bit width 5 and accounts f0100-f0102 with legacy code CIDs.
+    let car = include_bytes!("fixtures/bitwidth5.car");
+    let store = MemoryBlockstore::new();
+    let v = VM::load_car(&store, car.as_slice()).unwrap();
+
+    let accounts: Vec<Address> = (100..=102).map(Address::new_id).collect();
+    for (i, addr) in accounts.iter().enumerate() {
+        let actor = v.get_actor(*addr).unwrap();
+        assert_eq!(actor.code, *ACCOUNT_ACTOR_CODE_ID);
+        assert_eq!(actor.call_seq_num, i as u64);
+        assert_eq!(actor.balance, TokenAmount::from(10_000 * (i as u32 + 1)));
+        let st: AccountState = v.get_state(*addr).unwrap();
+        let key = [&[1u8][..], &[100 + i as u8; 20]].concat();
+        assert_eq!(st.address, Address::from_bytes(&key).unwrap());
+    }
+
+    // the unchanged tree is exported as it was loaded
+    let fixture_store = MemoryBlockstore::new();
+    let roots = block_on(load_car(&fixture_store, car.as_slice())).unwrap();
+    let (version, _, info): (u64, Cid, Cid) = fixture_store.get_cbor(&roots[0]).unwrap().unwrap();
+    let root = v.export_car(Vec::new()).unwrap();
+    assert_eq!(root, roots[0]);
+    let actors_root = versioned_actors(&store, root, version, info);
+    assert_eq!(get_exported(&store, actors_root, accounts[0]).code, make_builtin(b"fil/7/account"));
+
+    // changes to the state tree are exported with the same layout, in a state
+    // root with the same version and info
+    let mut actor = v.get_actor(accounts[1]).unwrap();
+    actor.balance = TokenAmount::from(1u8);
+    v.set_actor(accounts[1], actor);
+    let mut car = Vec::new();
+    let root = v.export_car(&mut car).unwrap();
+    assert_ne!(root, roots[0]);
+    let actors_root = versioned_actors(&store, root, version, info);
+    assert_eq!(get_exported(&store, actors_root, accounts[1]).balance, TokenAmount::from(1u8));
+    assert_eq!(get_exported(&store, actors_root, accounts[2]).code, make_builtin(b"fil/7/account"));
+
+    // and can be loaded again
+    let snapshot_store = MemoryBlockstore::new();
+    let snapshot = VM::load_car(&snapshot_store, car.as_slice()).unwrap();
+    assert_eq!(snapshot.get_actor(accounts[1]).unwrap().balance, TokenAmount::from(1u8));
+    assert_eq!(snapshot.export_car(Vec::new()).unwrap(), root);
+}
+
+/// Actors root of a versioned state root, checking its version and info.
+fn versioned_actors(store: &MemoryBlockstore, root: Cid, version: u64, info: Cid) -> Cid {
+    let state_root: (u64, Cid, Cid) = store.get_cbor(&root).unwrap().unwrap();
+    assert_eq!((state_root.0, state_root.2), (version, info));
+    state_root.1
+}
+
+/// Actor in an exported state tree, which has the bit width of the FVM.
+fn get_exported(store: &MemoryBlockstore, root: Cid, addr: Address) -> Actor {
+    let actors =
+        Hamt::<_, Actor, BytesKey, Sha256>::load_with_bit_width(&root, store, HAMT_BIT_WIDTH)
+            .unwrap();
+    actors.get(&addr.to_bytes()).unwrap().cloned().unwrap()
+}
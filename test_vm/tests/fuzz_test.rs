@@ -0,0 +1,28 @@
+use proptest::prelude::*;
+use test_vm::fuzz::{any_op, market_op, msig_op, ops, run, sca_op};
+
+// Failing sequences are shrunk and saved to proptest-regressions/fuzz_test.txt,
+// which is replayed before generating new cases.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn sca_invariants(ops in ops(sca_op(), 20)) {
+        prop_assert_eq!(run(&ops), Ok(()));
+    }
+
+    #[test]
+    fn market_invariants(ops in ops(market_op(), 20)) {
+        prop_assert_eq!(run(&ops), Ok(()));
+    }
+
+    #[test]
+    fn multisig_invariants(ops in ops(msig_op(), 20)) {
+        prop_assert_eq!(run(&ops), Ok(()));
+    }
+
+    #[test]
+    fn mixed_invariants(ops in ops(any_op(), 40)) {
+        prop_assert_eq!(run(&ops), Ok(()));
+    }
+}
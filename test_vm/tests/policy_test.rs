@@ -0,0 +1,107 @@
+use fil_actor_power::{CreateMinerParams, Method as PowerMethod};
+use fil_actors_runtime::runtime::policy::policy_constants;
+use fil_actors_runtime::runtime::{Policy, Runtime, RuntimePolicy};
+use fil_actors_runtime::STORAGE_POWER_ACTOR_ADDR;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::BytesDe;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::{RegisteredPoStProof, RegisteredSealProof, StoragePower};
+use fvm_shared::version::NetworkVersion;
+use test_vm::util::create_accounts;
+use test_vm::VM;
+
+#[test]
+fn network_policies() {
+    // the policy of the features this crate is built with
+    let policy = Policy::from_features();
+    assert_eq!(policy.pre_commit_challenge_delay, policy_constants::PRE_COMMIT_CHALLENGE_DELAY);
+    assert_eq!(
+        policy.minimum_consensus_power,
+        StoragePower::from(policy_constants::MINIMUM_CONSENSUS_POWER)
+    );
+    assert_eq!(
+        Policy::for_network("mainnet"),
+        Some(Policy::for_features(&["sector-32g", "sector-64g"]))
+    );
+    assert_eq!(Policy::for_network("unknown"), None);
+
+    let devnet = Policy::for_network("devnet").unwrap();
+    assert_eq!(devnet.pre_commit_challenge_delay, 10);
+    assert_eq!(devnet.minimum_verified_deal_size, StoragePower::from(256));
+    assert_eq!(devnet.minimum_consensus_power, StoragePower::from(2 << 10));
+    assert_eq!(devnet.prov_collateral_percent_supply_num, 1);
+    assert_eq!(
+        devnet.valid_post_proof_type,
+        [RegisteredPoStProof::StackedDRGWindow2KiBV1, RegisteredPoStProof::StackedDRGWindow8MiBV1]
+            .into()
+    );
+    assert_eq!(
+        devnet.valid_pre_commit_proof_type,
+        [RegisteredSealProof::StackedDRG2KiBV1P1, RegisteredSealProof::StackedDRG8MiBV1P1].into()
+    );
+
+    // the testing network also allows every sector size and drops the deal collateral
+    let testing = Policy::for_network("testing").unwrap();
+    assert_eq!(testing.prov_collateral_percent_supply_num, 0);
+    assert_eq!(testing.valid_post_proof_type.len(), 5);
+    assert_eq!(testing.valid_pre_commit_proof_type.len(), 5);
+    assert_eq!(
+        Policy { prov_collateral_percent_supply_num: 1, ..testing.clone() },
+        Policy {
+            valid_post_proof_type: testing.valid_post_proof_type.clone(),
+            valid_pre_commit_proof_type: testing.valid_pre_commit_proof_type.clone(),
+            ..devnet
+        }
+    );
+}
+
+#[test]
+fn create_miner_with_policy() {
+    let cases = [
+        (Policy::for_network("mainnet").unwrap(), ExitCode::USR_ILLEGAL_ARGUMENT),
+        (Policy::for_network("devnet").unwrap(), ExitCode::OK),
+        (Policy::for_network("testing").unwrap(), ExitCode::OK),
+    ];
+    for (policy, code) in cases {
+        let store = MemoryBlockstore::new();
+        // the policy is kept when moving to a new epoch
+        let v = VM::new_with_singletons(&store).with_policy(policy.clone()).with_epoch(10);
+        let owner = create_accounts(&v, 1, TokenAmount::from(10_000u32))[0];
+        assert_eq!(&policy, v.runtime(owner, *STORAGE_POWER_ACTOR_ADDR).policy());
+
+        // 2KiB sectors are only allowed by the devnet and testing policies
+        let params = CreateMinerParams {
+            owner,
+            worker: owner,
+            window_post_proof_type: RegisteredPoStProof::StackedDRGWindow2KiBV1,
+            peer: b"miner".to_vec(),
+            multiaddrs: vec![BytesDe(b"multiaddr".to_vec())],
+        };
+        let res = v
+            .apply_message(
+                owner,
+                *STORAGE_POWER_ACTOR_ADDR,
+                TokenAmount::from(0u8),
+                PowerMethod::CreateMiner as u64,
+                params,
+            )
+            .unwrap();
+        assert_eq!(code, res.code);
+    }
+}
+
+#[test]
+fn network_version() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    assert_eq!(NetworkVersion::V16, v.network_version());
+    let v = v.with_network_version(NetworkVersion::V15).with_epoch(10);
+    assert_eq!(NetworkVersion::V15, v.network_version());
+
+    // invoked actors see the network version of the VM
+    let owner = create_accounts(&v, 1, TokenAmount::from(10_000u32))[0];
+    let rt = v.runtime(owner, *STORAGE_POWER_ACTOR_ADDR);
+    assert_eq!(NetworkVersion::V15, rt.network_version());
+    assert_eq!(10, rt.curr_epoch());
+}
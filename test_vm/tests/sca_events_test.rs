@@ -0,0 +1,50 @@
+use fil_actor_account::State as AccountState;
+use fil_actor_hierarchical_sca::{events, CrossMsgParams, Method as SCAMethod, StorableMsg};
+use fil_actors_runtime::SCA_ACTOR_ADDR;
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+use test_vm::fuzz::{apply, setup, Op, ScaOp};
+use test_vm::EmittedEvent;
+
+#[test]
+fn send_cross_events() {
+    let store = MemoryBlockstore::new();
+    let (v, env) = setup(&store);
+    let v = apply(v, &env, &Op::Sca(ScaOp::Register { subnet: 0, stake: 10 })).unwrap();
+    let sender = env.accounts[0];
+    let sender_key = v.get_state::<AccountState>(sender).unwrap().address;
+    let dest = SubnetID::new(&env.network, env.subnets[0]);
+    let value = TokenAmount::from(10u64.pow(18));
+
+    let msg = StorableMsg {
+        to: env.accounts[1],
+        method: METHOD_SEND,
+        value: value.clone(),
+        ..Default::default()
+    };
+    let params = CrossMsgParams { msg: msg.clone(), destination: dest.clone() };
+    let ret = v
+        .apply_message(sender, *SCA_ACTOR_ADDR, value.clone(), SCAMethod::SendCross as u64, params)
+        .unwrap();
+    assert_eq!(ret.code, ExitCode::OK);
+
+    // the cross-message is committed top-down with the first nonce of the subnet
+    let expected_msg = StorableMsg {
+        from: Address::new_hierarchical(&env.network, &sender_key).unwrap(),
+        to: Address::new_hierarchical(&dest, &env.accounts[1]).unwrap(),
+        ..msg.clone()
+    };
+    let event = events::send_cross(&dest, &expected_msg).unwrap();
+    assert_eq!(ret.events, vec![EmittedEvent { emitter: *SCA_ACTOR_ADDR, event }]);
+
+    // events of aborted messages are dropped
+    let params = CrossMsgParams { msg, destination: env.network.clone() };
+    let ret = v
+        .apply_message(sender, *SCA_ACTOR_ADDR, value, SCAMethod::SendCross as u64, params)
+        .unwrap();
+    assert_eq!(ret.code, ExitCode::USR_ILLEGAL_ARGUMENT);
+    assert!(ret.events.is_empty());
+}
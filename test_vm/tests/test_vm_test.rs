@@ -1,12 +1,25 @@
 use fil_actor_account::State as AccountState;
-use fil_actors_runtime::test_utils::{make_builtin, ACCOUNT_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID};
+use fil_actor_cron::Method as CronMethod;
+use fil_actor_hierarchical_sca::{
+    ConstructorParams, CrossMsgMeta, CrossMsgs, State as SCAState, StorableMsg,
+};
+use fil_actors_runtime::test_utils::{
+    make_builtin, ACCOUNT_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID, SCA_ACTOR_CODE_ID,
+};
+use fil_actors_runtime::{CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR, SCA_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
 use fvm_ipld_blockstore::MemoryBlockstore;
 use fvm_ipld_encoding::RawBytes;
-use fvm_shared::address::Address;
+use fvm_shared::address::subnet::ROOTNET_ID;
+use fvm_shared::address::{Address, SubnetID};
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
-use fvm_shared::METHOD_SEND;
-use test_vm::util::pk_addrs_from;
+use fvm_shared::sector::RegisteredPoStProof;
+use fvm_shared::{MethodNum, METHOD_SEND};
+use num_traits::{Signed, Zero};
+use test_vm::gas::PriceList;
+use test_vm::util::{
+    advance_epoch, create_accounts, create_miner, pending_cross_msgs, pk_addrs_from,
+};
 use test_vm::{actor, FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR, VM};
 
 #[test]
@@ -121,3 +134,157 @@ fn test_pk_gen() {
     let first_addr_seeded_six = addrs[0];
     assert_ne!(second_addr_seeded_five, first_addr_seeded_six);
 }
+
+#[test]
+fn test_gas_accounting() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+    let (a, b) = (addrs[0], addrs[1]);
+    let prices = PriceList::default();
+
+    // a plain send is charged for the message and the transfer
+    let value = TokenAmount::from(100u32);
+    let res = v.apply_message(a, b, value.clone(), METHOD_SEND, RawBytes::default()).unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    let params_len = RawBytes::serialize(RawBytes::default()).unwrap().len();
+    let send_gas =
+        prices.on_chain_message(params_len) + prices.on_method_invocation(&value, METHOD_SEND);
+    assert_eq!(send_gas, res.gas_used);
+
+    // invoking a method also pays for the state it reads
+    let pubkey_method = fil_actor_account::Method::PubkeyAddress as MethodNum;
+    let res = v.apply_message(a, b, TokenAmount::from(0u8), pubkey_method, RawBytes::default());
+    let call_gas = res.unwrap().gas_used;
+    assert!(call_gas > send_gas - prices.send_transfer_funds);
+
+    // the message aborts when it goes over its gas limit
+    let res = v
+        .apply_message_with_gas_limit(
+            a,
+            b,
+            value.clone(),
+            METHOD_SEND,
+            RawBytes::default(),
+            send_gas - 1,
+        )
+        .unwrap();
+    assert_eq!(ExitCode::SYS_OUT_OF_GAS, res.code);
+    assert_eq!(send_gas - 1, res.gas_used);
+    assert_eq!(TokenAmount::from(10_100u32), v.get_actor(b).unwrap().balance);
+    let res = v
+        .apply_message_with_gas_limit(a, b, value, METHOD_SEND, RawBytes::default(), send_gas)
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    assert_eq!(TokenAmount::from(10_200u32), v.get_actor(b).unwrap().balance);
+
+    // gas prices can be replaced
+    let store = MemoryBlockstore::new();
+    let prices = PriceList { ipld_get_base: prices.ipld_get_base * 10, ..prices };
+    let v = VM::new_with_singletons(&store).with_price_list(prices);
+    let addrs = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+    let res = v.apply_message(
+        addrs[0],
+        addrs[1],
+        TokenAmount::from(0u8),
+        pubkey_method,
+        RawBytes::default(),
+    );
+    assert!(res.unwrap().gas_used > call_gas);
+}
+
+#[test]
+fn test_implicit_messages() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let to = create_accounts(&v, 1, TokenAmount::from(0u8))[0];
+
+    // the SCA holds a bottom-up message from a child subnet waiting to be applied
+    let value = TokenAmount::from(100u32);
+    let child = SubnetID::new(&ROOTNET_ID, Address::new_id(5000));
+    let msg = StorableMsg {
+        from: Address::new_hierarchical(&child, &Address::new_id(1001)).unwrap(),
+        to: Address::new_hierarchical(&ROOTNET_ID, &to).unwrap(),
+        method: METHOD_SEND,
+        value: value.clone(),
+        ..Default::default()
+    };
+    let params = ConstructorParams {
+        network_name: ROOTNET_ID.to_string(),
+        checkpoint_period: 10,
+        escape_hatch_periods: 10,
+    };
+    let mut st = SCAState::new(&store, params).unwrap();
+    let cross_msgs = CrossMsgs { msgs: vec![msg], metas: Vec::new() };
+    let meta = CrossMsgMeta {
+        from: child,
+        to: ROOTNET_ID.clone(),
+        msgs_cid: v.put_store(&cross_msgs).into(),
+        nonce: 0,
+        value: value.clone(),
+        assets: Vec::new(),
+    };
+    st.check_msg_registry.set(&store, &meta.msgs_cid, cross_msgs).unwrap();
+    st.bottomup_msg_meta.set(&store, 0, meta).unwrap();
+    st.bottomup_nonce = 1;
+    v.set_actor(*SCA_ACTOR_ADDR, actor(*SCA_ACTOR_CODE_ID, v.put_store(&st), 0, value.clone()));
+    assert_eq!(1, pending_cross_msgs(&v).len());
+
+    // implicit messages don't bump the nonce of the sender nor use gas
+    let call_seq = v.get_actor(*SYSTEM_ACTOR_ADDR).unwrap().call_seq_num;
+    let res = v
+        .apply_implicit_message(
+            *SYSTEM_ACTOR_ADDR,
+            *CRON_ACTOR_ADDR,
+            TokenAmount::from(0u8),
+            CronMethod::EpochTick as u64,
+            RawBytes::default(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::OK, res.code);
+    assert_eq!(0, res.gas_used);
+    assert_eq!(call_seq, v.get_actor(*SYSTEM_ACTOR_ADDR).unwrap().call_seq_num);
+
+    // advancing the epoch applies the pending cross-message
+    let v = advance_epoch(v, &[]);
+    assert_eq!(1, v.get_epoch());
+    assert_eq!(value, v.get_actor(to).unwrap().balance);
+    assert!(pending_cross_msgs(&v).is_empty());
+    let st: SCAState = v.get_state(*SCA_ACTOR_ADDR).unwrap();
+    assert_eq!(0, st.applied_bottomup_nonce);
+}
+
+#[test]
+fn advance_epoch_rewards_block_miners() {
+    let store = MemoryBlockstore::new();
+    let mut v = VM::new_with_singletons(&store);
+    let addrs = create_accounts(&v, 1, TokenAmount::from(10_000u32));
+    let (maddr, _) = create_miner(
+        &mut v,
+        addrs[0],
+        addrs[0],
+        RegisteredPoStProof::StackedDRGWindow32GiBV1,
+        TokenAmount::zero(),
+    );
+    let miner_balance = v.get_actor(maddr).unwrap().balance;
+    let reward_balance = v.get_actor(*REWARD_ACTOR_ADDR).unwrap().balance;
+
+    // the block reward moves from the reward actor to the miner, where it vests
+    let v = advance_epoch(v, &[maddr]);
+    let reward = v.get_actor(maddr).unwrap().balance - miner_balance;
+    assert!(reward.is_positive());
+    assert_eq!(reward_balance - &reward, v.get_actor(*REWARD_ACTOR_ADDR).unwrap().balance);
+    assert_eq!(reward, v.get_miner_balance(maddr).vesting_balance);
+
+    // an implicit message from an address that isn't in the state tree is still applied
+    let res = v
+        .apply_implicit_message(
+            Address::new_id(9999),
+            *CRON_ACTOR_ADDR,
+            TokenAmount::zero(),
+            CronMethod::EpochTick as u64,
+            RawBytes::default(),
+        )
+        .unwrap();
+    assert_eq!(ExitCode::USR_FORBIDDEN, res.code);
+}
@@ -0,0 +1,227 @@
+use fil_actor_hierarchical_sca::{
+    ApplyMsgParams, ConstructorParams as SCAConstructorParams, CrossMsgParams, Method as SCAMethod,
+    State as SCAState, StorableMsg,
+};
+use fil_actor_init::{ExecParams, ExecReturn};
+use fil_actor_multisig::ProposeParams;
+use fil_actors_runtime::test_utils::{MULTISIG_ACTOR_CODE_ID, SCA_ACTOR_CODE_ID};
+use fil_actors_runtime::{
+    INIT_ACTOR_ADDR, SCA_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+};
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::subnet::ROOTNET_ID;
+use fvm_shared::address::{Address, SubnetID};
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{METHOD_CONSTRUCTOR, METHOD_SEND};
+use serde_json::json;
+use test_vm::util::{apply_ok, create_accounts, pk_addrs_from};
+use test_vm::{actor, ExpectInvocation, VM};
+
+#[test]
+fn trace_json_and_tree() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let sender = create_accounts(&v, 1, TokenAmount::from(10_000u32))[0];
+    let receiver = pk_addrs_from(42, 1)[0];
+
+    // sending to a new address creates its account
+    v.take_invocations();
+    apply_ok(&v, sender, receiver, TokenAmount::from(100u8), METHOD_SEND, RawBytes::default());
+    let invocs = v.take_invocations();
+    let receiver_id = v.normalize_address(&receiver).unwrap();
+
+    let trace = invocs[0].to_json();
+    assert_eq!(trace.to, receiver_id.to_string());
+    assert_eq!(trace.actor.as_deref(), Some("account"));
+    assert_eq!(trace.method_name.as_deref(), Some("Send"));
+    assert_eq!(trace.value, "100");
+    assert_eq!(trace.params, json!(null));
+    assert_eq!(trace.code, None);
+    let constructor = &trace.subinvocations[0];
+    assert_eq!(constructor.from, SYSTEM_ACTOR_ADDR.to_string());
+    assert_eq!(constructor.method_name.as_deref(), Some("Constructor"));
+    // params are decoded with the types of the method
+    assert_eq!(constructor.params, json!(receiver.to_string()));
+
+    // the trace serializes to its JSON form
+    let value = serde_json::to_value(&invocs[0]).unwrap();
+    assert_eq!(serde_json::from_value::<test_vm::trace::TraceJson>(value).unwrap(), trace);
+
+    assert_eq!(
+        invocs[0].to_string(),
+        format!(
+            "{} -> {} [account] Send(0) value 100 => ok\n  {} -> {} [account] Constructor(1) => ok\n",
+            sender, receiver_id, *SYSTEM_ACTOR_ADDR, receiver_id
+        )
+    );
+
+    // differences with the expected invocations are reported by path
+    let expect = ExpectInvocation {
+        to: receiver_id,
+        method: METHOD_SEND,
+        value: Some(TokenAmount::from(100u8)),
+        subinvocs: Some(vec![ExpectInvocation {
+            to: receiver_id,
+            method: METHOD_CONSTRUCTOR,
+            from: Some(*SYSTEM_ACTOR_ADDR),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    assert!(expect.diff(&invocs[0]).is_empty());
+    let expect = ExpectInvocation {
+        to: receiver_id,
+        method: METHOD_SEND,
+        code: Some(ExitCode::USR_FORBIDDEN),
+        subinvocs: Some(vec![ExpectInvocation {
+            to: Address::new_id(1),
+            method: METHOD_CONSTRUCTOR,
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    assert_eq!(
+        expect.diff(&invocs[0]),
+        vec![
+            format!("root: code expected {} was {}", ExitCode::USR_FORBIDDEN, ExitCode::OK),
+            format!("0: to expected {} was {}", Address::new_id(1), receiver_id),
+        ]
+    );
+}
+
+#[test]
+fn trace_method_types() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let signers = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+
+    // init exec params and return value
+    let ctor_params = RawBytes::serialize(fil_actor_multisig::ConstructorParams {
+        signers: signers.clone(),
+        num_approvals_threshold: 2,
+        unlock_duration: 0,
+        start_epoch: 0,
+    })
+    .unwrap();
+    v.take_invocations();
+    let ret: ExecReturn = apply_ok(
+        &v,
+        signers[0],
+        *INIT_ACTOR_ADDR,
+        TokenAmount::zero(),
+        fil_actor_init::Method::Exec as u64,
+        ExecParams { code_cid: *MULTISIG_ACTOR_CODE_ID, constructor_params: ctor_params.clone() },
+    )
+    .deserialize()
+    .unwrap();
+    let trace = v.take_invocations()[0].to_json();
+    assert_eq!(trace.method_name.as_deref(), Some("Exec"));
+    let ctor_hex: String = ctor_params.bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(
+        trace.params,
+        json!({
+            "code_cid": { "/": MULTISIG_ACTOR_CODE_ID.to_string() },
+            "constructor_params": ctor_hex,
+        })
+    );
+    assert_eq!(
+        trace.ret,
+        Some(json!({
+            "id_address": ret.id_address.to_string(),
+            "robust_address": ret.robust_address.to_string(),
+        }))
+    );
+
+    // multisig proposals, with token amounts in their text form
+    let propose = ProposeParams {
+        to: signers[1],
+        value: TokenAmount::zero(),
+        method: METHOD_SEND,
+        params: RawBytes::default(),
+    };
+    apply_ok(
+        &v,
+        signers[0],
+        ret.id_address,
+        TokenAmount::zero(),
+        fil_actor_multisig::Method::Propose as u64,
+        propose,
+    );
+    let trace = v.take_invocations()[0].to_json();
+    assert_eq!(
+        trace.params,
+        json!({ "to": signers[1].to_string(), "value": "0", "method": 0, "params": "" })
+    );
+    assert_eq!(trace.ret, Some(json!({ "txn_id": 0, "applied": false, "code": 0, "ret": "" })));
+}
+
+#[test]
+fn trace_sca_and_market_types() {
+    let store = MemoryBlockstore::new();
+    let v = VM::new_with_singletons(&store);
+    let params = SCAConstructorParams {
+        network_name: ROOTNET_ID.to_string(),
+        checkpoint_period: 10,
+        escape_hatch_periods: 10,
+    };
+    let st = SCAState::new(&store, params).unwrap();
+    v.set_actor(
+        *SCA_ACTOR_ADDR,
+        actor(*SCA_ACTOR_CODE_ID, v.put_store(&st), 0, TokenAmount::zero()),
+    );
+    let accounts = create_accounts(&v, 2, TokenAmount::from(10_000u32));
+
+    // params are decoded whether or not the call succeeds, with nested structs
+    let msg = StorableMsg {
+        to: accounts[1],
+        method: METHOD_SEND,
+        value: TokenAmount::from(10u8),
+        ..Default::default()
+    };
+    let destination = SubnetID::new(&ROOTNET_ID, Address::new_id(1000));
+    let params = CrossMsgParams { msg: msg.clone(), destination };
+    v.take_invocations();
+    v.apply_message(
+        accounts[0],
+        *SCA_ACTOR_ADDR,
+        msg.value.clone(),
+        SCAMethod::SendCross as u64,
+        params,
+    )
+    .unwrap();
+    let trace = v.take_invocations()[0].to_json();
+    assert_eq!(trace.method_name.as_deref(), Some("SendCross"));
+    assert_eq!(trace.params["msg"]["to"], json!(accounts[1].to_string()));
+    assert_eq!(trace.params["msg"]["method"], json!(0));
+    assert_eq!(trace.params["msg"]["value"], json!("10"));
+    assert!(trace.params.get("destination").is_some());
+
+    // and options
+    let params = ApplyMsgParams { msg, meta_from: None };
+    v.apply_message(
+        accounts[0],
+        *SCA_ACTOR_ADDR,
+        TokenAmount::zero(),
+        SCAMethod::ApplyMessage as u64,
+        params,
+    )
+    .unwrap();
+    let trace = v.take_invocations()[0].to_json();
+    assert_eq!(trace.params["msg"]["to"], json!(accounts[1].to_string()));
+    assert_eq!(trace.params["meta_from"], json!(null));
+
+    // market balances
+    apply_ok(
+        &v,
+        accounts[0],
+        *STORAGE_MARKET_ACTOR_ADDR,
+        TokenAmount::from(100u8),
+        fil_actor_market::Method::AddBalance as u64,
+        accounts[0],
+    );
+    let trace = v.take_invocations()[0].to_json();
+    assert_eq!(trace.params, json!(accounts[0].to_string()));
+}